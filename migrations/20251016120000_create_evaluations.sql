@@ -0,0 +1,14 @@
+-- One row per eligibility determination, written when DATABASE_URL is configured
+CREATE TABLE IF NOT EXISTS evaluations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    -- RFC 3339 timestamp of the end of the evaluation
+    evaluated_at TEXT NOT NULL,
+    -- Normalized input sent to the rule engine, as JSON
+    input TEXT NOT NULL,
+    case_letter TEXT NOT NULL,
+    monthly_benefit INTEGER NOT NULL,
+    potentially_eligible INTEGER NOT NULL,
+    rules_version TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS evaluations_evaluated_at ON evaluations (evaluated_at);
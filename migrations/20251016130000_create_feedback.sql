@@ -0,0 +1,16 @@
+-- Identifier returned to clients with each evaluation, quoted back when sending feedback
+ALTER TABLE evaluations ADD COLUMN evaluation_id TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS evaluations_evaluation_id ON evaluations (evaluation_id);
+
+-- Real-world outcome of an evaluated application, reported through record_eligibility_feedback
+CREATE TABLE IF NOT EXISTS feedback (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    evaluation_id TEXT NOT NULL,
+    -- RFC 3339 timestamp of the submission
+    recorded_at TEXT NOT NULL,
+    outcome TEXT NOT NULL,
+    notes TEXT
+);
+
+CREATE INDEX IF NOT EXISTS feedback_evaluation_id ON feedback (evaluation_id);
@@ -0,0 +1,2 @@
+-- Tenant whose rules produced the evaluation, NULL for the shared rules
+ALTER TABLE evaluations ADD COLUMN tenant_id TEXT;
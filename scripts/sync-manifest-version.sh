@@ -108,13 +108,17 @@ if [ -f "$RUST_FILE" ]; then
     if [[ "$OSTYPE" == "darwin"* ]]; then
         # macOS sed syntax
         sed -i '' "s/std::env::var(\"APP_NAME\")\.unwrap_or_else(|_| \"[^\"]*\"\.to_string())/\"$APP_NAME\".to_string()/g" "$RUST_FILE"
+        sed -i '' "s/pub const SERVER_NAME: \&str = \"[^\"]*\";/pub const SERVER_NAME: \&str = \"$APP_NAME\";/g" "$RUST_FILE"
         sed -i '' "s/std::env::var(\"VERSION\")\.unwrap_or_else(|_| \"[^\"]*\"\.to_string())/\"$ENV_VERSION\".to_string()/g" "$RUST_FILE"
         sed -i '' "s/std::env::var(\"TITLE\")\.unwrap_or_else(|_| \"[^\"]*\"\.to_string())/\"$TITLE\".to_string()/g" "$RUST_FILE"
+        sed -i '' "s/pub const SERVER_TITLE: \&str = \"[^\"]*\";/pub const SERVER_TITLE: \&str = \"$TITLE\";/g" "$RUST_FILE"
     else
         # Linux sed syntax
         sed -i "s/std::env::var(\"APP_NAME\")\.unwrap_or_else(|_| \"[^\"]*\"\.to_string())/\"$APP_NAME\".to_string()/g" "$RUST_FILE"
+        sed -i "s/pub const SERVER_NAME: \&str = \"[^\"]*\";/pub const SERVER_NAME: \&str = \"$APP_NAME\";/g" "$RUST_FILE"
         sed -i "s/std::env::var(\"VERSION\")\.unwrap_or_else(|_| \"[^\"]*\"\.to_string())/\"$ENV_VERSION\".to_string()/g" "$RUST_FILE"
         sed -i "s/std::env::var(\"TITLE\")\.unwrap_or_else(|_| \"[^\"]*\"\.to_string())/\"$TITLE\".to_string()/g" "$RUST_FILE"
+        sed -i "s/pub const SERVER_TITLE: \&str = \"[^\"]*\";/pub const SERVER_TITLE: \&str = \"$TITLE\";/g" "$RUST_FILE"
     fi
     
     echo "✅ Updated $RUST_FILE with .env values"
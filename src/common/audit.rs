@@ -0,0 +1,293 @@
+use once_cell::sync::OnceCell;
+#[cfg(feature = "audit")]
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+#[cfg(feature = "audit")]
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+
+use super::eligibility_engine::{UnpaidLeaveInput, UnpaidLeaveResponse};
+use super::metrics::EligibilityMetrics;
+
+/// SQLite database every evaluation is written to, e.g. `sqlite:///var/lib/eligibility/audit.db`.
+/// Evaluations are not persisted when unset
+pub const DATABASE_URL_ENV: &str = "DATABASE_URL";
+
+/// Audit log opened at startup from `DATABASE_URL`
+static AUDIT_LOG: OnceCell<AuditLog> = OnceCell::new();
+
+/// Opens the audit log when `DATABASE_URL` is set, creating the database and applying the
+/// migrations as needed. An audit log that was asked for but cannot be opened, or was not
+/// compiled in with the `audit` feature, is a startup error
+pub async fn init() -> anyhow::Result<()> {
+    let url = match std::env::var(DATABASE_URL_ENV) {
+        Ok(url) if !url.trim().is_empty() => url,
+        _ => return Ok(()),
+    };
+    let audit_log = AuditLog::connect(url.trim()).await?;
+    tracing::info!("Recording evaluations to the audit log");
+    let _ = AUDIT_LOG.set(audit_log);
+    Ok(())
+}
+
+/// Audit log opened by `init`, `None` when persistence is not configured
+pub fn current() -> Option<AuditLog> {
+    AUDIT_LOG.get().cloned()
+}
+
+/// Append-only record of evaluations, stored in the `evaluations` table
+#[cfg(feature = "audit")]
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    pool: SqlitePool,
+}
+
+#[cfg(feature = "audit")]
+impl AuditLog {
+    /// Connects to the SQLite database at `url` and applies the pending migrations
+    pub async fn connect(url: &str) -> anyhow::Result<Self> {
+        let options = SqliteConnectOptions::from_str(url)
+            .map_err(|e| anyhow::anyhow!("Invalid {} '{}': {}", DATABASE_URL_ENV, url, e))?
+            .create_if_missing(true);
+        let pool = SqlitePoolOptions::new()
+            .connect_with(options)
+            .await
+            .map_err(|e| anyhow::anyhow!("Cannot open the audit database '{}': {}", url, e))?;
+        sqlx::migrate!()
+            .run(&pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Cannot apply the audit database migrations: {}", e))?;
+        Ok(Self { pool })
+    }
+
+    /// Writes the evaluation of `input` in the background. A failed write is logged and counted
+    /// in `metrics`, it never affects the evaluation itself
+    pub fn record(&self, input: &UnpaidLeaveInput, response: &UnpaidLeaveResponse, metrics: Arc<EligibilityMetrics>) -> JoinHandle<()> {
+        let pool = self.pool.clone();
+        let evaluated_at = chrono::Utc::now().to_rfc3339();
+        let evaluation_id = response.evaluation_id.clone();
+        let input = serde_json::to_string(input).unwrap_or_default();
+        let case = response.output.case.clone();
+        let monthly_benefit = response.output.monthly_benefit;
+        let potentially_eligible = response.output.potentially_eligible;
+        let rules_version = response.rules_version.clone();
+        let tenant_id = response.tenant_id.clone();
+
+        tokio::spawn(async move {
+            let result = sqlx::query(
+                "INSERT INTO evaluations (evaluation_id, evaluated_at, input, case_letter, monthly_benefit, potentially_eligible, rules_version, tenant_id) \
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(evaluation_id)
+            .bind(evaluated_at)
+            .bind(input)
+            .bind(case)
+            .bind(monthly_benefit)
+            .bind(potentially_eligible)
+            .bind(rules_version)
+            .bind(tenant_id)
+            .execute(&pool)
+            .await;
+
+            if let Err(e) = result {
+                tracing::warn!(error = %e, "Failed to write the evaluation to the audit log");
+                metrics.increment_audit_failures();
+            }
+        })
+    }
+
+    /// Stores the real-world `outcome` of the evaluation `evaluation_id`
+    pub async fn record_feedback(&self, evaluation_id: &str, outcome: &str, notes: Option<&str>) -> anyhow::Result<()> {
+        sqlx::query("INSERT INTO feedback (evaluation_id, recorded_at, outcome, notes) VALUES (?, ?, ?, ?)")
+            .bind(evaluation_id)
+            .bind(chrono::Utc::now().to_rfc3339())
+            .bind(outcome)
+            .bind(notes)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Stand-in used when the `audit` feature is disabled. It has no values, `connect` always fails
+#[cfg(not(feature = "audit"))]
+#[derive(Debug, Clone)]
+pub enum AuditLog {}
+
+#[cfg(not(feature = "audit"))]
+impl AuditLog {
+    pub async fn connect(_url: &str) -> anyhow::Result<Self> {
+        anyhow::bail!("{} is set but this server was built without the audit feature", DATABASE_URL_ENV)
+    }
+
+    pub fn record(&self, _input: &UnpaidLeaveInput, _response: &UnpaidLeaveResponse, _metrics: Arc<EligibilityMetrics>) -> JoinHandle<()> {
+        match *self {}
+    }
+
+    pub async fn record_feedback(&self, _evaluation_id: &str, _outcome: &str, _notes: Option<&str>) -> anyhow::Result<()> {
+        match *self {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::eligibility_engine::{EligibilityEngine, EligibilityFeedbackParams};
+    #[cfg(feature = "audit")]
+    use crate::common::eligibility_engine::{direct_params, Ruleset, RulesetRegistry, TenantRegistry};
+
+    #[cfg(feature = "audit")]
+    async fn temp_audit_log() -> (AuditLog, std::path::PathBuf) {
+        let path = std::env::temp_dir().join(format!("eligibility-audit-{}.db", uuid::Uuid::new_v4()));
+        let audit_log = AuditLog::connect(&format!("sqlite://{}", path.display())).await.unwrap();
+        (audit_log, path)
+    }
+
+    #[cfg(feature = "audit")]
+    #[tokio::test]
+    async fn test_evaluations_are_recorded() {
+        let (audit_log, path) = temp_audit_log().await;
+        let metrics = Arc::new(EligibilityMetrics::new());
+        let eligibility_engine = EligibilityEngine::new()
+            .with_metrics(metrics.clone())
+            .with_audit_log(Some(audit_log.clone()));
+
+        let response = eligibility_engine.evaluate(direct_params("Mother", "illness")).await.unwrap();
+
+        // The write is not awaited by the evaluation
+        let mut rows = Vec::new();
+        for _ in 0..50 {
+            rows = sqlx::query_as::<_, (String, String, String, i32, bool, String)>(
+                "SELECT evaluation_id, input, case_letter, monthly_benefit, potentially_eligible, rules_version FROM evaluations",
+            )
+            .fetch_all(&audit_log.pool)
+            .await
+            .unwrap();
+            if !rows.is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+
+        let (evaluation_id, input, case, monthly_benefit, potentially_eligible, rules_version) = &rows[0];
+        assert_eq!(Some(evaluation_id), response.evaluation_id.as_ref());
+        let input: UnpaidLeaveInput = serde_json::from_str(input).unwrap();
+        assert_eq!(input.relationship, "mother");
+        assert_eq!(case, &response.output.case);
+        assert_eq!(*monthly_benefit, response.output.monthly_benefit);
+        assert!(potentially_eligible);
+        assert_eq!(rules_version, &response.rules_version);
+        #[cfg(feature = "metrics")]
+        assert_eq!(metrics.audit_failures_total.get(), 0.0);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[cfg(feature = "audit")]
+    #[tokio::test]
+    async fn test_failed_write_does_not_fail_the_evaluation() {
+        let (audit_log, path) = temp_audit_log().await;
+        audit_log.pool.close().await;
+        let metrics = Arc::new(EligibilityMetrics::new());
+        let eligibility_engine = EligibilityEngine::new()
+            .with_metrics(metrics.clone())
+            .with_audit_log(Some(audit_log.clone()));
+
+        let response = eligibility_engine.evaluate(direct_params("Mother", "illness")).await.unwrap();
+        assert_eq!(response.output.case, "A");
+
+        audit_log.record(&direct_params("Mother", "illness").input, &response, metrics.clone()).await.unwrap();
+        #[cfg(feature = "metrics")]
+        assert!(metrics.audit_failures_total.get() >= 1.0);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[cfg(feature = "audit")]
+    #[tokio::test]
+    async fn test_tenant_is_recorded() {
+        let (audit_log, path) = temp_audit_log().await;
+        let rules = include_str!("unpaid-leave-assistance-2025.json");
+        let ruleset = Ruleset::from_json("acme-rules", chrono::NaiveDate::from_ymd_opt(2025, 4, 1).unwrap(), rules).unwrap();
+        let tenants = TenantRegistry::new().with_tenant("acme", Arc::new(RulesetRegistry::new().with_ruleset(ruleset)));
+        let eligibility_engine = EligibilityEngine::new()
+            .with_tenants(Arc::new(tenants))
+            .with_audit_log(Some(audit_log.clone()));
+
+        let mut tenant_params = direct_params("Mother", "illness");
+        tenant_params.tenant_id = Some("acme".to_string());
+        eligibility_engine.evaluate(tenant_params).await.unwrap();
+        eligibility_engine.evaluate(direct_params("Mother", "illness")).await.unwrap();
+
+        let mut rows = Vec::new();
+        for _ in 0..50 {
+            rows = sqlx::query_as::<_, (String, Option<String>)>("SELECT rules_version, tenant_id FROM evaluations ORDER BY rules_version")
+                .fetch_all(&audit_log.pool)
+                .await
+                .unwrap();
+            if rows.len() == 2 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert_eq!(rows, vec![
+            ("acme-rules".to_string(), Some("acme".to_string())),
+            (crate::common::eligibility_engine::RULES_VERSION.to_string(), None),
+        ]);
+        let _ = std::fs::remove_file(path);
+    }
+
+    fn feedback(evaluation_id: &str, outcome: &str) -> EligibilityFeedbackParams {
+        EligibilityFeedbackParams {
+            evaluation_id: evaluation_id.to_string(),
+            outcome: outcome.to_string(),
+            notes: Some("Granted in full".to_string()),
+        }
+    }
+
+    #[cfg(feature = "audit")]
+    #[tokio::test]
+    async fn test_feedback_is_recorded() {
+        let (audit_log, path) = temp_audit_log().await;
+        let metrics = Arc::new(EligibilityMetrics::new());
+        let eligibility_engine = EligibilityEngine::new()
+            .with_metrics(metrics.clone())
+            .with_audit_log(Some(audit_log.clone()));
+        let evaluation_id = eligibility_engine.evaluate(direct_params("Mother", "illness")).await.unwrap().evaluation_id.unwrap();
+
+        let response = eligibility_engine
+            .record_feedback(feedback(&evaluation_id.to_uppercase(), " Approved "))
+            .await
+            .unwrap();
+        assert!(response.recorded);
+        assert_eq!(response.evaluation_id, evaluation_id);
+
+        let rows = sqlx::query_as::<_, (String, String, Option<String>)>("SELECT evaluation_id, outcome, notes FROM feedback")
+            .fetch_all(&audit_log.pool)
+            .await
+            .unwrap();
+        assert_eq!(rows, vec![(evaluation_id, "approved".to_string(), Some("Granted in full".to_string()))]);
+        #[cfg(feature = "metrics")]
+        assert_eq!(metrics.feedback_total.with_label_values(&["approved"]).get(), 1.0);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn test_feedback_without_audit_log() {
+        let metrics = Arc::new(EligibilityMetrics::new());
+        let eligibility_engine = EligibilityEngine::new().with_metrics(metrics.clone()).with_audit_log(None);
+        let evaluation_id = uuid::Uuid::new_v4().to_string();
+
+        let response = eligibility_engine.record_feedback(feedback(&evaluation_id, "denied")).await.unwrap();
+        assert!(!response.recorded);
+        #[cfg(feature = "metrics")]
+        assert_eq!(metrics.feedback_total.with_label_values(&["denied"]).get(), 1.0);
+
+        let error = eligibility_engine.record_feedback(feedback("case-a", "granted")).await.unwrap_err();
+        match error {
+            crate::common::eligibility_engine::UnpaidLeaveError::ValidationError(errors) => {
+                let paths: Vec<&str> = errors.iter().map(|error| error.path.as_str()).collect();
+                assert_eq!(paths, vec!["/evaluation_id", "/outcome"]);
+            }
+            other => panic!("expected validation errors, got {:?}", other),
+        }
+    }
+}
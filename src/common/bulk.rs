@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+use rmcp::schemars;
+
+use super::eligibility_engine::{EligibilityEngine, UnpaidLeaveDirectParams, UnpaidLeaveError};
+
+/// Tool label the evaluations of a CSV run are counted under
+const CSV_TOOL_LABEL: &str = "evaluate_unpaid_leave_csv";
+
+/// Columns written after the input columns of every row
+const RESULT_COLUMNS: [&str; 5] = ["case", "potentially_eligible", "monthly_benefit", "evaluation_id", "errors"];
+
+/// Outcome of a CSV run
+#[derive(Debug, Default, PartialEq)]
+pub struct BulkSummary {
+    /// Rows evaluated, eligible or not
+    pub evaluated: usize,
+    /// Rows that could not be read or evaluated, reported in their `errors` column
+    pub failed: usize,
+}
+
+/// Evaluates every row of the CSV in `reader`, whose header names `UnpaidLeaveDirectParams` fields,
+/// and writes to `writer` the row number, the input columns and the result of each row. Empty cells
+/// are omitted fields. A malformed row gets its problem in the `errors` column and the run goes on;
+/// only an unreadable header or a failed write stops it
+pub async fn evaluate_csv<R: Read, W: Write>(engine: &EligibilityEngine, reader: R, writer: W) -> anyhow::Result<BulkSummary> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).trim(csv::Trim::All).from_reader(reader);
+    let headers = reader.headers().map_err(|e| anyhow::anyhow!("Cannot read the CSV header: {}", e))?.clone();
+    let column_types = column_types();
+
+    let mut writer = csv::Writer::from_writer(writer);
+    writer.write_record(std::iter::once("row").chain(headers.iter()).chain(RESULT_COLUMNS))?;
+
+    let mut summary = BulkSummary::default();
+    for (index, record) in reader.records().enumerate() {
+        // Numbered like the lines of the file, the header being line 1
+        let row = (index + 2).to_string();
+        let (inputs, result) = match record {
+            Ok(record) => {
+                let inputs: Vec<String> = (0..headers.len()).map(|i| record.get(i).unwrap_or_default().to_string()).collect();
+                let result = if record.len() != headers.len() {
+                    Err(format!("expected {} columns, found {}", headers.len(), record.len()))
+                } else {
+                    evaluate_row(engine, &headers, &record, &column_types).await
+                };
+                (inputs, result)
+            }
+            Err(e) => (vec![String::new(); headers.len()], Err(e.to_string())),
+        };
+
+        let results = match result {
+            Ok(results) => {
+                summary.evaluated += 1;
+                results
+            }
+            Err(error) => {
+                summary.failed += 1;
+                vec![String::new(), String::new(), String::new(), String::new(), error]
+            }
+        };
+        writer.write_record(std::iter::once(row).chain(inputs).chain(results))?;
+    }
+    writer.flush()?;
+    Ok(summary)
+}
+
+/// Result columns of one row, or the reason it could not be evaluated
+async fn evaluate_row(
+    engine: &EligibilityEngine,
+    headers: &csv::StringRecord,
+    record: &csv::StringRecord,
+    column_types: &HashMap<String, String>,
+) -> Result<Vec<String>, String> {
+    let fields: serde_json::Map<String, serde_json::Value> = headers
+        .iter()
+        .zip(record.iter())
+        .filter(|(_, cell)| !cell.is_empty())
+        .map(|(column, cell)| (column.to_string(), cell_value(column_types.get(column).map(String::as_str), cell)))
+        .collect();
+    let params: UnpaidLeaveDirectParams =
+        serde_json::from_value(serde_json::Value::Object(fields)).map_err(|e| format!("invalid row: {}", e))?;
+
+    match engine.evaluate_for_tool(params, CSV_TOOL_LABEL).await {
+        Ok(response) => Ok(vec![
+            response.output.case,
+            response.output.potentially_eligible.to_string(),
+            response.output.monthly_benefit.to_string(),
+            response.evaluation_id.unwrap_or_default(),
+            response.output.errors.join("; "),
+        ]),
+        Err(UnpaidLeaveError::ValidationError(errors)) => {
+            Err(errors.iter().map(|error| format!("{}: {}", error.path, error.message)).collect::<Vec<_>>().join("; "))
+        }
+        Err(error) => Err(error.to_string()),
+    }
+}
+
+/// JSON type of each input field, e.g. "integer" for leave_days, from the input schema
+fn column_types() -> HashMap<String, String> {
+    let schema = serde_json::to_value(schemars::schema_for!(UnpaidLeaveDirectParams)).unwrap_or_default();
+    let Some(properties) = schema["properties"].as_object() else {
+        return HashMap::new();
+    };
+    properties
+        .iter()
+        .filter_map(|(field, property)| {
+            let json_type = match &property["type"] {
+                serde_json::Value::String(json_type) => Some(json_type.as_str()),
+                // Optional fields are typed as [type, "null"]
+                serde_json::Value::Array(types) => types.iter().filter_map(|json_type| json_type.as_str()).find(|json_type| *json_type != "null"),
+                _ => None,
+            };
+            json_type.map(|json_type| (field.clone(), json_type.to_string()))
+        })
+        .collect()
+}
+
+/// Cells are strings, unless their column is numeric or boolean and they hold such a value
+fn cell_value(column_type: Option<&str>, cell: &str) -> serde_json::Value {
+    let typed = match column_type {
+        Some("integer" | "number" | "boolean") => serde_json::from_str::<serde_json::Value>(&cell.to_lowercase()).ok(),
+        _ => None,
+    };
+    typed
+        .filter(|value| value.is_number() || value.is_boolean())
+        .unwrap_or_else(|| serde_json::Value::String(cell.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_evaluate_csv() {
+        let input = "\
+relationship,situation,is_single_parent,total_children_after,leave_days
+mother,illness,false,,30
+father,birth,false,3,
+brother,illness,false,,
+mother,illness,false,,thirty
+mother,illness
+";
+        let mut output = Vec::new();
+        let summary = evaluate_csv(&EligibilityEngine::new(), input.as_bytes(), &mut output).await.unwrap();
+        assert_eq!(summary, BulkSummary { evaluated: 3, failed: 2 });
+
+        let mut reader = csv::Reader::from_reader(output.as_slice());
+        let headers = reader.headers().unwrap().clone();
+        assert_eq!(
+            headers.iter().collect::<Vec<_>>(),
+            vec![
+                "row", "relationship", "situation", "is_single_parent", "total_children_after", "leave_days",
+                "case", "potentially_eligible", "monthly_benefit", "evaluation_id", "errors",
+            ]
+        );
+        let rows: Vec<HashMap<String, String>> = reader.deserialize().map(Result::unwrap).collect();
+        let column = |row: usize, column: &str| rows[row][column].as_str();
+
+        assert_eq!((column(0, "row"), column(0, "case"), column(0, "monthly_benefit")), ("2", "A", "725"));
+        assert_eq!(column(0, "potentially_eligible"), "true");
+        assert!(!column(0, "evaluation_id").is_empty());
+        assert_eq!((column(1, "case"), column(1, "monthly_benefit")), ("B", "500"));
+
+        // Not eligible is a result, not a failure
+        assert_eq!((column(2, "potentially_eligible"), column(2, "monthly_benefit")), ("false", "0"));
+
+        // Malformed rows are reported and do not stop the run
+        assert!(column(3, "errors").starts_with("invalid row:"), "{}", column(3, "errors"));
+        assert_eq!(column(3, "case"), "");
+        assert_eq!(column(4, "errors"), "expected 5 columns, found 2");
+        assert_eq!(column(4, "relationship"), "mother");
+    }
+
+    #[test]
+    fn test_cell_value() {
+        assert_eq!(cell_value(Some("integer"), "30"), serde_json::json!(30));
+        assert_eq!(cell_value(Some("boolean"), "TRUE"), serde_json::json!(true));
+        assert_eq!(cell_value(Some("string"), "42"), serde_json::json!("42"));
+        // Left to the field's own parsing, which reports the problem
+        assert_eq!(cell_value(Some("integer"), "thirty"), serde_json::json!("thirty"));
+        assert_eq!(column_types().get("leave_days").map(String::as_str), Some("integer"));
+    }
+}
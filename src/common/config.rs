@@ -0,0 +1,393 @@
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+use super::money::{Money, SUPPORTED_LOCALES};
+
+/// Path of the optional TOML configuration file
+pub const CONFIG_ENV: &str = "ELIGIBILITY_CONFIG";
+
+/// Cases whose benefit amount can be overridden
+const CONFIGURABLE_CASES: &[&str] = &["A", "B", "C", "D", "E"];
+
+/// Deployment settings applied on top of the rule document
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct EligibilityConfig {
+    /// Monthly benefit amount by case letter, replacing the amount produced by the rules
+    #[serde(default)]
+    pub benefit_amounts: BTreeMap<String, i32>,
+    /// ISO 4217 code of the currency benefits are paid in
+    #[serde(default = "default_currency")]
+    pub currency: String,
+    /// Locale used to format amounts, one of `SUPPORTED_LOCALES`
+    #[serde(default = "default_locale")]
+    pub locale: String,
+    /// Maximum yearly household income by case letter, above which the case is not eligible
+    #[serde(default)]
+    pub income_thresholds: BTreeMap<String, f64>,
+    /// Maximum yearly household income per household member by case letter, above which the case is not eligible
+    #[serde(default)]
+    pub per_member_income_thresholds: BTreeMap<String, f64>,
+    /// Adjustments by autonomous community, keyed by the lowercase region name
+    #[serde(default)]
+    pub regions: BTreeMap<String, RegionConfig>,
+    /// Increase of the benefit when the person cared for has a recognized disability
+    #[serde(default)]
+    pub disability_uplift: Option<DisabilityUplift>,
+    /// Highest monthly benefit paid, whatever the amounts of the case and its adjustments add up to.
+    /// Unlimited when unset
+    #[serde(default)]
+    pub max_monthly_benefit: Option<i32>,
+}
+
+/// Region reported in responses when a requested region has no adjustments configured
+pub const NATIONAL_REGION: &str = "national";
+
+/// Benefit adjustments of one region, applied to the national amount of eligible cases
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RegionConfig {
+    /// Factor applied to the national amount by case letter, rounded to the nearest euro
+    #[serde(default)]
+    pub multipliers: BTreeMap<String, f64>,
+    /// Monthly benefit amount by case letter, replacing the national amount
+    #[serde(default)]
+    pub benefit_amounts: BTreeMap<String, i32>,
+}
+
+impl RegionConfig {
+    /// Amount of `case` in this region, given its `national` amount
+    pub fn amount(&self, case: &str, national: i32) -> i32 {
+        match (self.benefit_amounts.get(case), self.multipliers.get(case)) {
+            (Some(amount), _) => *amount,
+            (None, Some(multiplier)) => (national as f64 * multiplier).round() as i32,
+            (None, None) => national,
+        }
+    }
+}
+
+/// Increase of the monthly benefit of illness and accident care when the person cared for
+/// has a recognized disability. The percentage applies first, then the fixed amount is added
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DisabilityUplift {
+    /// Percentage added to the monthly benefit, rounded to the nearest euro
+    #[serde(default)]
+    pub percentage: Option<f64>,
+    /// Amount in euros added to the monthly benefit
+    #[serde(default)]
+    pub amount: Option<i32>,
+}
+
+impl DisabilityUplift {
+    /// `monthly_benefit` raised by the uplift, saturating at the bounds of `i32`
+    pub fn apply(&self, monthly_benefit: i32) -> i32 {
+        let raised = match self.percentage {
+            Some(percentage) => (monthly_benefit as f64 * (1.0 + percentage / 100.0))
+                .round()
+                .clamp(i32::MIN as f64, i32::MAX as f64) as i32,
+            None => monthly_benefit,
+        };
+        raised.saturating_add(self.amount.unwrap_or(0))
+    }
+}
+
+fn default_currency() -> String {
+    "EUR".to_string()
+}
+
+fn default_locale() -> String {
+    "es-ES".to_string()
+}
+
+impl Default for EligibilityConfig {
+    fn default() -> Self {
+        Self {
+            benefit_amounts: BTreeMap::new(),
+            currency: default_currency(),
+            locale: default_locale(),
+            income_thresholds: BTreeMap::new(),
+            per_member_income_thresholds: BTreeMap::new(),
+            regions: BTreeMap::new(),
+            disability_uplift: None,
+            max_monthly_benefit: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(String, std::io::Error),
+    Parse(toml::de::Error),
+    Invalid(Vec<String>),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, e) => write!(f, "Cannot read configuration file '{}': {}", path, e),
+            ConfigError::Parse(e) => write!(f, "Invalid configuration file: {}", e),
+            ConfigError::Invalid(problems) => write!(f, "Invalid configuration: {}", problems.join("; ")),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl EligibilityConfig {
+    /// Parses and validates a TOML configuration
+    pub fn from_toml(content: &str) -> Result<Self, ConfigError> {
+        let config: Self = toml::from_str(content).map_err(ConfigError::Parse)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Loads the file named by `ELIGIBILITY_CONFIG`, or the defaults when it is not set
+    pub fn from_env() -> Result<Self, ConfigError> {
+        match std::env::var(CONFIG_ENV) {
+            Ok(path) if !path.trim().is_empty() => {
+                let content = std::fs::read_to_string(&path).map_err(|e| ConfigError::Io(path.clone(), e))?;
+                Self::from_toml(&content)
+            }
+            _ => Ok(Self::default()),
+        }
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        let mut problems = Vec::new();
+        for (case, amount) in &self.benefit_amounts {
+            if !CONFIGURABLE_CASES.contains(&case.as_str()) {
+                problems.push(format!("unknown case '{}' in benefit_amounts, expected one of {}", case, CONFIGURABLE_CASES.join(", ")));
+            }
+            if *amount < 0 {
+                problems.push(format!("benefit amount for case '{}' must be non-negative, got {}", case, amount));
+            }
+        }
+        if self.currency.len() != 3 || !self.currency.chars().all(|c| c.is_ascii_uppercase()) {
+            problems.push(format!("currency must be a three-letter ISO 4217 code, got '{}'", self.currency));
+        }
+        if !SUPPORTED_LOCALES.contains(&self.locale.as_str()) {
+            problems.push(format!("unsupported locale '{}', expected one of {}", self.locale, SUPPORTED_LOCALES.join(", ")));
+        }
+        for (case, threshold) in &self.income_thresholds {
+            if !CONFIGURABLE_CASES.contains(&case.as_str()) {
+                problems.push(format!("unknown case '{}' in income_thresholds, expected one of {}", case, CONFIGURABLE_CASES.join(", ")));
+            }
+            if !threshold.is_finite() || *threshold < 0.0 {
+                problems.push(format!("income threshold for case '{}' must be non-negative, got {}", case, threshold));
+            }
+        }
+        for (case, threshold) in &self.per_member_income_thresholds {
+            if !CONFIGURABLE_CASES.contains(&case.as_str()) {
+                problems.push(format!(
+                    "unknown case '{}' in per_member_income_thresholds, expected one of {}",
+                    case,
+                    CONFIGURABLE_CASES.join(", ")
+                ));
+            }
+            if !threshold.is_finite() || *threshold < 0.0 {
+                problems.push(format!("per-member income threshold for case '{}' must be non-negative, got {}", case, threshold));
+            }
+        }
+        for (name, region) in &self.regions {
+            if name.is_empty() || name != &name.to_lowercase() || name == NATIONAL_REGION {
+                problems.push(format!("region name '{}' must be lowercase, non-empty and not '{}'", name, NATIONAL_REGION));
+            }
+            for case in region.multipliers.keys().chain(region.benefit_amounts.keys()) {
+                if !CONFIGURABLE_CASES.contains(&case.as_str()) {
+                    problems.push(format!("unknown case '{}' in region '{}', expected one of {}", case, name, CONFIGURABLE_CASES.join(", ")));
+                }
+            }
+            for (case, multiplier) in &region.multipliers {
+                if !multiplier.is_finite() || *multiplier < 0.0 {
+                    problems.push(format!("multiplier for case '{}' in region '{}' must be non-negative, got {}", case, name, multiplier));
+                }
+                if region.benefit_amounts.contains_key(case) {
+                    problems.push(format!("case '{}' in region '{}' has both a multiplier and a benefit amount", case, name));
+                }
+            }
+            for (case, amount) in &region.benefit_amounts {
+                if *amount < 0 {
+                    problems.push(format!("benefit amount for case '{}' in region '{}' must be non-negative, got {}", case, name, amount));
+                }
+            }
+        }
+        if let Some(uplift) = &self.disability_uplift {
+            if uplift.percentage.is_none() && uplift.amount.is_none() {
+                problems.push("disability_uplift must set a percentage, an amount or both".to_string());
+            }
+            if let Some(percentage) = uplift.percentage
+                && (!percentage.is_finite() || percentage < 0.0)
+            {
+                problems.push(format!("disability uplift percentage must be non-negative, got {}", percentage));
+            }
+            if let Some(amount) = uplift.amount
+                && amount < 0
+            {
+                problems.push(format!("disability uplift amount must be non-negative, got {}", amount));
+            }
+        }
+        if let Some(cap) = self.max_monthly_benefit
+            && cap < 0
+        {
+            problems.push(format!("max_monthly_benefit must be non-negative, got {}", cap));
+        }
+        if problems.is_empty() { Ok(()) } else { Err(ConfigError::Invalid(problems)) }
+    }
+
+    /// Configured amount for `case`, if it is overridden
+    pub fn benefit_amount(&self, case: &str) -> Option<i32> {
+        self.benefit_amounts.get(case).copied()
+    }
+
+    /// Income limit of `case`, if a means test is configured for it
+    pub fn income_threshold(&self, case: &str) -> Option<f64> {
+        self.income_thresholds.get(case).copied()
+    }
+
+    /// Income limit per household member of `case`, if a per-member means test is configured for it
+    pub fn per_member_income_threshold(&self, case: &str) -> Option<f64> {
+        self.per_member_income_thresholds.get(case).copied()
+    }
+
+    /// `amount` limited to `max_monthly_benefit`
+    pub fn capped_benefit(&self, amount: i32) -> i32 {
+        self.max_monthly_benefit.map_or(amount, |cap| amount.min(cap))
+    }
+
+    /// Adjustments of `region`, matched case-insensitively, `None` when it is not configured
+    pub fn region(&self, region: &str) -> Option<(&str, &RegionConfig)> {
+        let region = region.trim().to_lowercase();
+        self.regions.get_key_value(&region).map(|(name, config)| (name.as_str(), config))
+    }
+
+    /// `amount` in the configured currency, formatted for the configured locale
+    pub fn money(&self, amount: f64) -> Money {
+        Money::new(amount, &self.currency, &self.locale)
+    }
+}
+
+/// Configuration loaded once from `ELIGIBILITY_CONFIG`
+static CONFIG: Lazy<Result<Arc<EligibilityConfig>, ConfigError>> = Lazy::new(|| EligibilityConfig::from_env().map(Arc::new));
+
+/// Loads the configuration and logs the overrides it applies.
+/// Binaries call this at startup so an invalid file stops the server before it accepts requests.
+pub fn load_config() -> Result<Arc<EligibilityConfig>, &'static ConfigError> {
+    let config = CONFIG.as_ref()?;
+    for (case, amount) in &config.benefit_amounts {
+        tracing::info!("Benefit amount for case {} overridden to {}", case, config.money(*amount as f64).formatted);
+    }
+    for (case, threshold) in &config.income_thresholds {
+        tracing::info!("Household income limit for case {} set to {}", case, config.money(*threshold).formatted);
+    }
+    for (case, threshold) in &config.per_member_income_thresholds {
+        tracing::info!("Household income limit per member for case {} set to {}", case, config.money(*threshold).formatted);
+    }
+    for name in config.regions.keys() {
+        tracing::info!("Regional benefit adjustments configured for {}", name);
+    }
+    if let Some(uplift) = &config.disability_uplift {
+        tracing::info!(
+            "Disability uplift of {}% plus {} configured",
+            uplift.percentage.unwrap_or(0.0),
+            config.money(uplift.amount.unwrap_or(0) as f64).formatted
+        );
+    }
+    if let Some(cap) = config.max_monthly_benefit {
+        tracing::info!("Monthly benefit capped at {}", config.money(cap as f64).formatted);
+    }
+    Ok(config.clone())
+}
+
+/// Process-wide configuration, the defaults if it could not be loaded (already reported by `load_config`)
+pub fn current_config() -> Arc<EligibilityConfig> {
+    CONFIG.as_ref().cloned().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_benefit_amount_overrides() {
+        let config = EligibilityConfig::from_toml("[benefit_amounts]\nA = 800\nC = 0\n").unwrap();
+        assert_eq!(config.benefit_amount("A"), Some(800));
+        assert_eq!(config.benefit_amount("C"), Some(0));
+        assert_eq!(config.benefit_amount("B"), None);
+        assert_eq!(EligibilityConfig::from_toml("").unwrap(), EligibilityConfig::default());
+    }
+
+    #[test]
+    fn test_invalid_benefit_amounts_rejected() {
+        match EligibilityConfig::from_toml("[benefit_amounts]\nA = -1\nZ = 100\n") {
+            Err(ConfigError::Invalid(problems)) => assert_eq!(problems.len(), 2),
+            other => panic!("Expected an invalid configuration, got {:?}", other),
+        }
+        assert!(matches!(EligibilityConfig::from_toml("amounts = 1"), Err(ConfigError::Parse(_))));
+        assert!(matches!(EligibilityConfig::from_toml("currency = \"euro\""), Err(ConfigError::Invalid(_))));
+        assert!(matches!(EligibilityConfig::from_toml("locale = \"xx\""), Err(ConfigError::Invalid(_))));
+        assert!(matches!(EligibilityConfig::from_toml("[income_thresholds]\nA = -1.0\n"), Err(ConfigError::Invalid(_))));
+        assert!(matches!(EligibilityConfig::from_toml("[per_member_income_thresholds]\nZ = 1.0\n"), Err(ConfigError::Invalid(_))));
+    }
+
+    #[test]
+    fn test_currency_defaults_to_spanish_euros() {
+        let config = EligibilityConfig::from_toml("").unwrap();
+        assert_eq!(config.money(725.0).formatted, "725,00 €");
+
+        let config = EligibilityConfig::from_toml("currency = \"USD\"\nlocale = \"en-US\"\n").unwrap();
+        assert_eq!(config.money(500.0), Money::new(500.0, "USD", "en-US"));
+    }
+
+    #[test]
+    fn test_regional_adjustments() {
+        let config = EligibilityConfig::from_toml(
+            "[regions.catalonia.multipliers]\nA = 1.1\nB = 1.5\n\n[regions.navarre.benefit_amounts]\nA = 900\n",
+        )
+        .unwrap();
+        let (name, catalonia) = config.region(" Catalonia ").unwrap();
+        assert_eq!(name, "catalonia");
+        assert_eq!(catalonia.amount("A", 725), 798);
+        assert_eq!(catalonia.amount("C", 500), 500);
+        let (_, navarre) = config.region("navarre").unwrap();
+        assert_eq!(navarre.amount("A", 725), 900);
+        assert!(config.region("atlantis").is_none());
+
+        let invalid = "[regions.Galicia.multipliers]\nA = -1\nZ = 2\n\n[regions.Galicia.benefit_amounts]\nA = 600\n";
+        match EligibilityConfig::from_toml(invalid) {
+            Err(ConfigError::Invalid(problems)) => assert_eq!(problems.len(), 4),
+            other => panic!("Expected an invalid configuration, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_disability_uplift() {
+        let percentage = EligibilityConfig::from_toml("[disability_uplift]\npercentage = 10.0\n").unwrap().disability_uplift.unwrap();
+        assert_eq!(percentage.apply(725), 798);
+        assert_eq!(percentage.apply(500), 550);
+        let amount = EligibilityConfig::from_toml("[disability_uplift]\namount = 100\n").unwrap().disability_uplift.unwrap();
+        assert_eq!(amount.apply(725), 825);
+        let both = DisabilityUplift { percentage: Some(20.0), amount: Some(50) };
+        assert_eq!(both.apply(725), 920);
+        let huge = DisabilityUplift { percentage: Some(1e12), amount: Some(i32::MAX) };
+        assert_eq!(huge.apply(725), i32::MAX);
+        assert_eq!(DisabilityUplift { percentage: None, amount: Some(i32::MAX) }.apply(725), i32::MAX);
+        assert_eq!(EligibilityConfig::default().disability_uplift, None);
+
+        for invalid in ["[disability_uplift]\n", "[disability_uplift]\npercentage = -5.0\n", "[disability_uplift]\namount = -1\n"] {
+            assert!(matches!(EligibilityConfig::from_toml(invalid), Err(ConfigError::Invalid(_))), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_max_monthly_benefit() {
+        let config = EligibilityConfig::from_toml("max_monthly_benefit = 1000\n").unwrap();
+        assert_eq!(config.capped_benefit(1450), 1000);
+        assert_eq!(config.capped_benefit(725), 725);
+        assert_eq!(EligibilityConfig::default().capped_benefit(i32::MAX), i32::MAX);
+        assert!(matches!(EligibilityConfig::from_toml("max_monthly_benefit = -1\n"), Err(ConfigError::Invalid(_))));
+    }
+}
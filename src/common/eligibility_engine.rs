@@ -1,24 +1,55 @@
 use serde::{Deserialize, Serialize, Deserializer, de::Error as DeError};
-use zen_engine::DecisionEngine;
+use once_cell::sync::{Lazy, OnceCell};
+use zen_engine::{Decision, DecisionEngine, DecisionGraphTrace, EvaluationOptions};
+use zen_engine::handler::custom_node_adapter::NoopCustomNode;
+use zen_engine::loader::NoopLoader;
 use zen_engine::model::DecisionContent;
 use zen_engine::{EvaluationError, NodeError};
+use chrono::NaiveDate;
+use lru::LruCache;
+use std::collections::BTreeMap;
 use std::fmt;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tracing::Instrument;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 
-use super::metrics::{increment_requests, increment_errors, RequestTimer};
+use super::audit::{self, AuditLog};
+use super::events::{self, EvaluationEvents};
+use super::webhook::{self, Webhook};
+use super::http;
+use super::i18n::{self, Locale};
+use super::config::{current_config, EligibilityConfig, NATIONAL_REGION};
+use super::money::{prorated_total, scale_by_percentage, Money, DAYS_PER_MONTH};
+use super::metrics::{
+    EligibilityMetrics, MetricLabels, RequestTimer, DEFAULT_TENANT_LABEL, DEFAULT_TOOL_LABEL, GIT_SHA, METRICS, UNKNOWN_TENANT_LABEL,
+    VERSION,
+};
+use super::prompts;
 
 use rmcp::{
-    ServerHandler,
+    RoleServer, ServerHandler,
     handler::server::{router::tool::ToolRouter, wrapper::Parameters},
-    model::{ServerCapabilities, ServerInfo, CallToolResult, Content},
+    model::{
+        AnnotateAble, CallToolResult, Content, Extensions, GetPromptRequestParam, GetPromptResult, ListPromptsResult,
+        ListResourcesResult, PaginatedRequestParam, RawResource, ReadResourceRequestParam, ReadResourceResult,
+        ResourceContents, ServerCapabilities, ServerInfo,
+    },
+    service::RequestContext,
     ErrorData as McpError,
     schemars, tool, tool_handler, tool_router,
 };
 
 // =================== ERROR STRUCTURES ===================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
 pub struct ValidationError {
+    #[schemars(description = "Human-readable description of the problem")]
     pub message: String,
+    #[schemars(description = "JSON pointer to the offending field, e.g. /input/situation")]
     pub path: String,
 }
 
@@ -36,30 +67,68 @@ pub struct ValidationErrorDetails {
 }
 
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum UnpaidLeaveError {
     ValidationError(Vec<ValidationError>),
     ZenEngineError(EvaluationError),
     SerializationError(serde_json::Error),
+    InternalError(String),
+    /// The evaluation panicked, the panic message is logged but not returned to clients
+    Panicked,
+    /// The rule document could not be loaded, so there is nothing to evaluate against
+    RulesUnavailable(String),
+    /// The concurrency limit was reached and no evaluation slot freed up in time
+    ServerBusy(usize),
+    /// The decision engine did not answer within the evaluation deadline
+    Timeout(Duration),
 }
 
 impl fmt::Display for UnpaidLeaveError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             UnpaidLeaveError::ValidationError(errors) => {
-                write!(f, "Validation errors:\n")?;
+                writeln!(f, "Validation errors:")?;
                 for error in errors {
-                    write!(f, "  - {}: {}\n", error.path, error.message)?;
+                    writeln!(f, "  - {}: {}", error.path, error.message)?;
                 }
                 Ok(())
             },
             UnpaidLeaveError::ZenEngineError(e) => write!(f, "Decision engine error: {}", e),
             UnpaidLeaveError::SerializationError(e) => write!(f, "Serialization error: {}", e),
+            UnpaidLeaveError::InternalError(e) => write!(f, "Internal error: {}", e),
+            UnpaidLeaveError::Panicked => write!(f, "Internal error: the evaluation failed unexpectedly"),
+            UnpaidLeaveError::RulesUnavailable(e) => write!(f, "Rules unavailable: {}", e),
+            UnpaidLeaveError::ServerBusy(limit) => write!(f, "Server busy: {} evaluations already running, try again later", limit),
+            UnpaidLeaveError::Timeout(deadline) => write!(f, "Evaluation timed out after {} ms", deadline.as_millis()),
         }
     }
 }
 
 impl std::error::Error for UnpaidLeaveError {}
 
+impl UnpaidLeaveError {
+    /// Whether attempting the evaluation again may succeed: the engine thread failing to start or
+    /// being cancelled, or the evaluation running past its deadline on a loaded server. Decisions are
+    /// loaded in memory, so invalid inputs, rule errors and panics fail the same way every time
+    pub fn is_transient(&self) -> bool {
+        matches!(self, UnpaidLeaveError::InternalError(_) | UnpaidLeaveError::Timeout(_))
+    }
+
+    /// Label used for this error in the error-type metric
+    pub fn error_type(&self) -> &'static str {
+        match self {
+            UnpaidLeaveError::ValidationError(_) => "validation",
+            UnpaidLeaveError::ZenEngineError(_) => "engine",
+            UnpaidLeaveError::SerializationError(_) => "serialization",
+            UnpaidLeaveError::InternalError(_) => "join",
+            UnpaidLeaveError::Panicked => "panic",
+            UnpaidLeaveError::RulesUnavailable(_) => "rules",
+            UnpaidLeaveError::ServerBusy(_) => "busy",
+            UnpaidLeaveError::Timeout(_) => "timeout",
+        }
+    }
+}
+
 impl From<EvaluationError> for UnpaidLeaveError {
     fn from(error: EvaluationError) -> Self {
         UnpaidLeaveError::ZenEngineError(error)
@@ -120,114 +189,200 @@ where
     deserializer.deserialize_any(BoolOrStringVisitor)
 }
 
-/// Deserializes a value that can be f64 or string
+/// Deserializes a value that can be f64 or string.
+/// Goes through `serde_json::Value`, the only type that understands the arbitrary-precision
+/// numbers zen-engine enables in serde_json once `#[serde(flatten)]` has buffered them
 fn deserialize_f64_or_string<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Null => Ok(None),
+        serde_json::Value::Number(number) => number
+            .as_f64()
+            .map(Some)
+            .ok_or_else(|| DeError::custom(format!("invalid number: {}", number))),
+        serde_json::Value::String(value) => value
+            .parse::<f64>()
+            .map(Some)
+            .map_err(|_| DeError::custom(format!("invalid number string: {}", value))),
+        other => Err(DeError::custom(format!("invalid type: {}, expected f64, string, or null", other))),
+    }
+}
+
+/// Formats accepted for dates besides ISO 8601 date-times, tried in order.
+/// Day-first formats follow the Spanish convention, month-first dates are not accepted
+const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%Y/%m/%d", "%d/%m/%Y", "%d-%m-%Y"];
+
+/// Parses a date in one of `DATE_FORMATS`, or the date of an ISO 8601 date-time such as
+/// "2025-03-01T10:00:00Z", the forms LLMs commonly emit. `None` when nothing matches
+pub fn parse_date(value: &str) -> Option<NaiveDate> {
+    let value = value.trim();
+    DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+        .or_else(|| chrono::DateTime::parse_from_rfc3339(value).ok().map(|date_time| date_time.date_naive()))
+        .or_else(|| chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S").ok().map(|date_time| date_time.date()))
+}
+
+/// Deserializes a date given as a string in any format `parse_date` accepts, or null
+fn deserialize_date_or_string<'de, D>(deserializer: D) -> Result<Option<NaiveDate>, D::Error>
 where
     D: Deserializer<'de>,
 {
     use serde::de::Visitor;
     use std::fmt;
 
-    struct F64OrStringVisitor;
+    struct DateOrStringVisitor;
 
-    impl<'de> Visitor<'de> for F64OrStringVisitor {
-        type Value = Option<f64>;
+    impl<'de> Visitor<'de> for DateOrStringVisitor {
+        type Value = Option<NaiveDate>;
 
         fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-            formatter.write_str("f64, string, or null")
-        }
-
-        fn visit_f64<E>(self, value: f64) -> Result<Option<f64>, E>
-        where
-            E: DeError,
-        {
-            Ok(Some(value))
+            formatter.write_str("a date string such as YYYY-MM-DD, or null")
         }
 
-        fn visit_i64<E>(self, value: i64) -> Result<Option<f64>, E>
+        fn visit_unit<E>(self) -> Result<Option<NaiveDate>, E>
         where
             E: DeError,
         {
-            Ok(Some(value as f64))
+            Ok(None)
         }
 
-        fn visit_u64<E>(self, value: u64) -> Result<Option<f64>, E>
+        fn visit_none<E>(self) -> Result<Option<NaiveDate>, E>
         where
             E: DeError,
         {
-            Ok(Some(value as f64))
+            Ok(None)
         }
 
-        fn visit_str<E>(self, value: &str) -> Result<Option<f64>, E>
+        fn visit_str<E>(self, value: &str) -> Result<Option<NaiveDate>, E>
         where
             E: DeError,
         {
-            value.parse::<f64>()
-                .map(Some)
-                .map_err(|_| DeError::custom(format!("invalid number string: {}", value)))
+            parse_date(value).map(Some).ok_or_else(|| {
+                DeError::custom(format!(
+                    "invalid date string: {}, expected YYYY-MM-DD (YYYY/MM/DD, DD/MM/YYYY, DD-MM-YYYY and ISO 8601 date-times are also accepted)",
+                    value
+                ))
+            })
         }
 
-        fn visit_string<E>(self, value: String) -> Result<Option<f64>, E>
+        fn visit_string<E>(self, value: String) -> Result<Option<NaiveDate>, E>
         where
             E: DeError,
         {
             self.visit_str(&value)
         }
+    }
 
-        fn visit_none<E>(self) -> Result<Option<f64>, E>
-        where
-            E: DeError,
-        {
-            Ok(None)
-        }
+    deserializer.deserialize_any(DateOrStringVisitor)
+}
 
-        fn visit_unit<E>(self) -> Result<Option<f64>, E>
-        where
-            E: DeError,
-        {
-            Ok(None)
-        }
+/// Formats validation errors as the bullet list returned to MCP clients
+fn format_validation_errors(errors: &[ValidationError]) -> String {
+    let mut msg = "Validation errors:\n".to_string();
+    for error in errors {
+        msg.push_str(&format!("  - Field '{}': {}\n", error.path, error.message));
     }
+    msg
+}
 
-    deserializer.deserialize_any(F64OrStringVisitor)
+/// Formats an evaluation error as the message returned to MCP clients
+fn format_evaluation_error(error: &UnpaidLeaveError) -> String {
+    match error {
+        UnpaidLeaveError::ValidationError(validation_errors) => format_validation_errors(validation_errors),
+        UnpaidLeaveError::InternalError(_) | UnpaidLeaveError::Panicked | UnpaidLeaveError::RulesUnavailable(_) => error.to_string(),
+        _ => format!("Evaluation error: {}", error),
+    }
 }
 
 // =================== DATA STRUCTURES ===================
 
-// Direct parameters structure for MCP (flattened)
-#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+// Direct parameters structure for MCP (flattened). The engine fields are declared once in
+// `UnpaidLeaveInput` and flattened here so both structures cannot drift apart
+// `Default` leaves `relationship` and `situation` empty, which never passes validation
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
 pub struct UnpaidLeaveDirectParams {
-    #[schemars(description = "Family relationship with the person who needs care. VALID VALUES: 'father', 'mother', 'parent', 'son', 'daughter', 'spouse', 'partner', 'husband', 'wife', 'foster_parent'. Example: My mother had an accident and I'm taking care of her => 'son'; I had a baby => 'mother' or 'parent'")]
-    pub relationship: String,
-    
-    #[schemars(description = "Situation that motivates the need for care. VALID VALUES: 'birth', 'adoption', 'foster_care', 'multiple_birth', 'multiple_adoption', 'multiple_foster_care', 'illness', 'accident'. If number of children born or adopted or fostered is greater than one at the same time, USE 'multiple_birth' or 'multiple_adoption' or 'multiple_foster_care'. Example: I had a baby => 'birth'; I adopted a child => 'adoption'; I'm fostering two kids => 'multiple_foster_care'")]
-    pub situation: String,
-    
-    #[schemars(description = "Are you a single parent? Only relevant for birth/adoption situations, otherwise it is not relevant and should be always false")]
-    #[serde(deserialize_with = "deserialize_bool_or_string")]
-    pub is_single_parent: bool,
-    
-    #[schemars(description = "Total number of children you'll have after birth/adoption (0 for illness/accident care)")]
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(deserialize_with = "deserialize_f64_or_string")]
-    #[serde(default)]
-    pub total_children_after: Option<f64>,
+    #[serde(flatten)]
+    pub input: UnpaidLeaveInput,
+
+    #[schemars(description = "Date the leave starts, preferably in YYYY-MM-DD format. Selects the regulation in effect on that date; the latest regulation is used when omitted")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective_date: Option<String>,
+
+    #[schemars(description = "Optional length of the leave in days. Added to leave_months (30 days each) to compute total_benefit")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leave_days: Option<u32>,
+
+    #[schemars(description = "Optional length of the leave in months. Added to leave_days to compute total_benefit")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leave_months: Option<u32>,
+
+    #[schemars(description = "Optional first day of the leave, in YYYY-MM-DD format. Together with leave_end, gives the length of the leave used to compute total_benefit, instead of leave_days and leave_months")]
+    #[schemars(with = "Option<String>")]
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "deserialize_date_or_string")]
+    pub leave_start: Option<NaiveDate>,
+
+    #[schemars(description = "Optional last day of the leave, included, in YYYY-MM-DD format. Must not be before leave_start")]
+    #[schemars(with = "Option<String>")]
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "deserialize_date_or_string")]
+    pub leave_end: Option<NaiveDate>,
+
+    #[schemars(description = "Optional percentage of a full-time leave, from 1 to 100, for part-time leave. Scales monthly_benefit, rounded to the nearest euro. Omit it for a full-time leave")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leave_percentage: Option<u32>,
+
+    #[schemars(description = "Optional autonomous community where the benefit is requested, e.g. 'catalonia'. Regions configured on the server adjust monthly_benefit; any other region gets the national amount")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+
+    #[schemars(description = "Optional language of the texts of the response (description, additional_requirements, errors, warnings, explanation) and of validation messages: 'es' (default) or 'en'. Regional tags such as 'en-GB' are accepted. Over HTTP, the Accept-Language header is used when omitted")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+
+    #[schemars(description = "Optional yearly household income. When the server configures an income limit for the resulting case and the income exceeds it, the result is not eligible. Omit it to skip the means test")]
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "deserialize_f64_or_string")]
+    pub household_income: Option<f64>,
+
+    #[schemars(description = "Optional number of household members, at least 1. With household_income, the income per member is checked against the per-member income limit the server configures for the resulting case")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub household_size: Option<u32>,
+
+    #[schemars(description = "Optional. true when the person cared for has a recognized disability, which raises monthly_benefit for illness and accident care when the server configures a disability uplift. Defaults to false")]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not", deserialize_with = "deserialize_bool_or_string")]
+    pub has_disability: bool,
+
+    #[schemars(description = "Optional. true echoes the input, as normalized before evaluation, in the 'input' field of the response; false leaves 'input' empty. When omitted, 'input' is whatever the rule document returns")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_input: Option<bool>,
+
+    #[schemars(description = "Optional id of the organization whose rules apply, on servers hosting several. The shared rules apply when omitted")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+
+    // Any keys not declared above. Ignored unless strict parsing is enabled
+    #[schemars(skip)]
+    #[serde(flatten, skip_serializing)]
+    pub unknown_fields: BTreeMap<String, serde_json::Value>,
 }
 
 // Internal structure for the ZEN engine (nested)
-#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
 pub struct UnpaidLeaveInput {
-    #[schemars(description = "Family relationship with the person who needs care. VALID VALUES: 'father', 'mother', 'parent', 'son', 'daughter', 'spouse', 'partner', 'husband', 'wife', 'foster_parent'. Example: My mother had an accident and I'm taking care of her => 'son'; I had a baby => 'mother' or 'parent'")]
+    #[schemars(description = relationship_description())]
     pub relationship: String,
     
-    #[schemars(description = "Situation that motivates the need for care. VALID VALUES: 'birth', 'adoption', 'foster_care', 'multiple_birth', 'multiple_adoption', 'multiple_foster_care', 'illness', 'accident'. If number of children born or adopted or fostered is greater than one at the same time, USE 'multiple_birth' or 'multiple_adoption' or 'multiple_foster_care'. Example: I had a baby => 'birth'; I adopted a child => 'adoption'; I'm fostering two kids => 'multiple_foster_care'")]
+    #[schemars(description = situation_description())]
     pub situation: String,
     
     #[schemars(description = "Are you a single parent? Only relevant for birth/adoption situations, otherwise it is not relevant and should be always false")]
+    #[serde(deserialize_with = "deserialize_bool_or_string")]
     pub is_single_parent: bool,
     
     #[schemars(description = "Total number of children you'll have after birth/adoption (0 for illness/accident care)")]
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(deserialize_with = "deserialize_f64_or_string")]
     #[serde(default)]
     pub total_children_after: Option<f64>,
 }
@@ -238,28 +393,106 @@ pub struct UnpaidLeaveRequest {
     pub input: UnpaidLeaveInput,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
 pub struct UnpaidLeaveResponse {
     #[schemars(description = "Evaluation result")]
     pub output: UnpaidLeaveOutputForSchema,
+    #[schemars(description = "Evaluated input after normalization, present when include_input was true")]
     #[serde(default)]
     pub input: Option<UnpaidLeaveInput>,
+    #[schemars(description = "Whether relationship, after normalization, is one of the valid values. Always set by this server")]
     #[serde(default)]
     pub relationship_valid: Option<bool>,
+    #[schemars(description = "Version of the rule document that produced this result")]
+    #[serde(default)]
+    pub rules_version: String,
+    #[schemars(description = "Language of the texts of the response. Always set by this server")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    #[schemars(description = "Confidence in the determination from 0 to 1: 1.0 when relationship and situation were exact valid values, lower when they had to be normalized or mapped from a synonym. Ask the user to confirm the input when it is below 1. Always set by this server")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
+    #[schemars(description = "Human-readable explanation of the requirements that are not met, only present when potentially_eligible is false")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<String>,
+    #[schemars(description = "What the applicant should do next, such as the documents to provide for the case, in the language of the response. Always set by this server")]
+    #[serde(default)]
+    pub next_steps: Vec<String>,
+    #[schemars(description = "Unique id of this evaluation, to quote in support requests or when sending feedback with record_eligibility_feedback")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evaluation_id: Option<String>,
+    #[schemars(description = "Region whose amounts were applied, 'national' when the requested region has no adjustments. Only present when a region was given")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    #[schemars(description = "Tenant whose rules produced this result, only present when a tenant_id was given")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+    #[schemars(description = "Monthly benefit with its currency and formatted for display")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monthly_benefit_money: Option<Money>,
+    #[schemars(description = "Length of the leave in days used for total_benefit, only present when a leave duration was given")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leave_duration_days: Option<u32>,
+    #[schemars(description = "Benefit over the whole leave: the monthly amount prorated per 30-day month and rounded to the cent, only present when a leave duration was given")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_benefit: Option<Money>,
+    #[schemars(description = "Decision path followed by the engine, only present when an explanation was requested")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<Vec<DecisionTraceStep>>,
+}
+
+/// One node executed by the decision engine, in execution order
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct DecisionTraceStep {
+    #[schemars(description = "Position of the node in the execution order")]
+    pub order: u32,
+
+    #[schemars(description = "Identifier of the node in the rule document")]
+    pub node_id: String,
+
+    #[schemars(description = "Name of the node in the rule document")]
+    pub name: String,
+
+    #[schemars(description = "Data received by the node")]
+    pub input: serde_json::Value,
+
+    #[schemars(description = "Data produced by the node")]
+    pub output: serde_json::Value,
+
+    #[schemars(description = "Node-specific details, e.g. the decision table rule that matched")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_data: Option<serde_json::Value>,
+}
+
+impl From<DecisionGraphTrace> for DecisionTraceStep {
+    fn from(trace: DecisionGraphTrace) -> Self {
+        Self {
+            order: trace.order,
+            node_id: trace.id,
+            name: trace.name,
+            input: trace.input.into(),
+            output: trace.output.into(),
+            trace_data: trace.trace_data,
+        }
+    }
 }
 
 // Estructura para el schema JSON (para documentación MCP)
-#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
 pub struct UnpaidLeaveOutputForSchema {
     #[schemars(description = "Description of the applicable case")]
     pub description: String,
     
-    #[schemars(description = "Monthly benefit amount in euros. 725€ for Case A (family care), 500€ for other valid cases, 0€ if not eligible")]
+    #[schemars(description = "Monthly benefit amount in the configured currency: the amount of the case, 0 if not eligible")]
     pub monthly_benefit: i32,
     
     #[schemars(description = "Detailed description of additional requirements that must be met")]
     #[serde(default)]
     pub additional_requirements: String,
+
+    #[schemars(description = "Checklist of the additional requirements of the case, each with whether the input shows it met, unmet or unknown")]
+    #[serde(default)]
+    pub requirements: Vec<Requirement>,
     
     #[schemars(description = "Letter of the applicable case according to regulations (A, B, C, D, E) or empty if not eligible")]
     pub case: String,
@@ -267,365 +500,5178 @@ pub struct UnpaidLeaveOutputForSchema {
     #[schemars(description = "Does it meet the intrinsic requirements to potentially be entitled to the benefit?")]
     pub potentially_eligible: bool,
     
-    #[schemars(description = "List of errors or unmet requirements")]
-    #[serde(default)]
-    pub errores: Vec<String>,
+    #[schemars(description = "List of errors or unmet requirements, sorted alphabetically without duplicates")]
+    // The rule document still produces the Spanish "errores" key
+    #[serde(default, rename = "errors", alias = "errores")]
+    pub errors: Vec<String>,
     
-    #[schemars(description = "List of warnings or additional relevant information")]
+    #[schemars(description = "List of warnings or additional relevant information, sorted alphabetically without duplicates")]
     #[serde(default)]
     pub warnings: Vec<String>,
 }
 
-// =================== DECISION ENGINE ===================
+impl UnpaidLeaveOutputForSchema {
+    /// Sorts `errors` and `warnings` by byte order and removes duplicates, so the same
+    /// evaluation always lists its messages the same way whatever produced them
+    pub fn tidy_messages(&mut self) {
+        for messages in [&mut self.errors, &mut self.warnings] {
+            messages.sort();
+            messages.dedup();
+        }
+    }
 
-#[derive(Debug, Clone)]
-struct UnpaidLeaveDecisionEngine;
+    /// Problem with a rule output marked potentially eligible without a case. The opposite is
+    /// legitimate: a case whose requirements are not met, such as B before the third child
+    fn case_eligibility_mismatch(&self) -> Option<String> {
+        (self.potentially_eligible && self.case.trim().is_empty())
+            .then(|| "potentially eligible without a case".to_string())
+    }
+}
 
-impl UnpaidLeaveDecisionEngine {
-    fn new() -> Self {
-        Self
+// =================== INPUT VALIDATION ===================
+
+/// Relationship values understood by the rule document
+pub const VALID_RELATIONSHIPS: &[&str] = &[
+    "father", "mother", "parent", "son", "daughter", "spouse", "partner", "husband", "wife", "foster_parent",
+];
+
+/// Situation values understood by the rule document
+pub const VALID_SITUATIONS: &[&str] = &[
+    "birth", "adoption", "foster_care", "multiple_birth", "multiple_adoption", "multiple_foster_care", "illness", "accident",
+];
+
+/// Formats `values` as `'a', 'b', 'c'` for descriptions and instructions
+fn quoted_values(values: &[&str]) -> String {
+    values.iter().map(|value| format!("'{}'", value)).collect::<Vec<_>>().join(", ")
+}
+
+fn relationship_description() -> String {
+    format!(
+        "Family relationship with the person who needs care. VALID VALUES: {}. Example: My mother had an accident and I'm taking care of her => 'son'; I had a baby => 'mother' or 'parent'",
+        quoted_values(VALID_RELATIONSHIPS)
+    )
+}
+
+fn situation_description() -> String {
+    format!(
+        "Situation that motivates the need for care. VALID VALUES: {}. If number of children born or adopted or fostered is greater than one at the same time, USE 'multiple_birth' or 'multiple_adoption' or 'multiple_foster_care'. Example: I had a baby => 'birth'; I adopted a child => 'adoption'; I'm fostering two kids => 'multiple_foster_care'",
+        quoted_values(VALID_SITUATIONS)
+    )
+}
+
+/// Situations in which the person being cared for is a child of the applicant
+const CHILD_CARE_SITUATIONS: &[&str] = &[
+    "birth", "adoption", "foster_care", "multiple_birth", "multiple_adoption", "multiple_foster_care",
+];
+
+/// Situations in which the applicant cares for a sick or injured relative
+const FAMILY_CARE_SITUATIONS: &[&str] = &["illness", "accident"];
+
+/// Relationship/situation pairings that are logically implausible, with the catalog key of the reason
+/// given to the caller. Add a row here to flag a new pairing; matching pairs produce warnings, never hard errors.
+const IMPLAUSIBLE_COMBINATIONS: &[(&str, &[&str], &str)] = &[
+    ("son", CHILD_CARE_SITUATIONS, "warning.reason.parent_of_child"),
+    ("daughter", CHILD_CARE_SITUATIONS, "warning.reason.parent_of_child"),
+    ("foster_parent", &["birth", "multiple_birth"], "warning.reason.birth_not_foster_care"),
+];
+
+/// Relationship synonyms commonly sent by LLMs, mapped to the value understood by the rule document.
+/// Add a row here to accept a new synonym; every replacement is reported as a warning.
+const RELATIONSHIP_SYNONYMS: &[(&str, &str)] = &[
+    ("dad", "father"),
+    ("daddy", "father"),
+    ("papa", "father"),
+    ("mom", "mother"),
+    ("mum", "mother"),
+    ("mommy", "mother"),
+    ("mama", "mother"),
+    ("kid", "son"),
+    ("boy", "son"),
+    ("girl", "daughter"),
+    ("boyfriend", "partner"),
+    ("girlfriend", "partner"),
+    ("foster_mother", "foster_parent"),
+    ("foster_father", "foster_parent"),
+    ("foster parent", "foster_parent"),
+];
+
+/// Situation synonyms commonly sent by LLMs, mapped to the value understood by the rule document
+const SITUATION_SYNONYMS: &[(&str, &str)] = &[
+    ("newborn", "birth"),
+    ("childbirth", "birth"),
+    ("delivery", "birth"),
+    ("twins", "multiple_birth"),
+    ("triplets", "multiple_birth"),
+    ("fostering", "foster_care"),
+    ("foster care", "foster_care"),
+    ("multiple birth", "multiple_birth"),
+    ("multiple adoption", "multiple_adoption"),
+    ("multiple foster care", "multiple_foster_care"),
+    ("sickness", "illness"),
+    ("disease", "illness"),
+    ("injury", "accident"),
+];
+
+/// `value` trimmed, with its inner whitespace runs collapsed into single spaces and lowercased
+fn fold_value(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Trims `value`, collapses its inner whitespace runs into single spaces and lowercases it,
+/// then replaces it with its canonical form from `synonyms`.
+/// Returns the warning to report, in `locale`, when the value changed
+fn normalize_value(field: &str, value: &mut String, synonyms: &[(&str, &'static str)], locale: Locale) -> Option<String> {
+    let folded = fold_value(value);
+    let (normalized, key) = match synonyms.iter().find(|(synonym, _)| *synonym == folded) {
+        Some((_, canonical)) => (canonical.to_string(), "warning.interpreted"),
+        None => (folded, "warning.normalized"),
+    };
+    if normalized == *value {
+        return None;
     }
+    let warning = i18n::render(locale, key, &[("field", &field), ("value", value), ("normalized", &normalized)]);
+    *value = normalized;
+    Some(warning)
+}
 
-    async fn evaluate_unpaid_leave(&self, request: &UnpaidLeaveRequest) -> Result<UnpaidLeaveResponse, UnpaidLeaveError> {
-        // Load the decision from the JSON file
-        let decision_content: DecisionContent = 
-            serde_json::from_str(include_str!("unpaid-leave-assistance-2025.json"))
-            .map_err(UnpaidLeaveError::from)?;
-        let engine = DecisionEngine::default();
-        let decision = engine.create_decision(decision_content.into());
-        
-        // Convert struct to JSON and then to Variable
-        let json_value = serde_json::to_value(request)?;
-        
-        match decision.evaluate(json_value.into()).await {
-            Ok(result) => {
-                // Convert result from Variable to Value and then deserialize directly
-                let result_value: serde_json::Value = result.result.into();
-                let response: UnpaidLeaveResponse = serde_json::from_value(result_value)?;
-                
-                Ok(response)
-            },
-            Err(zen_error) => {
-                // Attempt to extract validation error information
-                if let Some(validation_errors) = Self::extract_validation_errors(&zen_error) {
-                    Err(UnpaidLeaveError::ValidationError(validation_errors))
-                } else {
-                    Err(UnpaidLeaveError::ZenEngineError(*zen_error))
-                }
-            }
-        }
+/// Confidence lost by a value that only needed trimming, whitespace or case changes
+const FORMATTING_PENALTY: f64 = 0.05;
+/// Confidence lost by a value replaced by the canonical value of a synonym
+const SYNONYM_PENALTY: f64 = 0.2;
+/// Confidence lost by a value that is not valid even after normalization
+const UNKNOWN_VALUE_PENALTY: f64 = 0.5;
+
+/// Confidence lost by `value` of a field accepting `valid` values and `synonyms`
+fn value_penalty(value: &str, valid: &[&str], synonyms: &[(&str, &'static str)]) -> f64 {
+    if valid.contains(&value) {
+        return 0.0;
     }
-    
-    // Helper function to extract validation errors from ZEN error
-    fn extract_validation_errors(error: &EvaluationError) -> Option<Vec<ValidationError>> {
-        if let EvaluationError::NodeError(node_error) = error {
-            if let Some(errors) = Self::extract_from_node_error(node_error) {
-                return Some(errors);
-            }
+    let folded = fold_value(value);
+    if synonyms.iter().any(|(synonym, _)| *synonym == folded) {
+        SYNONYM_PENALTY
+    } else if valid.contains(&folded.as_str()) {
+        FORMATTING_PENALTY
+    } else {
+        UNKNOWN_VALUE_PENALTY
+    }
+}
+
+/// Outcome of validating an input without evaluating it
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct UnpaidLeaveValidationResult {
+    #[schemars(description = "Is the input well-formed and ready to be evaluated?")]
+    pub valid: bool,
+
+    #[schemars(description = "List of problems that would prevent or invalidate the evaluation")]
+    pub errors: Vec<ValidationError>,
+
+    #[schemars(description = "List of warnings about implausible or incomplete input")]
+    pub warnings: Vec<String>,
+}
+
+/// Levenshtein distance between two strings, used to suggest valid values
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b_chars.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b_chars.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
         }
-        
-        let error_str = format!("{:?}", error);
-        Self::extract_from_error_string(&error_str)
+        previous = current;
     }
-    
-    fn extract_from_node_error(node_error: &NodeError) -> Option<Vec<ValidationError>> {
-        let source_str = format!("{:?}", node_error.source);
-        Self::extract_json_from_string(&source_str)
+    previous[b_chars.len()]
+}
+
+/// Suggests the valid value the caller most likely meant. Only close typos
+/// (same first letter, at most two edits, five or more characters) are suggested
+/// so that e.g. 'brother' is not turned into 'mother'.
+fn suggest_value(value: &str, candidates: &[&'static str]) -> Option<&'static str> {
+    let normalized = value.trim().to_lowercase();
+    if let Some(exact) = candidates.iter().find(|candidate| **candidate == normalized) {
+        return Some(exact);
     }
-    
-    fn extract_from_error_string(error_str: &str) -> Option<Vec<ValidationError>> {
-        Self::extract_json_from_string(error_str)
+    if normalized.chars().count() < 5 {
+        return None;
     }
-    
-    fn extract_json_from_string(text: &str) -> Option<Vec<ValidationError>> {
-        let patterns = vec![
-            (r#"{"source":{"errors":"#, r#""type":"Validation"}"#),
-            (r#"{"errors":"#, r#""type":"Validation"}"#),
-            (r#""errors":["#, r#"]"#),
-        ];
-        
-        for (start_pattern, end_pattern) in patterns {
-            if let Some(start) = text.find(start_pattern) {
-                let search_from = start + start_pattern.len();
-                if let Some(relative_end) = text[search_from..].find(end_pattern) {
-                    let end = search_from + relative_end + end_pattern.len();
-                    let json_candidate = &text[start..end];
-                    
-                    if let Ok(details) = serde_json::from_str::<ValidationErrorDetails>(json_candidate) {
-                        return Some(details.source.errors);
-                    }
-                    
-                    if let Some(errors) = Self::manual_extract_errors(text) {
-                        return Some(errors);
-                    }
-                }
-            }
+    candidates
+        .iter()
+        .filter(|candidate| candidate.chars().next() == normalized.chars().next())
+        .map(|candidate| (edit_distance(&normalized, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Longest leave period accepted, in days: three years, the longest leave for child care
+const MAX_LEAVE_PERIOD_DAYS: i64 = 3 * 365 + 1;
+
+/// Builds the validation error, in `locale`, for a value outside its allowed set
+fn invalid_value_error(field: &str, value: &str, candidates: &[&'static str], locale: Locale) -> ValidationError {
+    let mut message = i18n::render(locale, "validation.invalid_value", &[("value", &value), ("field", &field), ("values", &candidates.join(", "))]);
+    if let Some(suggestion) = suggest_value(value, candidates) {
+        message.push_str(". ");
+        message.push_str(&i18n::render(locale, "validation.did_you_mean", &[("suggestion", &suggestion)]));
+    }
+    ValidationError {
+        message,
+        path: format!("/input/{}", field),
+    }
+}
+
+impl UnpaidLeaveDirectParams {
+    /// Language of the texts of this evaluation, the default one when `locale` is missing or unsupported
+    pub fn locale(&self) -> Locale {
+        self.locale.as_deref().and_then(Locale::from_tag).unwrap_or_default()
+    }
+
+    /// Returns one validation error per unexpected key received from the caller
+    pub fn unknown_field_errors(&self) -> Vec<ValidationError> {
+        self.unknown_fields
+            .keys()
+            .map(|field| ValidationError {
+                message: i18n::render(self.locale(), "validation.unknown_field", &[("field", field)]),
+                path: format!("/input/{}", field),
+            })
+            .collect()
+    }
+
+    /// Returns validation errors for values outside the documented domains
+    pub fn value_errors(&self) -> Vec<ValidationError> {
+        let locale = self.locale();
+        let mut errors = Vec::new();
+        if !VALID_RELATIONSHIPS.contains(&self.input.relationship.as_str()) {
+            errors.push(invalid_value_error("relationship", &self.input.relationship, VALID_RELATIONSHIPS, locale));
         }
-        
-        Self::manual_extract_errors(text)
+        if !VALID_SITUATIONS.contains(&self.input.situation.as_str()) {
+            errors.push(invalid_value_error("situation", &self.input.situation, VALID_SITUATIONS, locale));
+        }
+        if let Some(children) = self.input.total_children_after
+            && (children < 0.0 || children.fract() != 0.0)
+        {
+            errors.push(ValidationError {
+                message: i18n::render(locale, "validation.total_children_after", &[("value", &children)]),
+                path: "/input/total_children_after".to_string(),
+            });
+        }
+        if let Err(error) = self.parsed_effective_date() {
+            errors.push(error);
+        }
+        errors.extend(self.leave_percentage_error());
+        errors.extend(self.household_income_error());
+        errors.extend(self.household_size_error());
+        errors.extend(self.leave_period_error());
+        errors
     }
-    
-    fn manual_extract_errors(text: &str) -> Option<Vec<ValidationError>> {
-        if text.contains("is not one of") {
-            let lines: Vec<&str> = text.split(',').collect();
-            
-            let mut message = String::new();
-            let mut path = String::new();
-            
-            for line in lines {
-                if line.contains("\"message\":") {
-                    if let Some(start) = line.find("\"message\":\"") {
-                        let msg_start = start + "\"message\":\"".len();
-                        if let Some(end) = line[msg_start..].find("\"") {
-                            message = line[msg_start..msg_start + end].to_string();
-                        }
-                    }
-                }
-                if line.contains("\"path\":") {
-                    if let Some(start) = line.find("\"path\":\"") {
-                        let path_start = start + "\"path\":\"".len();
-                        if let Some(end) = line[path_start..].find("\"") {
-                            path = line[path_start..path_start + end].to_string();
-                        }
-                    }
-                }
+
+    /// Returns an error when `leave_start` and `leave_end` do not form a valid period: only one
+    /// of them given, combined with `leave_days` or `leave_months`, inverted, or longer than
+    /// `MAX_LEAVE_PERIOD_DAYS`
+    pub fn leave_period_error(&self) -> Option<ValidationError> {
+        let locale = self.locale();
+        let error = |message: String, field: &str| Some(ValidationError { message, path: format!("/input/{}", field) });
+        match (self.leave_start, self.leave_end) {
+            (None, None) => None,
+            (Some(_), None) => error(i18n::render(locale, "validation.leave_end_required", &[]), "leave_end"),
+            (None, Some(_)) => error(i18n::render(locale, "validation.leave_start_required", &[]), "leave_start"),
+            (Some(_), Some(_)) if self.leave_days.is_some() || self.leave_months.is_some() => {
+                error(i18n::render(locale, "validation.leave_period_combined", &[]), "leave_start")
             }
-            
-            if !message.is_empty() {
-                if path.is_empty() {
-                    path = "/input/unknown".to_string();
-                }
-                return Some(vec![ValidationError { message, path }]);
+            (Some(start), Some(end)) if end < start => {
+                error(i18n::render(locale, "validation.leave_end_before_start", &[("end", &end), ("start", &start)]), "leave_end")
             }
+            (Some(start), Some(end)) if (end - start).num_days() >= MAX_LEAVE_PERIOD_DAYS => error(
+                i18n::render(locale, "validation.leave_too_long", &[("start", &start), ("end", &end), ("max", &MAX_LEAVE_PERIOD_DAYS)]),
+                "leave_end",
+            ),
+            _ => None,
         }
-        
-        None
     }
-}
 
-// =================== Eligibility ENGINE MCP ===================
+    /// Returns an error when `household_income` is negative
+    pub fn household_income_error(&self) -> Option<ValidationError> {
+        match self.household_income {
+            Some(income) if !income.is_finite() || income < 0.0 => Some(ValidationError {
+                message: i18n::render(self.locale(), "validation.household_income", &[("value", &income)]),
+                path: "/input/household_income".to_string(),
+            }),
+            _ => None,
+        }
+    }
 
-#[derive(Debug, Clone)]
-pub struct EligibilityEngine {
-    tool_router: ToolRouter<Self>,
-}
+    /// Returns an error when `household_size` is 0
+    pub fn household_size_error(&self) -> Option<ValidationError> {
+        match self.household_size {
+            Some(0) => Some(ValidationError {
+                message: i18n::render(self.locale(), "validation.household_size", &[]),
+                path: "/input/household_size".to_string(),
+            }),
+            _ => None,
+        }
+    }
 
-#[tool_router]
-impl EligibilityEngine {
-    pub fn new() -> Self {
-        Self {
-            tool_router: Self::tool_router(),
+    /// Returns an error when `leave_percentage` is outside 1-100
+    pub fn leave_percentage_error(&self) -> Option<ValidationError> {
+        match self.leave_percentage {
+            Some(percentage) if !(1..=100).contains(&percentage) => Some(ValidationError {
+                message: i18n::render(self.locale(), "validation.leave_percentage", &[("value", &percentage)]),
+                path: "/input/leave_percentage".to_string(),
+            }),
+            _ => None,
         }
     }
 
-    /// Evaluates unpaid leave assistance eligibility according to fictional regulations
-    /// 
-    /// IMPORTANT: Use the exact values specified in each parameter.
-    /// IMPORTANT: If number of children is greater than one, USE 'multiple_birth' or 'multiple_adoption' or 'multiple_foster_care'.
-    /// IMPORTANT: If no information regarding the family structure use always false.
-    /// IMPORTANT: If no information regarding the number of children use always 0.
-    #[tool(description = "Evaluates unpaid leave assistance eligibility according to legal regulations. Determines case (A-E) and amount (0€/500€/725€). CASES: A=Sick family care (725€), B=Third child+ (500€), C=Adoption (500€), D=Multiple (500€), E=Single-parent (500€). USE EXACT VALUES: relationship ('father'/'mother'/'parent'/'son'/'daughter'/'spouse'/'partner'/'husband'/'wife'/'foster_parent'), situation ('birth'/'adoption'/'foster_care'/'multiple_birth'/'multiple_adoption'/'multiple_foster_care'/'illness'/'accident'), is_single_parent (true/false), total_children_after (number).")]
-    pub async fn evaluate_unpaid_leave_eligibility(
-        &self, 
-        Parameters(direct_params): Parameters<UnpaidLeaveDirectParams>
-    ) -> Result<CallToolResult, McpError> {
-        // Initialize metrics tracking
-        let _timer = RequestTimer::new();
-        increment_requests();
-        // Convert direct parameters to nested structure expected by the engine
-        let request = UnpaidLeaveRequest {
-            input: UnpaidLeaveInput {
-                relationship: direct_params.relationship,
-                situation: direct_params.situation,
-                is_single_parent: direct_params.is_single_parent,
-                total_children_after: direct_params.total_children_after,
-            }
+    /// Total length of the leave in days: the days from `leave_start` to `leave_end`, both included,
+    /// or else `leave_days` plus `leave_months` counted as `DAYS_PER_MONTH` days each.
+    /// `None` when no length was provided.
+    pub fn leave_duration_days(&self) -> Option<u32> {
+        if let (Some(start), Some(end)) = (self.leave_start, self.leave_end) {
+            return u32::try_from((end - start).num_days() + 1).ok();
+        }
+        match (self.leave_days, self.leave_months) {
+            (None, None) => None,
+            (days, months) => Some(days.unwrap_or(0).saturating_add(months.unwrap_or(0).saturating_mul(DAYS_PER_MONTH))),
+        }
+    }
+
+    /// Parses `effective_date`, returning `None` when it was not provided
+    pub fn parsed_effective_date(&self) -> Result<Option<NaiveDate>, ValidationError> {
+        self.effective_date
+            .as_deref()
+            .map(|date| parse_date(date).ok_or_else(|| ValidationError {
+                message: i18n::render(self.locale(), "validation.effective_date", &[("value", &date)]),
+                path: "/input/effective_date".to_string(),
+            }))
+            .transpose()
+    }
+
+    /// Trimmed, lowercased `tenant_id`, `None` when it was not provided or is blank
+    pub fn normalized_tenant_id(&self) -> Option<String> {
+        self.tenant_id
+            .as_deref()
+            .map(|tenant_id| tenant_id.trim().to_lowercase())
+            .filter(|tenant_id| !tenant_id.is_empty())
+    }
+
+    /// Lowercases and trims `relationship` and `situation` and replaces known synonyms with their
+    /// canonical values, returning one warning per changed field
+    pub fn normalize(&mut self) -> Vec<String> {
+        let locale = self.locale();
+        let mut warnings = Vec::new();
+        warnings.extend(normalize_value("relationship", &mut self.input.relationship, RELATIONSHIP_SYNONYMS, locale));
+        warnings.extend(normalize_value("situation", &mut self.input.situation, SITUATION_SYNONYMS, locale));
+        warnings
+    }
+
+    /// How certain the determination is given how much `relationship` and `situation`, as received,
+    /// had to be interpreted: 1.0 for exact valid values, minus `FORMATTING_PENALTY` per value that
+    /// only needed case or whitespace fixes, `SYNONYM_PENALTY` per synonym replaced by its canonical
+    /// value and `UNKNOWN_VALUE_PENALTY` per value still invalid after normalization, never below 0
+    pub fn confidence(&self) -> f64 {
+        let penalty = value_penalty(&self.input.relationship, VALID_RELATIONSHIPS, RELATIONSHIP_SYNONYMS)
+            + value_penalty(&self.input.situation, VALID_SITUATIONS, SITUATION_SYNONYMS);
+        // Rounded so that penalties add up to round numbers
+        ((1.0 - penalty).max(0.0) * 100.0).round() / 100.0
+    }
+
+    /// Runs every input check without evaluating eligibility
+    pub fn validate(&self, strict_parsing: bool) -> UnpaidLeaveValidationResult {
+        let mut params = self.clone();
+        let mut warnings = params.normalize();
+
+        let mut errors = params.value_errors();
+        if strict_parsing {
+            errors.extend(params.unknown_field_errors());
+        }
+
+        warnings.extend(params.cross_field_warnings());
+        warnings.extend(params.children_count_warnings());
+
+        UnpaidLeaveValidationResult {
+            valid: errors.is_empty(),
+            errors,
+            warnings,
+        }
+    }
+
+    /// Returns warnings for relationship/situation pairings listed in `IMPLAUSIBLE_COMBINATIONS`
+    pub fn cross_field_warnings(&self) -> Vec<String> {
+        IMPLAUSIBLE_COMBINATIONS
+            .iter()
+            .filter(|(relationship, situations, _)| {
+                *relationship == self.input.relationship && situations.contains(&self.input.situation.as_str())
+            })
+            .map(|(relationship, _, reason)| {
+                let reason = i18n::render(self.locale(), reason, &[]);
+                i18n::render(
+                    self.locale(),
+                    "warning.implausible_combination",
+                    &[("relationship", relationship), ("situation", &self.input.situation), ("reason", &reason)],
+                )
+            })
+            .collect()
+    }
+
+    /// Returns warnings when `total_children_after` does not fit the situation
+    pub fn children_count_warnings(&self) -> Vec<String> {
+        let situation = self.input.situation.as_str();
+        match self.input.total_children_after {
+            Some(children) if children > 0.0 && FAMILY_CARE_SITUATIONS.contains(&situation) => {
+                vec![i18n::render(self.locale(), "warning.children_ignored", &[("children", &children), ("situation", &situation)])]
+            }
+            None if CHILD_CARE_SITUATIONS.contains(&situation) => {
+                vec![i18n::render(self.locale(), "warning.children_missing", &[("situation", &situation)])]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Converts the flat MCP parameters into the nested engine request, normalizing synonyms,
+    /// and returns the warnings detected on the way
+    pub fn into_request(mut self) -> (UnpaidLeaveRequest, Vec<String>) {
+        let mut warnings = self.normalize();
+        warnings.extend(self.cross_field_warnings());
+        warnings.extend(self.children_count_warnings());
+
+        let request = UnpaidLeaveRequest { input: self.into() };
+
+        (request, warnings)
+    }
+}
+
+/// Engine input for the flat MCP parameters, with `relationship` and `situation` normalized.
+/// The extra parameters (effective date, leave length) are not part of the rule document input
+impl From<UnpaidLeaveDirectParams> for UnpaidLeaveInput {
+    fn from(mut params: UnpaidLeaveDirectParams) -> Self {
+        params.normalize();
+        params.input
+    }
+}
+
+// =================== ELIGIBILITY CASES ===================
+
+/// Case defined by the regulation, mirroring the eligible rows of the rule document
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct EligibilityCase {
+    #[schemars(description = "Letter of the case according to regulations")]
+    pub case: &'static str,
+
+    #[schemars(description = "Description of the case, as returned by the evaluation")]
+    pub description: &'static str,
+
+    #[schemars(description = "Monthly benefit amount in euros")]
+    pub monthly_benefit: i32,
+
+    #[schemars(description = "Requirements to prove besides the ones evaluated, as returned by the evaluation")]
+    pub additional_requirements: &'static [&'static str],
+
+    /// Identifiers of `additional_requirements`, in the same order
+    #[serde(skip)]
+    pub requirement_ids: &'static [&'static str],
+}
+
+/// Cases A-E. Descriptions, amounts and requirements must match `unpaid-leave-assistance-2025.json`
+const ELIGIBILITY_CASES: &[EligibilityCase] = &[
+    EligibilityCase {
+        case: "A",
+        description: "First-degree family care sick or accident victim",
+        monthly_benefit: 725,
+        additional_requirements: &["The person must have been hospitalized and the care of the person must be continued"],
+        requirement_ids: &["hospitalized_continued_care"],
+    },
+    EligibilityCase {
+        case: "B",
+        description: "Third child or more with newborn",
+        monthly_benefit: 500,
+        additional_requirements: &[
+            "The number of children must be 3 or more, the ages of at least 2 of the minors must be less than 6, if there is disability greater than 33% then the limit is 9 years",
+        ],
+        requirement_ids: &["three_children_under_six"],
+    },
+    EligibilityCase {
+        case: "C",
+        description: "Adoption or foster care",
+        monthly_benefit: 500,
+        additional_requirements: &["In the foster care case the duration must be longer than one year"],
+        requirement_ids: &["foster_care_over_one_year"],
+    },
+    EligibilityCase {
+        case: "D",
+        description: "Delivery, adoption or foster care multiple",
+        monthly_benefit: 500,
+        additional_requirements: &[],
+        requirement_ids: &[],
+    },
+    EligibilityCase {
+        case: "E",
+        description: "Single-parent family with newborn",
+        monthly_benefit: 500,
+        additional_requirements: &["The single-parent status must be documented"],
+        requirement_ids: &["single_parent_documented"],
+    },
+];
+
+/// Message keys of the next steps of an eligible applicant by case, texts are in the i18n catalogs
+const NEXT_STEPS: &[(&str, &[&str])] = &[
+    ("A", &["next_steps.apply", "next_steps.medical_certificate"]),
+    ("B", &["next_steps.apply", "next_steps.children_certificates"]),
+    ("C", &["next_steps.apply", "next_steps.adoption_resolution"]),
+    ("D", &["next_steps.apply", "next_steps.children_certificates"]),
+    ("E", &["next_steps.apply", "next_steps.single_parent_certificate"]),
+];
+
+/// Message keys of the next steps of an applicant who is not potentially eligible, whatever the case
+const NOT_ELIGIBLE_NEXT_STEPS: &[&str] = &["next_steps.review_errors", "next_steps.contact_administration"];
+
+/// Next steps after `output` in `locale`. Eligible outputs of cases without an entry, e.g. from a custom
+/// rule document, only get the generic application step
+fn next_steps(output: &UnpaidLeaveOutputForSchema, locale: Locale) -> Vec<String> {
+    let keys = if output.potentially_eligible {
+        NEXT_STEPS
+            .iter()
+            .find(|(case, _)| *case == output.case)
+            .map(|(_, keys)| *keys)
+            .unwrap_or(&["next_steps.apply"])
+    } else {
+        NOT_ELIGIBLE_NEXT_STEPS
+    };
+    keys.iter().filter_map(|key| i18n::message(locale, key)).map(str::to_string).collect()
+}
+
+/// Whether the input shows a requirement met
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RequirementStatus {
+    Met,
+    Unmet,
+    Unknown,
+}
+
+/// One entry of the requirements checklist of an evaluation
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct Requirement {
+    #[schemars(description = "Stable identifier of the requirement, such as 'hospitalized_continued_care'")]
+    pub id: String,
+
+    #[schemars(description = "Text of the requirement, the same as in additional_requirements")]
+    pub description: String,
+
+    #[schemars(description = "'met' or 'unmet' when the input tells, 'unknown' when it must be proven")]
+    pub status: RequirementStatus,
+}
+
+/// Status of requirement `id` as far as `input` tells
+fn requirement_status(id: &str, input: &UnpaidLeaveInput) -> RequirementStatus {
+    match id {
+        // Only the number of children is known, not their ages
+        "three_children_under_six" => match input.total_children_after {
+            Some(children) if children < 3.0 => RequirementStatus::Unmet,
+            _ => RequirementStatus::Unknown,
+        },
+        // Adoptions have no minimum duration
+        "foster_care_over_one_year" if !input.situation.contains("foster_care") => RequirementStatus::Met,
+        _ => RequirementStatus::Unknown,
+    }
+}
+
+/// Checklist of the additional requirements of `case` in `locale`, empty for unknown cases
+fn case_requirements(case: &str, input: &UnpaidLeaveInput, locale: Locale) -> Vec<Requirement> {
+    let Some(eligibility_case) = ELIGIBILITY_CASES.iter().find(|eligibility_case| eligibility_case.case == case) else {
+        return Vec::new();
+    };
+    eligibility_case
+        .requirement_ids
+        .iter()
+        .zip(eligibility_case.additional_requirements)
+        .map(|(id, text)| Requirement {
+            id: id.to_string(),
+            description: i18n::rule_text(locale, text),
+            status: requirement_status(id, input),
+        })
+        .collect()
+}
+
+/// Case to describe without running an evaluation
+#[derive(Debug, Default, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct DescribeCaseParams {
+    #[schemars(description = "Letter of the case: 'A', 'B', 'C', 'D' or 'E'")]
+    pub case: String,
+    #[schemars(description = "Optional language of description and additional_requirements: 'es' (default) or 'en'. Over HTTP, the Accept-Language header is used when omitted")]
+    #[serde(default)]
+    pub locale: Option<String>,
+}
+
+/// Full details of a case, with the same texts and amount an evaluation falling under it returns
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct CaseDescription {
+    #[schemars(description = "Letter of the case according to regulations")]
+    pub case: String,
+    #[schemars(description = "Description of the case")]
+    pub description: String,
+    #[schemars(description = "Monthly benefit amount in euros, including configured amounts")]
+    pub monthly_benefit: i32,
+    #[schemars(description = "Requirements to prove besides the ones evaluated, empty when there are none")]
+    pub additional_requirements: Vec<String>,
+    #[schemars(description = "Language of description and additional_requirements")]
+    pub locale: String,
+}
+
+/// Machine-readable list of every accepted parameter value and case
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct EligibilityOptions {
+    #[schemars(description = "Valid values for the relationship parameter")]
+    pub relationships: &'static [&'static str],
+
+    #[schemars(description = "Valid values for the situation parameter")]
+    pub situations: &'static [&'static str],
+
+    #[schemars(description = "Cases evaluated by the regulation")]
+    pub cases: &'static [EligibilityCase],
+}
+
+impl EligibilityOptions {
+    pub fn new() -> Self {
+        Self {
+            relationships: VALID_RELATIONSHIPS,
+            situations: VALID_SITUATIONS,
+            cases: ELIGIBILITY_CASES,
+        }
+    }
+}
+
+/// Children a two-parent family needs after a single birth to fall under case B
+const CASE_B_MIN_CHILDREN: f64 = 3.0;
+
+/// Plain-language reasons, in `locale`, why `input` did not lead to an eligible `output`: the
+/// requirements the input misses, followed by the errors reported for the evaluation
+pub fn explain_not_eligible(input: &UnpaidLeaveInput, output: &UnpaidLeaveOutputForSchema, locale: Locale) -> String {
+    let mut reasons = Vec::new();
+    if !VALID_RELATIONSHIPS.contains(&input.relationship.as_str()) {
+        reasons.push(i18n::render(
+            locale,
+            "explanation.relationship",
+            &[("relationship", &input.relationship), ("relationships", &quoted_values(VALID_RELATIONSHIPS))],
+        ));
+    } else if input.situation == "birth" && !input.is_single_parent {
+        match input.total_children_after {
+            Some(children) if children >= CASE_B_MIN_CHILDREN => {}
+            Some(children) => reasons.push(i18n::render(locale, "explanation.third_child", &[("children", &children)])),
+            None => reasons.push(i18n::render(locale, "explanation.third_child_unknown", &[])),
+        }
+    }
+
+    reasons.extend(output.errors.iter().map(|error| {
+        let error = error.trim_end_matches('.');
+        format!("{}.", error)
+    }));
+    if reasons.is_empty() {
+        reasons.push(i18n::render(
+            locale,
+            "explanation.no_case",
+            &[("relationship", &input.relationship), ("situation", &input.situation)],
+        ));
+    }
+    reasons.join(" ")
+}
+
+// =================== DECISION ENGINE ===================
+
+type UnpaidLeaveDecision = Decision<NoopLoader, NoopCustomNode>;
+
+/// Version of the embedded rule document, reported to clients so they know which ruleset is served
+pub const RULES_VERSION: &str = "unpaid-leave-assistance-2025";
+
+/// MCP resource serving the rule document of the ruleset currently in effect
+pub const RULES_RESOURCE_URI: &str = "eligibility://rules/current";
+
+/// Date from which the embedded rule document applies
+const RULES_EFFECTIVE_FROM: (i32, u32, u32) = (2025, 4, 1);
+
+/// Rule document parsed into a decision, applicable from `effective_from`
+/// until the next ruleset of the registry takes over
+#[derive(Debug, Clone)]
+pub struct Ruleset {
+    pub version: String,
+    pub effective_from: NaiveDate,
+    // Unique per parsed document, so cached results never outlive the rules that produced them
+    id: u64,
+    // Hex SHA-256 of the rule document, to tell replicas serving different rules apart
+    checksum: String,
+    // Kept next to the decision built from it, for clients that want to inspect the rules
+    content: Arc<DecisionContent>,
+    decision: Arc<UnpaidLeaveDecision>,
+}
+
+/// Source of `Ruleset::id`
+static NEXT_RULESET_ID: AtomicU64 = AtomicU64::new(0);
+
+impl Ruleset {
+    /// Parses a rule document in the GoRules JDM format
+    pub fn from_json(version: &str, effective_from: NaiveDate, rules: &str) -> Result<Self, serde_json::Error> {
+        Ok(Self::build(version, effective_from, serde_json::from_str(rules)?, sha256_hex(rules.as_bytes())))
+    }
+
+    /// Builds the decision of an already parsed rule document. The checksum is the one of the
+    /// document serialized again, so it may differ from the checksum of the original text
+    pub fn from_content(version: &str, effective_from: NaiveDate, content: DecisionContent) -> Self {
+        let checksum = sha256_hex(&serde_json::to_vec(&content).unwrap_or_default());
+        Self::build(version, effective_from, content, checksum)
+    }
+
+    fn build(version: &str, effective_from: NaiveDate, content: DecisionContent, checksum: String) -> Self {
+        let content = Arc::new(content);
+        Self {
+            version: version.to_string(),
+            effective_from,
+            id: NEXT_RULESET_ID.fetch_add(1, Ordering::Relaxed),
+            checksum,
+            decision: Arc::new(DecisionEngine::default().create_decision(content.clone())),
+            content,
+        }
+    }
+
+    /// Hex SHA-256 of the rule document, the same `sha256sum` gives for the file it was read from
+    pub fn checksum(&self) -> &str {
+        &self.checksum
+    }
+
+    /// Rule document the decision was built from
+    pub fn content(&self) -> &DecisionContent {
+        &self.content
+    }
+}
+
+/// Lowercase hex SHA-256 of `bytes`
+fn sha256_hex(bytes: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Rulesets ordered by the date they come into effect
+#[derive(Debug, Clone, Default)]
+pub struct RulesetRegistry {
+    rulesets: Vec<Ruleset>,
+}
+
+impl RulesetRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a ruleset, keeping the registry ordered by effective date
+    pub fn with_ruleset(mut self, ruleset: Ruleset) -> Self {
+        let position = self.rulesets.partition_point(|existing| existing.effective_from <= ruleset.effective_from);
+        self.rulesets.insert(position, ruleset);
+        self
+    }
+
+    /// Every ruleset, in the order they come into effect
+    pub fn iter(&self) -> impl Iterator<Item = &Ruleset> {
+        self.rulesets.iter()
+    }
+
+    /// Ruleset in effect on `date`, or the latest one when no date is given
+    pub fn select(&self, date: Option<NaiveDate>) -> Option<&Ruleset> {
+        match date {
+            Some(date) => self.rulesets.iter().rev().find(|ruleset| ruleset.effective_from <= date),
+            None => self.rulesets.last(),
+        }
+    }
+}
+
+/// Rulesets of the tenants of a server hosting several organizations, keyed by tenant id
+#[derive(Debug, Clone, Default)]
+pub struct TenantRegistry {
+    tenants: BTreeMap<String, Arc<RulesetRegistry>>,
+}
+
+impl TenantRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Evaluates the inputs of `tenant_id` against `rulesets`
+    pub fn with_tenant(mut self, tenant_id: &str, rulesets: Arc<RulesetRegistry>) -> Self {
+        self.tenants.insert(tenant_id.to_string(), rulesets);
+        self
+    }
+
+    /// Rulesets of `tenant_id`, `None` for unknown tenants
+    pub fn get(&self, tenant_id: &str) -> Option<Arc<RulesetRegistry>> {
+        self.tenants.get(tenant_id).cloned()
+    }
+
+    /// Every tenant with its rulesets
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Arc<RulesetRegistry>)> {
+        self.tenants.iter().map(|(tenant_id, rulesets)| (tenant_id.as_str(), rulesets))
+    }
+
+    /// One tenant per `<tenant_id>.json` rule document in `dir`, applicable from `effective_from`.
+    /// Tenant ids may only hold lowercase letters, digits, '-' and '_'
+    fn from_dir(dir: &Path, effective_from: NaiveDate) -> Result<Self, String> {
+        let entries = std::fs::read_dir(dir).map_err(|e| format!("Cannot read tenant rules directory '{}': {}", dir.display(), e))?;
+        let mut registry = Self::new();
+        for entry in entries {
+            let path = entry.map_err(|e| format!("Cannot read tenant rules directory '{}': {}", dir.display(), e))?.path();
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+            let tenant_id = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+            if tenant_id.is_empty()
+                || !tenant_id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+            {
+                return Err(format!("Invalid tenant id '{}' in '{}', use lowercase letters, digits, '-' and '_'", tenant_id, path.display()));
+            }
+            let ruleset = ruleset_from_file(&path, effective_from)?;
+            registry = registry.with_tenant(&tenant_id, Arc::new(RulesetRegistry::new().with_ruleset(ruleset)));
+        }
+        Ok(registry)
+    }
+}
+
+/// Directory with one rule document per tenant, named `<tenant_id>.json`. Only the shared rules
+/// are served when unset
+pub const TENANT_RULES_DIR_ENV: &str = "TENANT_RULES_DIR";
+
+/// Tenants loaded once from `TENANT_RULES_DIR`
+static TENANTS: Lazy<Result<Arc<TenantRegistry>, String>> = Lazy::new(|| {
+    let Some(dir) = std::env::var_os(TENANT_RULES_DIR_ENV) else {
+        return Ok(Arc::new(TenantRegistry::new()));
+    };
+    let (year, month, day) = RULES_EFFECTIVE_FROM;
+    let effective_from = NaiveDate::from_ymd_opt(year, month, day).expect("valid effective date");
+    TenantRegistry::from_dir(Path::new(&dir), effective_from).map(Arc::new)
+});
+
+/// Rule document replacing the embedded one, served under the file name as its version
+pub const RULES_PATH_ENV: &str = "RULES_PATH";
+
+/// Rule document path given on the command line, takes precedence over `RULES_PATH`
+static RULES_PATH: OnceCell<PathBuf> = OnceCell::new();
+
+/// Serves the rule document at `path` instead of the embedded one.
+/// Must be called before the rules are first loaded, later calls are ignored
+#[allow(dead_code)] // Used by the binaries with command-line arguments
+pub fn set_rules_path(path: PathBuf) {
+    let _ = RULES_PATH.set(path);
+}
+
+/// Reads, parses and validates the rule document at `path`, versioned after its file name
+fn ruleset_from_file(path: &Path, effective_from: NaiveDate) -> Result<Ruleset, String> {
+    let rules = std::fs::read_to_string(path).map_err(|e| format!("Cannot read rule document '{}': {}", path.display(), e))?;
+    let version = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    let ruleset = Ruleset::from_json(&version, effective_from, &rules)
+        .map_err(|e| format!("Failed to parse rule document '{}': {}", path.display(), e))?;
+    validate_decision_content(ruleset.content())
+        .map_err(|problems| format!("Rule document '{}' is not valid: {}", path.display(), problems.join("; ")))?;
+    Ok(ruleset)
+}
+
+/// Checks that a rule document can serve evaluations: its graph is well-formed, with one input
+/// node and an output node, the node schemas declare the `input` and `output` objects the server
+/// exchanges, and the health check canary resolves to the expected case.
+/// Returns every problem found, in a form meant for the rule author
+pub fn validate_decision_content(content: &DecisionContent) -> Result<(), Vec<String>> {
+    let engine = check_decision_graph(content)?;
+    canary_problems(run_canary(&engine))
+}
+
+/// Same as `validate_decision_content`, for documents supplied at run time: the canary runs on a
+/// blocking thread, and taking longer than `deadline` is a problem of the document like any other.
+/// On timeout the canary is left to finish on its thread
+pub async fn validate_decision_content_within(content: &DecisionContent, deadline: Duration) -> Result<(), Vec<String>> {
+    let engine = check_decision_graph(content)?;
+    let canary = tokio::task::spawn_blocking(move || evaluate_canary(&engine));
+    let result = match with_deadline(deadline, canary).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(join_error)) => Err(UnpaidLeaveError::InternalError(join_error.to_string())),
+        Err(timeout) => Err(timeout),
+    };
+    canary_problems(result)
+}
+
+/// Checks the graph and node schemas of a rule document, returning the engine to run its canary
+/// once nothing is wrong, as the canary only means something on a graph the engine accepts
+fn check_decision_graph(content: &DecisionContent) -> Result<UnpaidLeaveDecisionEngine, Vec<String>> {
+    use zen_engine::model::DecisionNodeKind;
+
+    let mut problems = Vec::new();
+    let decision = DecisionEngine::default().create_decision(Arc::new(content.clone()));
+    if let Err(e) = decision.validate() {
+        problems.push(format!("The decision graph is not valid: {}", e));
+    }
+
+    let mut output_nodes = 0;
+    for node in &content.nodes {
+        let (schema, object) = match &node.kind {
+            DecisionNodeKind::InputNode { content } => (&content.schema, "input"),
+            DecisionNodeKind::OutputNode { content } => {
+                output_nodes += 1;
+                (&content.schema, "output")
+            }
+            _ => continue,
+        };
+        let Some(schema) = schema else { continue };
+        match serde_json::from_str::<serde_json::Value>(schema) {
+            Ok(schema) if schema["properties"].get(object).is_some() => {}
+            Ok(_) => problems.push(format!("The schema of node '{}' does not declare the '{}' object", node.name, object)),
+            Err(e) => problems.push(format!("The schema of node '{}' is not valid JSON: {}", node.name, e)),
+        }
+    }
+    if output_nodes == 0 {
+        problems.push("The decision graph has no output node".to_string());
+    }
+
+    if problems.is_empty() {
+        Ok(UnpaidLeaveDecisionEngine { decision: Arc::new(decision), rules_version: String::new() })
+    } else {
+        Err(problems)
+    }
+}
+
+/// Problems shown by the outcome of the canary, none when it resolved to the expected case
+fn canary_problems(canary: Result<UnpaidLeaveResponse, UnpaidLeaveError>) -> Result<(), Vec<String>> {
+    match canary {
+        Ok(response) if response.output.case == HEALTH_CHECK_CASE => Ok(()),
+        Ok(response) => Err(vec![format!(
+            "Canary evaluation returned case '{}', expected '{}'",
+            response.output.case, HEALTH_CHECK_CASE
+        )]),
+        Err(e) => Err(vec![format!("Canary evaluation failed: {}", e)]),
+    }
+}
+
+/// Evaluates the health check canary through `engine` on a thread of its own, so rule documents
+/// can be validated from inside a runtime without nesting another one in it
+fn run_canary(engine: &UnpaidLeaveDecisionEngine) -> Result<UnpaidLeaveResponse, UnpaidLeaveError> {
+    std::thread::scope(|scope| {
+        scope
+            .spawn(|| evaluate_canary(engine))
+            .join()
+            .unwrap_or(Err(UnpaidLeaveError::Panicked))
+    })
+}
+
+/// Evaluates the health check canary through `engine` on a runtime of its own, blocking the thread
+fn evaluate_canary(engine: &UnpaidLeaveDecisionEngine) -> Result<UnpaidLeaveResponse, UnpaidLeaveError> {
+    let (relationship, situation) = HEALTH_CHECK_INPUT;
+    let canary = UnpaidLeaveDirectParams {
+        input: UnpaidLeaveInput {
+            relationship: relationship.to_string(),
+            situation: situation.to_string(),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let (request, _) = canary.into_request();
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| UnpaidLeaveError::InternalError(format!("cannot create evaluation runtime: {}", e)))?;
+    rt.block_on(engine.evaluate_unpaid_leave(&request, false))
+}
+
+/// Registry built once from the embedded rule document, or the one at the rules path,
+/// and shared by every evaluation
+static DEFAULT_RULESETS: Lazy<Result<Arc<RulesetRegistry>, String>> = Lazy::new(|| {
+    let (year, month, day) = RULES_EFFECTIVE_FROM;
+    let effective_from = NaiveDate::from_ymd_opt(year, month, day).expect("valid effective date");
+    let rules_path = RULES_PATH.get().cloned().or_else(|| std::env::var_os(RULES_PATH_ENV).map(PathBuf::from));
+    let ruleset = match rules_path {
+        Some(path) => ruleset_from_file(&path, effective_from)?,
+        None => {
+            let ruleset = Ruleset::from_json(RULES_VERSION, effective_from, include_str!("unpaid-leave-assistance-2025.json"))
+                .map_err(|e| format!("Failed to parse rule document: {}", e))?;
+            validate_decision_content(ruleset.content())
+                .map_err(|problems| format!("Embedded rule document is not valid: {}", problems.join("; ")))?;
+            ruleset
+        }
+    };
+    Ok(Arc::new(RulesetRegistry::new().with_ruleset(ruleset)))
+});
+
+/// Returns the registry of the default rule documents
+fn default_rulesets() -> Result<Arc<RulesetRegistry>, UnpaidLeaveError> {
+    match &*DEFAULT_RULESETS {
+        Ok(registry) => Ok(registry.clone()),
+        Err(e) => Err(UnpaidLeaveError::RulesUnavailable(e.clone())),
+    }
+}
+
+/// Loads the rule documents, the tenant ones included, if they have not been loaded yet,
+/// returning an error if any cannot be parsed
+pub fn load_rules() -> Result<(), String> {
+    let rulesets = DEFAULT_RULESETS.as_ref().map_err(|e| e.clone())?;
+    let tenants = TENANTS.as_ref().map_err(|e| e.clone())?;
+    for (tenant_id, _) in tenants.iter() {
+        tracing::info!("Serving the rules of tenant {}", tenant_id);
+    }
+    record_rules(&METRICS, rulesets, tenants);
+    Ok(())
+}
+
+/// Logs the checksum of every loaded ruleset and exports it in `eligibility_rules_info`,
+/// replacing the rulesets recorded before so rules loaded again do not leave stale series
+fn record_rules(metrics: &EligibilityMetrics, rulesets: &RulesetRegistry, tenants: &TenantRegistry) {
+    let loaded = std::iter::once((None, rulesets))
+        .chain(tenants.iter().map(|(tenant_id, rulesets)| (Some(tenant_id), rulesets.as_ref())))
+        .flat_map(|(tenant_id, rulesets)| rulesets.iter().map(move |ruleset| (tenant_id, ruleset)));
+    metrics.reset_rules_info();
+    for (tenant_id, ruleset) in loaded {
+        tracing::info!(
+            tenant = tenant_id.unwrap_or(DEFAULT_TENANT_LABEL),
+            rules_version = %ruleset.version,
+            rules_checksum = %ruleset.checksum(),
+            "Loaded rule document"
+        );
+        metrics.set_rules_info(&ruleset.version, ruleset.checksum(), tenant_id);
+    }
+}
+
+/// Loads the rule document and runs the health check canary through it.
+/// Binaries call this at startup so that a rule document that cannot be parsed, or that
+/// no longer produces the expected case, stops the server instead of failing requests
+pub async fn verify_rules() -> Result<(), String> {
+    load_rules()?;
+    verify_rulesets(default_rulesets().map_err(|e| e.to_string())?).await?;
+    if let Ok(tenants) = &*TENANTS {
+        for (tenant_id, rulesets) in tenants.iter() {
+            verify_rulesets(rulesets.clone()).await.map_err(|e| format!("Tenant {}: {}", tenant_id, e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Runs the health check canary through `rulesets`
+async fn verify_rulesets(rulesets: Arc<RulesetRegistry>) -> Result<(), String> {
+    EligibilityEngine::new()
+        .with_result_cache(None)
+        .with_rulesets(rulesets)
+        .health_check()
+        .await
+        .map_err(|e| format!("Rule document failed the startup check: {}", e))
+}
+
+/// Whether the default rule document and every tenant one were loaded and parsed.
+/// Rules are only loaded once, and the binaries stop when `verify_rules` fails, so on a
+/// serving process this is the outcome of startup and does not change afterwards
+pub fn rules_ready() -> bool {
+    DEFAULT_RULESETS.is_ok() && TENANTS.is_ok()
+}
+
+#[derive(Debug, Clone)]
+struct UnpaidLeaveDecisionEngine {
+    decision: Arc<UnpaidLeaveDecision>,
+    rules_version: String,
+}
+
+impl UnpaidLeaveDecisionEngine {
+    fn new(ruleset: &Ruleset) -> Self {
+        Self {
+            decision: ruleset.decision.clone(),
+            rules_version: ruleset.version.clone(),
+        }
+    }
+
+    async fn evaluate_unpaid_leave(&self, request: &UnpaidLeaveRequest, trace: bool) -> Result<UnpaidLeaveResponse, UnpaidLeaveError> {
+        // Convert struct to JSON and then to Variable
+        let json_value = serde_json::to_value(request)?;
+        let options = EvaluationOptions { trace: Some(trace), max_depth: None };
+        
+        match self.decision.evaluate_with_opts(json_value.into(), options).await {
+            Ok(result) => {
+                // Convert result from Variable to Value and then deserialize directly
+                let result_value: serde_json::Value = result.result.into();
+                let mut response: UnpaidLeaveResponse = serde_json::from_value(result_value)?;
+                response.rules_version = self.rules_version.clone();
+
+                // Trace steps hold engine variables, so they are converted here, before leaving the engine thread
+                if let Some(node_traces) = result.trace {
+                    let mut steps: Vec<DecisionTraceStep> = node_traces.into_values().map(DecisionTraceStep::from).collect();
+                    steps.sort_by_key(|step| step.order);
+                    response.trace = Some(steps);
+                }
+                
+                Ok(response)
+            },
+            Err(zen_error) => {
+                // Attempt to extract validation error information
+                if let Some(validation_errors) = Self::extract_validation_errors(&zen_error) {
+                    Err(UnpaidLeaveError::ValidationError(validation_errors))
+                } else {
+                    Err(UnpaidLeaveError::ZenEngineError(*zen_error))
+                }
+            }
+        }
+    }
+    
+    // Helper function to extract validation errors from ZEN error
+    fn extract_validation_errors(error: &EvaluationError) -> Option<Vec<ValidationError>> {
+        if let EvaluationError::NodeError(node_error) = error
+            && let Some(errors) = Self::extract_from_node_error(node_error)
+        {
+            return Some(errors);
+        }
+        
+        let error_str = format!("{:?}", error);
+        Self::extract_from_error_string(&error_str)
+    }
+    
+    fn extract_from_node_error(node_error: &NodeError) -> Option<Vec<ValidationError>> {
+        let source_str = format!("{:?}", node_error.source);
+        Self::extract_json_from_string(&source_str)
+    }
+    
+    fn extract_from_error_string(error_str: &str) -> Option<Vec<ValidationError>> {
+        Self::extract_json_from_string(error_str)
+    }
+    
+    fn extract_json_from_string(text: &str) -> Option<Vec<ValidationError>> {
+        let patterns = vec![
+            (r#"{"source":{"errors":"#, r#""type":"Validation"}"#),
+            (r#"{"errors":"#, r#""type":"Validation"}"#),
+            (r#""errors":["#, r#"]"#),
+        ];
+        
+        for (start_pattern, end_pattern) in patterns {
+            if let Some(start) = text.find(start_pattern) {
+                let search_from = start + start_pattern.len();
+                if let Some(relative_end) = text[search_from..].find(end_pattern) {
+                    let end = search_from + relative_end + end_pattern.len();
+                    let json_candidate = &text[start..end];
+                    
+                    if let Ok(details) = serde_json::from_str::<ValidationErrorDetails>(json_candidate) {
+                        return Some(details.source.errors);
+                    }
+                    
+                    if let Some(errors) = Self::manual_extract_errors(text) {
+                        return Some(errors);
+                    }
+                }
+            }
+        }
+        
+        Self::manual_extract_errors(text)
+    }
+    
+    fn manual_extract_errors(text: &str) -> Option<Vec<ValidationError>> {
+        if text.contains("is not one of") {
+            let lines: Vec<&str> = text.split(',').collect();
+            
+            let mut message = String::new();
+            let mut path = String::new();
+            
+            for line in lines {
+                if line.contains("\"message\":")
+                    && let Some(start) = line.find("\"message\":\"")
+                {
+                    let msg_start = start + "\"message\":\"".len();
+                    if let Some(end) = line[msg_start..].find("\"") {
+                        message = line[msg_start..msg_start + end].to_string();
+                    }
+                }
+                if line.contains("\"path\":")
+                    && let Some(start) = line.find("\"path\":\"")
+                {
+                    let path_start = start + "\"path\":\"".len();
+                    if let Some(end) = line[path_start..].find("\"") {
+                        path = line[path_start..path_start + end].to_string();
+                    }
+                }
+            }
+            
+            if !message.is_empty() {
+                if path.is_empty() {
+                    path = "/input/unknown".to_string();
+                }
+                return Some(vec![ValidationError { message, path }]);
+            }
+        }
+        
+        None
+    }
+}
+
+// =================== Eligibility ENGINE MCP ===================
+
+/// Environment variable that enables strict input parsing when set to "true" or "1"
+pub const STRICT_PARSING_ENV: &str = "STRICT_INPUT_PARSING";
+
+// Basic server information from .env file (replaced by sync script during release).
+// The version is the crate's, the same `eligibility_build_info` exports
+pub const SERVER_NAME: &str = "eligibility-engine-mcp-rs";
+pub const SERVER_VERSION: &str = VERSION;
+pub const SERVER_TITLE: &str = "Eligibility Engine MCP Server";
+
+/// Server metadata served by the /version endpoint
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ServerVersionInfo {
+    pub name: String,
+    pub version: String,
+    pub title: String,
+    pub rules_version: String,
+    pub git_sha: String,
+}
+
+impl ServerVersionInfo {
+    #[allow(dead_code)] // Used by the HTTP version endpoints
+    pub fn current() -> Self {
+        Self {
+            name: SERVER_NAME.to_string(),
+            version: SERVER_VERSION.to_string(),
+            title: SERVER_TITLE.to_string(),
+            rules_version: default_rulesets()
+                .ok()
+                .and_then(|rulesets| rulesets.select(None).map(|ruleset| ruleset.version.clone()))
+                .unwrap_or_else(|| RULES_VERSION.to_string()),
+            git_sha: GIT_SHA.to_string(),
+        }
+    }
+}
+
+/// Environment variable that makes tools answer with compact JSON instead of pretty-printed JSON
+/// when set to "true" or "1", to save tokens on high-volume deployments
+pub const COMPACT_JSON_ENV: &str = "COMPACT_JSON_OUTPUT";
+
+/// Maximum number of evaluations running at once, unlimited when unset
+pub const MAX_CONCURRENT_EVALUATIONS_ENV: &str = "MAX_CONCURRENT_EVALUATIONS";
+/// Milliseconds an evaluation waits for a free slot before being rejected
+pub const EVALUATION_QUEUE_TIMEOUT_ENV: &str = "EVALUATION_QUEUE_TIMEOUT_MS";
+const DEFAULT_EVALUATION_QUEUE_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// Milliseconds an evaluation may take before it is abandoned
+pub const EVALUATION_TIMEOUT_ENV: &str = "EVALUATION_TIMEOUT_MS";
+const DEFAULT_EVALUATION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Times a transiently failing evaluation is attempted again
+pub const EVALUATION_RETRIES_ENV: &str = "EVALUATION_RETRIES";
+/// Milliseconds before the first retry, doubled for every further one
+pub const EVALUATION_RETRY_BACKOFF_ENV: &str = "EVALUATION_RETRY_BACKOFF_MS";
+const DEFAULT_EVALUATION_RETRIES: u32 = 2;
+const DEFAULT_EVALUATION_RETRY_BACKOFF: Duration = Duration::from_millis(50);
+
+/// Retries of evaluations failing with a transient error, with exponential backoff
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self { max_retries: DEFAULT_EVALUATION_RETRIES, initial_backoff: DEFAULT_EVALUATION_RETRY_BACKOFF }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy set by `EVALUATION_RETRIES` and `EVALUATION_RETRY_BACKOFF_MS`, defaults for unset or invalid values
+    fn from_env() -> Self {
+        let default = Self::default();
+        let max_retries = std::env::var(EVALUATION_RETRIES_ENV)
+            .ok()
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(default.max_retries);
+        let initial_backoff = std::env::var(EVALUATION_RETRY_BACKOFF_ENV)
+            .ok()
+            .and_then(|value| value.trim().parse().ok())
+            .map(Duration::from_millis)
+            .unwrap_or(default.initial_backoff);
+        Self { max_retries, initial_backoff }
+    }
+
+    /// Wait before retry number `retry`, counted from 0
+    fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff.saturating_mul(2u32.saturating_pow(retry))
+    }
+
+    /// Result of `attempt`, attempted again while it fails with a transient error and retries are left
+    async fn run<T, F, Fut>(&self, metrics: &EligibilityMetrics, mut attempt: F) -> Result<T, UnpaidLeaveError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, UnpaidLeaveError>>,
+    {
+        let mut retry = 0;
+        loop {
+            match attempt().await {
+                Err(error) if error.is_transient() && retry < self.max_retries => {
+                    let backoff = self.backoff(retry);
+                    tracing::warn!(error = %error, retry = retry + 1, "Transient evaluation error, retrying in {:?}", backoff);
+                    metrics.increment_retries();
+                    tokio::time::sleep(backoff).await;
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Runs `evaluation`, giving up with `UnpaidLeaveError::Timeout` once `deadline` has passed
+async fn with_deadline<F: Future>(deadline: Duration, evaluation: F) -> Result<F::Output, UnpaidLeaveError> {
+    tokio::time::timeout(deadline, evaluation)
+        .await
+        .map_err(|_| UnpaidLeaveError::Timeout(deadline))
+}
+
+/// Slots shared by every engine instance, bounding the blocking evaluation tasks
+#[derive(Debug, Clone)]
+pub struct EvaluationLimit {
+    semaphore: Arc<Semaphore>,
+    max_concurrent: usize,
+    queue_timeout: Duration,
+}
+
+impl EvaluationLimit {
+    pub fn new(max_concurrent: usize, queue_timeout: Duration) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(max_concurrent)),
+            max_concurrent,
+            queue_timeout,
+        }
+    }
+
+    /// Waits up to the queue timeout for a slot, which is released when the permit is dropped
+    async fn acquire(&self) -> Result<OwnedSemaphorePermit, UnpaidLeaveError> {
+        match tokio::time::timeout(self.queue_timeout, self.semaphore.clone().acquire_owned()).await {
+            Ok(Ok(permit)) => Ok(permit),
+            _ => Err(UnpaidLeaveError::ServerBusy(self.max_concurrent)),
+        }
+    }
+}
+
+/// Process-wide limit read from `MAX_CONCURRENT_EVALUATIONS` and `EVALUATION_QUEUE_TIMEOUT_MS`
+static EVALUATION_LIMIT: Lazy<Option<EvaluationLimit>> = Lazy::new(|| {
+    let max_concurrent = std::env::var(MAX_CONCURRENT_EVALUATIONS_ENV)
+        .ok()
+        .and_then(|value| value.trim().parse::<usize>().ok())
+        .filter(|max| *max > 0)?;
+    let queue_timeout = std::env::var(EVALUATION_QUEUE_TIMEOUT_ENV)
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_EVALUATION_QUEUE_TIMEOUT);
+    Some(EvaluationLimit::new(max_concurrent, queue_timeout))
+});
+
+/// Number of engine results kept in memory, 0 disables the cache
+pub const RESULT_CACHE_CAPACITY_ENV: &str = "RESULT_CACHE_CAPACITY";
+const DEFAULT_RESULT_CACHE_CAPACITY: usize = 1024;
+
+/// Engine input of a cached result. Only the fields sent to the rule document are part of it,
+/// everything else is applied on top of the cached result
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ResultCacheKey {
+    ruleset_id: u64,
+    input: CachedInput,
+}
+
+/// Normalized input of a cached result, whichever rules produced it
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CachedInput {
+    tenant_id: Option<String>,
+    relationship: String,
+    situation: String,
+    is_single_parent: bool,
+    // Bits of the count, with -0.0 folded into 0.0
+    total_children_after: Option<u64>,
+    explain: bool,
+}
+
+impl ResultCacheKey {
+    fn new(ruleset: &Ruleset, tenant_id: Option<&str>, input: &UnpaidLeaveInput, explain: bool) -> Self {
+        Self {
+            ruleset_id: ruleset.id,
+            input: CachedInput {
+                tenant_id: tenant_id.map(str::to_string),
+                relationship: input.relationship.clone(),
+                situation: input.situation.clone(),
+                is_single_parent: input.is_single_parent,
+                total_children_after: input.total_children_after.map(|children| (children + 0.0).to_bits()),
+                explain,
+            },
+        }
+    }
+}
+
+/// Least recently used engine results, shared by every engine instance.
+/// The rules are deterministic, so a result can be reused for as long as its ruleset is served
+#[derive(Debug, Clone)]
+pub struct ResultCache {
+    entries: Arc<Mutex<LruCache<ResultCacheKey, UnpaidLeaveResponse>>>,
+    // Latest result of each input under any rules, kept when the rules are replaced
+    // so it can stand in for an evaluation the decision engine fails
+    last_known_good: Arc<Mutex<LruCache<CachedInput, UnpaidLeaveResponse>>>,
+}
+
+impl ResultCache {
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(LruCache::new(capacity))),
+            last_known_good: Arc::new(Mutex::new(LruCache::new(capacity))),
+        }
+    }
+
+    fn get(&self, key: &ResultCacheKey) -> Option<UnpaidLeaveResponse> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).get(key).cloned()
+    }
+
+    fn put(&self, key: ResultCacheKey, response: UnpaidLeaveResponse) {
+        self.last_known_good
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .put(key.input.clone(), response.clone());
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).put(key, response);
+    }
+
+    /// Latest result of the input of `key`, possibly under rules no longer served
+    fn last_known_good(&self, key: &ResultCacheKey) -> Option<UnpaidLeaveResponse> {
+        self.last_known_good.lock().unwrap_or_else(|e| e.into_inner()).get(&key.input).cloned()
+    }
+
+    /// Drops every cached result, called when the rules are replaced.
+    /// The last known good results are kept, they are only served when the engine fails
+    pub fn clear(&self) {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+
+    /// Number of cached results
+    #[allow(dead_code)]
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+}
+
+/// Process-wide cache sized by `RESULT_CACHE_CAPACITY`
+static RESULT_CACHE: Lazy<Option<ResultCache>> = Lazy::new(|| {
+    let capacity = std::env::var(RESULT_CACHE_CAPACITY_ENV)
+        .ok()
+        .and_then(|value| value.trim().parse::<usize>().ok())
+        .unwrap_or(DEFAULT_RESULT_CACHE_CAPACITY);
+    NonZeroUsize::new(capacity).map(ResultCache::new)
+});
+
+#[derive(Debug, Clone)]
+pub struct EligibilityEngine {
+    tool_router: ToolRouter<Self>,
+    strict_parsing: bool,
+    compact_json: bool,
+    metrics: Arc<EligibilityMetrics>,
+    // Parsed decisions shared by every clone, the default registry unless replaced.
+    // Only `None` when the rule documents failed to load, evaluations then report why
+    rulesets: Option<Arc<RulesetRegistry>>,
+    // Rulesets of each tenant, empty unless `TENANT_RULES_DIR` is set
+    tenants: Arc<TenantRegistry>,
+    config: Arc<EligibilityConfig>,
+    evaluation_limit: Option<EvaluationLimit>,
+    evaluation_timeout: Duration,
+    retry_policy: RetryPolicy,
+    result_cache: Option<ResultCache>,
+    audit_log: Option<AuditLog>,
+    webhook: Option<Webhook>,
+    events: Option<EvaluationEvents>,
+}
+
+/// Maximum number of inputs accepted by a single batch evaluation
+pub const MAX_BATCH_SIZE: usize = 100;
+
+/// Batch of inputs evaluated in a single call
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct UnpaidLeaveBatchParams {
+    #[schemars(description = "List of inputs to evaluate, each with the same fields as evaluate_unpaid_leave_eligibility")]
+    pub inputs: Vec<UnpaidLeaveDirectParams>,
+}
+
+/// Result of one input of a batch: either its response or the errors that prevented it
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct UnpaidLeaveBatchItem {
+    #[schemars(description = "Position of the input in the submitted list")]
+    pub index: usize,
+
+    #[schemars(description = "Evaluation result, absent if the input could not be evaluated")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<UnpaidLeaveResponse>,
+
+    #[schemars(description = "Error message, absent if the input was evaluated")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    #[schemars(description = "Field-level validation errors of the input, if any")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub validation_errors: Vec<ValidationError>,
+}
+
+impl UnpaidLeaveBatchItem {
+    fn new(index: usize, result: Result<UnpaidLeaveResponse, UnpaidLeaveError>) -> Self {
+        match result {
+            Ok(response) => Self { index, response: Some(response), error: None, validation_errors: Vec::new() },
+            Err(e) => {
+                let error = Some(format_evaluation_error(&e));
+                let validation_errors = match e {
+                    UnpaidLeaveError::ValidationError(validation_errors) => validation_errors,
+                    _ => Vec::new(),
+                };
+                Self { index, response: None, error, validation_errors }
+            }
+        }
+    }
+}
+
+/// Two scenarios compared by the diff tool
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct UnpaidLeaveDiffParams {
+    #[schemars(description = "Original scenario, with the same fields as evaluate_unpaid_leave_eligibility")]
+    pub before: UnpaidLeaveDirectParams,
+    #[schemars(description = "Modified scenario, with the same fields as evaluate_unpaid_leave_eligibility")]
+    pub after: UnpaidLeaveDirectParams,
+}
+
+/// Value of a result field in both scenarios, only reported when it differs
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct FieldChange<T> {
+    pub before: T,
+    pub after: T,
+}
+
+impl<T: PartialEq> FieldChange<T> {
+    fn between(before: T, after: T) -> Option<Self> {
+        (before != after).then_some(Self { before, after })
+    }
+}
+
+/// Result fields that changed between two scenarios
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct UnpaidLeaveDiff {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub case: Option<FieldChange<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monthly_benefit: Option<FieldChange<i32>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub potentially_eligible: Option<FieldChange<bool>>,
+    #[schemars(description = "Warnings only present in the modified scenario")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings_added: Vec<String>,
+    #[schemars(description = "Warnings only present in the original scenario")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings_removed: Vec<String>,
+}
+
+impl UnpaidLeaveDiff {
+    pub fn new(before: &UnpaidLeaveResponse, after: &UnpaidLeaveResponse) -> Self {
+        let (before, after) = (&before.output, &after.output);
+        Self {
+            case: FieldChange::between(before.case.clone(), after.case.clone()),
+            monthly_benefit: FieldChange::between(before.monthly_benefit, after.monthly_benefit),
+            potentially_eligible: FieldChange::between(before.potentially_eligible, after.potentially_eligible),
+            warnings_added: after.warnings.iter().filter(|w| !before.warnings.contains(w)).cloned().collect(),
+            warnings_removed: before.warnings.iter().filter(|w| !after.warnings.contains(w)).cloned().collect(),
+        }
+    }
+}
+
+/// Responses of both scenarios and what changed between them
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct UnpaidLeaveDiffResponse {
+    pub before: UnpaidLeaveResponse,
+    pub after: UnpaidLeaveResponse,
+    pub changes: UnpaidLeaveDiff,
+}
+
+/// Children counts tried when `total_children_after` is unknown in a birth, adoption or foster
+/// care situation. Every count from the case B threshold on evaluates alike
+const ESTIMATE_CHILDREN_COUNTS: &[f64] = &[1.0, 2.0, CASE_B_MIN_CHILDREN];
+
+/// Maximum number of complete inputs a single estimate evaluates, the same as a batch. Knowing
+/// the relationship or the situation keeps an estimate under it
+pub const MAX_ESTIMATE_COMBINATIONS: usize = MAX_BATCH_SIZE;
+
+/// Tool label of the metrics of estimates
+const ESTIMATE_TOOL_LABEL: &str = "estimate_unpaid_leave";
+
+/// Partial input of an estimate, every field is optional
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct UnpaidLeaveEstimateParams {
+    #[schemars(description = "Optional relationship with the family member, same values as evaluate_unpaid_leave_eligibility. Every valid relationship is considered when omitted")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relationship: Option<String>,
+
+    #[schemars(description = "Optional situation, same values as evaluate_unpaid_leave_eligibility. Every valid situation is considered when omitted")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub situation: Option<String>,
+
+    #[schemars(description = "Optional. true for single-parent families, false otherwise. Both are considered when omitted")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_single_parent: Option<bool>,
+
+    #[schemars(description = "Optional total number of children after the birth or adoption. When omitted, 1, 2 and 3 children are considered for birth, adoption and foster care situations")]
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "deserialize_f64_or_string")]
+    pub total_children_after: Option<f64>,
+
+    #[schemars(description = "Optional autonomous community, adjusts the amounts as in evaluate_unpaid_leave_eligibility")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+
+    #[schemars(description = "Optional percentage of the working day taken as leave, from 1 to 100, scales the amounts as in evaluate_unpaid_leave_eligibility")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leave_percentage: Option<u32>,
+}
+
+impl UnpaidLeaveEstimateParams {
+    /// Complete inputs consistent with the known fields, the unknown ones enumerated over their valid values
+    fn candidates(&self) -> Vec<UnpaidLeaveDirectParams> {
+        let known = |value: &Option<String>, domain: &[&str]| match value {
+            Some(value) => vec![value.clone()],
+            None => domain.iter().map(|value| value.to_string()).collect(),
+        };
+        let relationships = known(&self.relationship, VALID_RELATIONSHIPS);
+        let situations = known(&self.situation, VALID_SITUATIONS);
+        let single_parent = self.is_single_parent.map_or(vec![false, true], |single_parent| vec![single_parent]);
+
+        let mut candidates = Vec::new();
+        for relationship in &relationships {
+            for situation in &situations {
+                let children: Vec<Option<f64>> = match self.total_children_after {
+                    Some(children) => vec![Some(children)],
+                    None if FAMILY_CARE_SITUATIONS.contains(&situation.trim().to_lowercase().as_str()) => vec![None],
+                    None => ESTIMATE_CHILDREN_COUNTS.iter().map(|children| Some(*children)).collect(),
+                };
+                for is_single_parent in &single_parent {
+                    for total_children_after in &children {
+                        candidates.push(UnpaidLeaveDirectParams {
+                            input: UnpaidLeaveInput {
+                                relationship: relationship.clone(),
+                                situation: situation.clone(),
+                                is_single_parent: *is_single_parent,
+                                total_children_after: *total_children_after,
+                            },
+                            region: self.region.clone(),
+                            leave_percentage: self.leave_percentage,
+                            ..Default::default()
+                        });
+                    }
+                }
+            }
+        }
+        candidates
+    }
+
+    /// Names of the fields left unknown
+    fn unknown_fields(&self) -> Vec<String> {
+        [
+            ("relationship", self.relationship.is_none()),
+            ("situation", self.situation.is_none()),
+            ("is_single_parent", self.is_single_parent.is_none()),
+            ("total_children_after", self.total_children_after.is_none()),
+        ]
+        .into_iter()
+        .filter(|(_, unknown)| *unknown)
+        .map(|(field, _)| field.to_string())
+        .collect()
+    }
+}
+
+/// Outcomes possible for a partial input
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct UnpaidLeaveEstimate {
+    #[schemars(description = "Lowest monthly benefit among the eligible outcomes, 0 when no outcome is eligible")]
+    pub min_monthly_benefit: i32,
+    #[schemars(description = "Highest monthly benefit among the eligible outcomes, 0 when no outcome is eligible")]
+    pub max_monthly_benefit: i32,
+    #[schemars(description = "Cases the input may fall under once complete, in alphabetical order")]
+    pub candidate_cases: Vec<String>,
+    #[schemars(description = "true when some completion of the input is not eligible")]
+    pub may_be_ineligible: bool,
+    #[schemars(description = "Fields that were not provided and were enumerated over their valid values")]
+    pub unknown_fields: Vec<String>,
+    #[schemars(description = "Number of complete inputs evaluated")]
+    pub combinations_evaluated: usize,
+    #[schemars(description = "Number of complete inputs whose evaluation failed, left out of the other fields")]
+    pub combinations_failed: usize,
+}
+
+/// Version reported by evaluations against a rule document given in the call
+pub const AD_HOC_RULES_VERSION: &str = "ad-hoc";
+
+/// Input evaluated against a rule document given in the call instead of the deployed ones
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct EvaluateWithRulesParams {
+    #[schemars(description = "Input to evaluate, with the same fields as evaluate_unpaid_leave_eligibility. tenant_id is ignored")]
+    pub input: UnpaidLeaveDirectParams,
+    #[schemars(description = "Full rule document in the GoRules JDM format, with 'nodes' and 'edges'")]
+    pub rules: serde_json::Value,
+}
+
+/// Result of an evaluation against an ad-hoc rule document, and what is wrong with the document
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct EvaluateWithRulesResponse {
+    #[schemars(description = "Evaluation result, absent if the input could not be evaluated")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<UnpaidLeaveResponse>,
+
+    #[schemars(description = "Error message, absent if the input was evaluated")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    #[schemars(description = "Field-level validation errors of the input, if any")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub validation_errors: Vec<ValidationError>,
+
+    #[schemars(description = "Problems of the rule document that would prevent the server from starting with it, empty when it could be deployed")]
+    pub rule_errors: Vec<String>,
+}
+
+impl EvaluateWithRulesResponse {
+    fn new(result: Result<UnpaidLeaveResponse, UnpaidLeaveError>, rule_errors: Vec<String>) -> Self {
+        let UnpaidLeaveBatchItem { response, error, validation_errors, .. } = UnpaidLeaveBatchItem::new(0, result);
+        Self { response, error, validation_errors, rule_errors }
+    }
+}
+
+/// Real-world outcomes accepted by the feedback tool
+pub const VALID_OUTCOMES: &[&str] = &["approved", "denied", "withdrawn"];
+
+/// Outcome of an application reported after a prior evaluation
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct EligibilityFeedbackParams {
+    #[schemars(description = "evaluation_id returned by the evaluation this feedback is about")]
+    pub evaluation_id: String,
+    #[schemars(description = "What actually happened to the application: 'approved', 'denied' or 'withdrawn'")]
+    pub outcome: String,
+    #[schemars(description = "Optional free-text details, e.g. the amount actually granted or the reason for a denial")]
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
+impl EligibilityFeedbackParams {
+    /// Evaluation id in canonical UUID form and lowercase outcome, or the errors that prevent storing them
+    pub fn normalized(&self) -> Result<(String, String), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        let evaluation_id = match uuid::Uuid::parse_str(self.evaluation_id.trim()) {
+            Ok(id) => id.to_string(),
+            Err(_) => {
+                errors.push(ValidationError {
+                    message: format!("evaluation_id '{}' is not an id returned by an evaluation", self.evaluation_id),
+                    path: "/evaluation_id".to_string(),
+                });
+                String::new()
+            }
+        };
+        let outcome = self.outcome.trim().to_lowercase();
+        if !VALID_OUTCOMES.contains(&outcome.as_str()) {
+            errors.push(ValidationError {
+                message: format!("outcome '{}' is not one of {}", self.outcome, quoted_values(VALID_OUTCOMES)),
+                path: "/outcome".to_string(),
+            });
+        }
+        if errors.is_empty() { Ok((evaluation_id, outcome)) } else { Err(errors) }
+    }
+}
+
+/// Whether a feedback submission was stored
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct EligibilityFeedbackResponse {
+    pub evaluation_id: String,
+    #[schemars(description = "True when the feedback was written to the audit log, false when the server has none")]
+    pub recorded: bool,
+    pub message: String,
+}
+
+/// Header carrying the caller's request id, used as the correlation id of the evaluation span
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longer request ids are replaced by a generated one rather than logged
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Request id sent by the caller, if it is printable ASCII of a reasonable length
+pub fn request_id_from_headers(headers: &axum::http::HeaderMap) -> Option<String> {
+    headers
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN)
+        .map(str::to_string)
+}
+
+/// Applies the `Accept-Language` default of the HTTP request behind a tool call to `locale`.
+/// The streamable-http transport hands over the HTTP request, other transports do not
+fn default_locale(locale: &mut Option<String>, extensions: &Extensions) {
+    if let Some(parts) = extensions.get::<axum::http::request::Parts>() {
+        i18n::default_locale(locale, &parts.headers);
+    }
+}
+
+/// Known-good input used by the health check, it must always resolve to `HEALTH_CHECK_CASE`
+const HEALTH_CHECK_INPUT: (&str, &str) = ("mother", "illness");
+const HEALTH_CHECK_CASE: &str = "A";
+
+impl EligibilityEngine {
+    /// Runs a canary evaluation through the cached decision without recording metrics.
+    /// The result cache is bypassed so the decision itself is exercised.
+    /// Returns an error describing the failure if the engine does not produce the expected case.
+    pub async fn health_check(&self) -> Result<(), String> {
+        let (relationship, situation) = HEALTH_CHECK_INPUT;
+        let canary = UnpaidLeaveDirectParams {
+            input: UnpaidLeaveInput {
+                relationship: relationship.to_string(),
+                situation: situation.to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let engine = self.clone().with_result_cache(None).with_audit_log(None).with_webhook(None).with_events(None);
+        match engine.evaluate_untracked(canary, false).await {
+            Ok(response) if response.output.case == HEALTH_CHECK_CASE => Ok(()),
+            Ok(response) => Err(format!(
+                "Canary evaluation returned case '{}', expected '{}'",
+                response.output.case, HEALTH_CHECK_CASE
+            )),
+            Err(e) => Err(format!("Canary evaluation failed: {}", e)),
+        }
+    }
+
+    /// Evaluates a single input through the shared decision. Input warnings are
+    /// appended to the response; metrics are recorded for every call.
+    #[allow(dead_code)] // The tools count their requests under their own name, see `evaluate_for_tool`
+    pub async fn evaluate(&self, direct_params: UnpaidLeaveDirectParams) -> Result<UnpaidLeaveResponse, UnpaidLeaveError> {
+        self.evaluate_tracked(direct_params, false, None, DEFAULT_TOOL_LABEL).await
+    }
+
+    /// Same as `evaluate`, counting the request in the metrics of `tool`
+    pub async fn evaluate_for_tool(
+        &self,
+        direct_params: UnpaidLeaveDirectParams,
+        tool: &'static str,
+    ) -> Result<UnpaidLeaveResponse, UnpaidLeaveError> {
+        self.evaluate_tracked(direct_params, false, None, tool).await
+    }
+
+    /// Same as `evaluate`, logging under the caller's `request_id` instead of a generated one
+    pub async fn evaluate_with_request_id(
+        &self,
+        direct_params: UnpaidLeaveDirectParams,
+        request_id: Option<String>,
+    ) -> Result<UnpaidLeaveResponse, UnpaidLeaveError> {
+        self.evaluate_tracked(direct_params, false, request_id, DEFAULT_TOOL_LABEL).await
+    }
+
+    /// Same as `evaluate`, additionally returning the decision path in `trace`
+    pub async fn evaluate_explained(&self, direct_params: UnpaidLeaveDirectParams) -> Result<UnpaidLeaveResponse, UnpaidLeaveError> {
+        self.evaluate_tracked(direct_params, true, None, "evaluate_unpaid_leave_explain").await
+    }
+
+    /// Stores the real-world outcome of a prior evaluation in the audit log.
+    /// Valid feedback is accepted but not stored when no audit log is configured
+    pub async fn record_feedback(&self, params: EligibilityFeedbackParams) -> Result<EligibilityFeedbackResponse, UnpaidLeaveError> {
+        let (evaluation_id, outcome) = params.normalized().map_err(UnpaidLeaveError::ValidationError)?;
+        self.metrics.increment_feedback(&outcome);
+
+        let Some(audit_log) = &self.audit_log else {
+            return Ok(EligibilityFeedbackResponse {
+                evaluation_id,
+                recorded: false,
+                message: "Feedback accepted but not stored: this server has no audit log configured".to_string(),
+            });
+        };
+        audit_log
+            .record_feedback(&evaluation_id, &outcome, params.notes.as_deref())
+            .await
+            .map_err(|e| UnpaidLeaveError::InternalError(format!("cannot store feedback: {}", e)))?;
+        Ok(EligibilityFeedbackResponse { evaluation_id, recorded: true, message: "Feedback recorded".to_string() })
+    }
+
+    /// Description, benefit and requirements of a case, as an evaluation falling under it reports them.
+    /// Unsupported locales fall back to the default one
+    pub fn case_description(&self, params: DescribeCaseParams) -> Result<CaseDescription, UnpaidLeaveError> {
+        let letter = params.case.trim().to_uppercase();
+        let locale = params.locale.as_deref().and_then(Locale::from_tag).unwrap_or_default();
+        let Some(eligibility_case) = ELIGIBILITY_CASES.iter().find(|eligibility_case| eligibility_case.case == letter) else {
+            let cases: Vec<&str> = ELIGIBILITY_CASES.iter().map(|eligibility_case| eligibility_case.case).collect();
+            return Err(UnpaidLeaveError::ValidationError(vec![ValidationError {
+                message: i18n::render(locale, "validation.unknown_case", &[("case", &params.case), ("cases", &quoted_values(&cases))]),
+                path: "/case".to_string(),
+            }]));
+        };
+        Ok(CaseDescription {
+            case: eligibility_case.case.to_string(),
+            description: i18n::rule_text(locale, eligibility_case.description),
+            monthly_benefit: self.case_benefit(eligibility_case),
+            additional_requirements: eligibility_case
+                .additional_requirements
+                .iter()
+                .map(|requirement| i18n::rule_text(locale, requirement))
+                .collect(),
+            locale: locale.code().to_string(),
+        })
+    }
+
+    /// Monthly benefit of `eligibility_case`, the configured amount when the server overrides it
+    fn case_benefit(&self, eligibility_case: &EligibilityCase) -> i32 {
+        self.config.benefit_amount(eligibility_case.case).unwrap_or(eligibility_case.monthly_benefit)
+    }
+
+    /// Cases evaluated as listed in the server instructions, one line per case with its configured amount
+    fn cases_summary(&self) -> String {
+        ELIGIBILITY_CASES
+            .iter()
+            .map(|eligibility_case| {
+                let title = match eligibility_case.case {
+                    "A" => "Sick/injured family care",
+                    "B" => "Third child+ with newborn",
+                    "C" => "Adoption/foster care",
+                    "D" => "Multiple births/adoptions",
+                    "E" => "Single-parent families",
+                    _ => eligibility_case.description,
+                };
+                let amount = self.config.money(self.case_benefit(eligibility_case) as f64);
+                format!("\n{}) {} ({}/month)", eligibility_case.case, title, amount.formatted)
+            })
+            .collect()
+    }
+
+    /// Range of monthly benefits and cases possible for a partial input, found by evaluating every
+    /// completion of it. Results come from the result cache when possible and are not audited.
+    /// Each estimate counts as a single request in the metrics
+    pub async fn estimate(&self, params: UnpaidLeaveEstimateParams) -> Result<UnpaidLeaveEstimate, UnpaidLeaveError> {
+        let labels = MetricLabels::new(ESTIMATE_TOOL_LABEL);
+        self.metrics.increment_requests(&labels);
+        let _timer = RequestTimer::new(&self.metrics, &labels);
+
+        let result = self.estimate_untracked(params).await;
+        if let Err(e) = &result {
+            self.metrics.increment_errors(e.error_type(), &labels);
+        }
+        self.metrics.record_evaluation();
+        result
+    }
+
+    /// Same as `estimate`, without the metrics. Every completion waits for its own slot of the
+    /// evaluation limit, and completions failing to evaluate are skipped unless all of them do
+    async fn estimate_untracked(&self, params: UnpaidLeaveEstimateParams) -> Result<UnpaidLeaveEstimate, UnpaidLeaveError> {
+        if let Some(children) = params.total_children_after
+            && (children < 0.0 || children.fract() != 0.0)
+        {
+            return Err(UnpaidLeaveError::ValidationError(vec![ValidationError {
+                message: format!("total_children_after must be a whole number greater than or equal to 0, got {}", children),
+                path: "/total_children_after".to_string(),
+            }]));
+        }
+        let candidates = params.candidates();
+        if candidates.len() > MAX_ESTIMATE_COMBINATIONS {
+            return Err(UnpaidLeaveError::ValidationError(vec![ValidationError {
+                message: format!(
+                    "Input too open: {} combinations to evaluate, the maximum is {}. Give at least the relationship or the situation",
+                    candidates.len(),
+                    MAX_ESTIMATE_COMBINATIONS
+                ),
+                path: String::new(),
+            }]));
+        }
+
+        let engine = self.clone().with_audit_log(None).with_webhook(None).with_events(None);
+        let mut benefits = Vec::new();
+        let mut candidate_cases = std::collections::BTreeSet::new();
+        let mut may_be_ineligible = false;
+        let mut failures = Vec::new();
+        for candidate in &candidates {
+            // A busy server fails every remaining completion alike, so it ends the estimate
+            let permit = engine.acquire_slot().await?;
+            match engine.evaluate_holding(candidate.clone(), false, permit).await {
+                Ok(response) if response.output.potentially_eligible => {
+                    benefits.push(response.output.monthly_benefit);
+                    candidate_cases.insert(response.output.case);
+                }
+                Ok(_) => may_be_ineligible = true,
+                Err(e) => {
+                    tracing::warn!(
+                        error = %e,
+                        relationship = %candidate.input.relationship,
+                        situation = %candidate.input.situation,
+                        "Estimate completion failed, skipped"
+                    );
+                    failures.push(e);
+                }
+            }
+        }
+        if failures.len() == candidates.len()
+            && let Some(error) = failures.pop()
+        {
+            return Err(error);
+        }
+        Ok(UnpaidLeaveEstimate {
+            min_monthly_benefit: benefits.iter().copied().min().unwrap_or(0),
+            max_monthly_benefit: benefits.iter().copied().max().unwrap_or(0),
+            candidate_cases: candidate_cases.into_iter().collect(),
+            may_be_ineligible,
+            unknown_fields: params.unknown_fields(),
+            combinations_evaluated: candidates.len(),
+            combinations_failed: failures.len(),
+        })
+    }
+
+    /// Evaluates `params.input` against `params.rules` instead of the deployed rules, for rule authors
+    /// trying changes. The rules are not kept, and the evaluation is neither cached nor audited. Like
+    /// any evaluation it waits for a slot of the evaluation limit, runs under the evaluation timeout and
+    /// is counted in the metrics, under the `evaluate_with_rules` tool. The problems
+    /// `validate_decision_content_within` finds in the document, with the evaluation timeout as the
+    /// deadline of its canary, are rule errors
+    pub async fn evaluate_against_rules(&self, params: EvaluateWithRulesParams) -> EvaluateWithRulesResponse {
+        let content: DecisionContent = match serde_json::from_value(params.rules) {
+            Ok(content) => content,
+            Err(e) => {
+                let error = UnpaidLeaveError::InternalError("The rule document could not be parsed".to_string());
+                return EvaluateWithRulesResponse::new(Err(error), vec![format!("Invalid rule document: {}", e)]);
+            }
+        };
+        let rule_errors = validate_decision_content_within(&content, self.evaluation_timeout).await.err().unwrap_or_default();
+        let ruleset = Ruleset::from_content(AD_HOC_RULES_VERSION, NaiveDate::MIN, content);
+        // No result cache first, so replacing the rules does not clear the shared one
+        let engine = self
+            .clone()
+            .with_result_cache(None)
+            .with_audit_log(None)
+            .with_webhook(None)
+            .with_events(None)
+            .with_tenants(Arc::default())
+            .with_rulesets(Arc::new(RulesetRegistry::new().with_ruleset(ruleset)));
+
+        let mut direct_params = params.input;
+        direct_params.tenant_id = None;
+        EvaluateWithRulesResponse::new(engine.evaluate_metered(direct_params, false, "evaluate_with_rules").await, rule_errors)
+    }
+
+    /// Runs the evaluation in an "evaluation" span carrying the request id, the situation,
+    /// and once finished, the evaluation id, the resulting case and the duration
+    async fn evaluate_tracked(
+        &self,
+        direct_params: UnpaidLeaveDirectParams,
+        explain: bool,
+        request_id: Option<String>,
+        tool: &'static str,
+    ) -> Result<UnpaidLeaveResponse, UnpaidLeaveError> {
+        let request_id = request_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let span = tracing::info_span!(
+            "evaluation",
+            request_id = %request_id,
+            situation = %direct_params.input.situation,
+            evaluation_id = tracing::field::Empty,
+            case = tracing::field::Empty,
+            duration_ms = tracing::field::Empty,
+        );
+
+        let started = Instant::now();
+        let result = self.evaluate_metered(direct_params, explain, tool).instrument(span.clone()).await;
+        span.record("duration_ms", started.elapsed().as_micros() as f64 / 1000.0);
+        match &result {
+            Ok(response) => {
+                if let Some(evaluation_id) = &response.evaluation_id {
+                    span.record("evaluation_id", evaluation_id.as_str());
+                }
+                span.record("case", response.output.case.as_str());
+                tracing::info!(parent: &span, "Evaluation completed");
+            }
+            Err(e) => tracing::info!(parent: &span, error = %e, "Evaluation failed"),
+        }
+        result
+    }
+
+    /// Slot of the evaluation limit for one evaluation, `None` when there is no limit
+    async fn acquire_slot(&self) -> Result<Option<OwnedSemaphorePermit>, UnpaidLeaveError> {
+        let Some(limit) = &self.evaluation_limit else {
+            return Ok(None);
+        };
+        limit.acquire().await.map(Some).inspect_err(|_| self.metrics.increment_rejected())
+    }
+
+    async fn evaluate_metered(
+        &self,
+        direct_params: UnpaidLeaveDirectParams,
+        explain: bool,
+        tool: &'static str,
+    ) -> Result<UnpaidLeaveResponse, UnpaidLeaveError> {
+        let tenant = self.tenant_label(&direct_params);
+        let labels = MetricLabels::new(tool).with_tenant(tenant.as_deref());
+        self.metrics.increment_requests(&labels);
+
+        // Wait for a free slot before counting the evaluation as active
+        let permit = match self.acquire_slot().await {
+            Ok(permit) => permit,
+            Err(e) => {
+                self.metrics.increment_errors(e.error_type(), &labels);
+                self.metrics.record_evaluation();
+                return Err(e);
+            }
+        };
+
+        // Initialize metrics tracking
+        let _timer = RequestTimer::new(&self.metrics, &labels);
+
+        let result = self.evaluate_holding(direct_params, explain, permit).await;
+        match &result {
+            Ok(response) => self.metrics.increment_case(&response.output.case, response.tenant_id.as_deref()),
+            Err(e) => self.metrics.increment_errors(e.error_type(), &labels),
+        }
+        self.metrics.record_evaluation();
+        result
+    }
+
+    /// Last known good result of the input of `cache_key` when the decision engine failed with
+    /// `error`, e.g. on rules reloaded with a defect, so an evaluation already made is not lost to
+    /// the failure. It may come from the rules served before, as its `rules_version` tells, and its
+    /// warning is in `locale`. `error` when it is not an engine failure or the same input was never evaluated
+    fn cached_on_error(
+        &self,
+        cache_key: &ResultCacheKey,
+        error: UnpaidLeaveError,
+        locale: Locale,
+    ) -> Result<UnpaidLeaveResponse, UnpaidLeaveError> {
+        let engine_failure = matches!(
+            error,
+            UnpaidLeaveError::ZenEngineError(_)
+                | UnpaidLeaveError::InternalError(_)
+                | UnpaidLeaveError::Panicked
+                | UnpaidLeaveError::Timeout(_)
+                | UnpaidLeaveError::RulesUnavailable(_)
+        );
+        let Some(mut response) = self
+            .result_cache
+            .as_ref()
+            .filter(|_| engine_failure)
+            .and_then(|cache| cache.last_known_good(cache_key))
+        else {
+            return Err(error);
+        };
+        tracing::warn!(error = %error, "Decision engine failed, serving the cached result");
+        self.metrics.increment_served_from_cache_on_error();
+        response.output.warnings.push(i18n::render(locale, "warning.served_from_cache", &[("error", &error)]));
+        Ok(response)
+    }
+
+    /// Error for an evaluation task that did not complete. A panic is logged in the current
+    /// span and counted, and clients only get a stable message without the panic payload
+    fn join_error(&self, error: tokio::task::JoinError) -> UnpaidLeaveError {
+        if !error.is_panic() {
+            return UnpaidLeaveError::InternalError(error.to_string());
+        }
+        let payload = error.into_panic();
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("non-string panic payload");
+        tracing::error!(panic = %message, "Evaluation panicked");
+        self.metrics.increment_panics();
+        UnpaidLeaveError::Panicked
+    }
+
+    /// Runs `engine` on `request` in a blocking task under the evaluation deadline, attempting it
+    /// again following `retry_policy` while it fails with a transient error. On timeout the attempt
+    /// is given up right away and its blocking task is left to finish, holding the `permit` so a
+    /// hung rule still counts against the evaluation limit
+    async fn run_engine(
+        &self,
+        engine: UnpaidLeaveDecisionEngine,
+        request: UnpaidLeaveRequest,
+        explain: bool,
+        permit: Option<OwnedSemaphorePermit>,
+    ) -> Result<UnpaidLeaveResponse, UnpaidLeaveError> {
+        // Shared by every attempt, the slot is released once the last of them is done
+        let permit = Arc::new(permit);
+        let request = Arc::new(request);
+        self.retry_policy
+            .run(&self.metrics, || {
+                let engine = engine.clone();
+                let request = request.clone();
+                let permit = permit.clone();
+                // Use tokio::task::spawn_blocking for operations that are not Send
+                let attempt = tokio::task::spawn_blocking(move || {
+                    let _permit = permit;
+                    // Create a tokio runtime for the async operation inside the blocking block
+                    let rt = tokio::runtime::Builder::new_current_thread()
+                        .enable_all()
+                        .build()
+                        .map_err(|e| UnpaidLeaveError::InternalError(format!("cannot create evaluation runtime: {}", e)))?;
+                    rt.block_on(engine.evaluate_unpaid_leave(&request, explain))
+                });
+                async move {
+                    match with_deadline(self.evaluation_timeout, attempt).await {
+                        Ok(Ok(result)) => result,
+                        Ok(Err(join_error)) => Err(self.join_error(join_error)),
+                        Err(timeout) => Err(timeout),
+                    }
+                }
+            })
+            .await
+    }
+
+    async fn evaluate_untracked(&self, direct_params: UnpaidLeaveDirectParams, explain: bool) -> Result<UnpaidLeaveResponse, UnpaidLeaveError> {
+        self.evaluate_holding(direct_params, explain, None).await
+    }
+
+    /// Same as `evaluate_untracked`, keeping the `permit` of the evaluation limit until the decision
+    /// engine is done, even when the request gave up waiting for it
+    async fn evaluate_holding(
+        &self,
+        direct_params: UnpaidLeaveDirectParams,
+        explain: bool,
+        permit: Option<OwnedSemaphorePermit>,
+    ) -> Result<UnpaidLeaveResponse, UnpaidLeaveError> {
+        // In strict mode, hallucinated extra fields are reported instead of dropped
+        if self.strict_parsing {
+            let unknown_field_errors = direct_params.unknown_field_errors();
+            if !unknown_field_errors.is_empty() {
+                return Err(UnpaidLeaveError::ValidationError(unknown_field_errors));
+            }
+        }
+
+        // The rule document does not know about the leave percentage, the household or the leave period, so they are checked here
+        let errors: Vec<ValidationError> = direct_params
+            .leave_percentage_error()
+            .into_iter()
+            .chain(direct_params.household_income_error())
+            .chain(direct_params.household_size_error())
+            .chain(direct_params.leave_period_error())
+            .collect();
+        if !errors.is_empty() {
+            return Err(UnpaidLeaveError::ValidationError(errors));
+        }
+
+        // Pick the ruleset of the tenant in effect on the requested date
+        let effective_date = direct_params
+            .parsed_effective_date()
+            .map_err(|error| UnpaidLeaveError::ValidationError(vec![error]))?;
+        let locale = direct_params.locale();
+        let tenant_id = direct_params.normalized_tenant_id();
+        let rulesets = self.tenant_rulesets(tenant_id.as_deref(), locale)?;
+        let ruleset = rulesets.select(effective_date).ok_or_else(|| {
+            let message = match effective_date {
+                Some(date) => i18n::render(locale, "validation.no_regulation", &[("date", &date)]),
+                None => i18n::render(locale, "validation.no_regulation_any_date", &[]),
+            };
+            UnpaidLeaveError::ValidationError(vec![ValidationError { message, path: "/input/effective_date".to_string() }])
+        })?;
+        let engine = UnpaidLeaveDecisionEngine::new(ruleset);
+
+        let leave_duration_days = direct_params.leave_duration_days();
+        let leave_percentage = direct_params.leave_percentage;
+        let region = direct_params.region.clone();
+        let household_income = direct_params.household_income;
+        let household_size = direct_params.household_size;
+        let has_disability = direct_params.has_disability;
+        let confidence = direct_params.confidence();
+        let requested_locale = direct_params.locale.clone();
+        let include_input = direct_params.include_input;
+
+        // Convert direct parameters to nested structure expected by the engine,
+        // input warnings are reported alongside the engine result
+        let (request, input_warnings) = direct_params.into_request();
+        let input = request.input.clone();
+        let relationship_valid = VALID_RELATIONSHIPS.contains(&input.relationship.as_str());
+
+        let cache_key = ResultCacheKey::new(ruleset, tenant_id.as_deref(), &request.input, explain);
+        let cached = self.result_cache.as_ref().and_then(|cache| cache.get(&cache_key));
+        let mut response = match cached {
+            Some(response) => {
+                self.metrics.increment_cache_hits();
+                response
+            }
+            None => {
+                if self.result_cache.is_some() {
+                    self.metrics.increment_cache_misses();
+                }
+
+                match self.run_engine(engine, request, explain, permit).await {
+                    Ok(response) => {
+                        // Checked on the raw rule output, the means tests below may clear eligibility but keep the case
+                        if let Some(mismatch) = response.output.case_eligibility_mismatch() {
+                            self.metrics.increment_inconsistent_outputs(&response.rules_version);
+                            tracing::warn!("Inconsistent output from rules {}: {}", response.rules_version, mismatch);
+                        }
+                        if let Some(cache) = &self.result_cache {
+                            cache.put(cache_key, response.clone());
+                        }
+                        response
+                    }
+                    Err(error) => self.cached_on_error(&cache_key, error, locale)?,
+                }
+            }
+        };
+
+        // Cached results hold the raw rule output, so they are checked again in the language of this request
+        if let Some(mismatch) = response.output.case_eligibility_mismatch() {
+            response.output.warnings.push(i18n::render(locale, "warning.inconsistent_output", &[("mismatch", &mismatch)]));
+        }
+        response.output.warnings.extend(input_warnings);
+
+        // Means test, only applied when the income is known and the case has a configured limit
+        if let Some(income) = household_income
+            && response.output.potentially_eligible
+            && let Some(threshold) = self.config.income_threshold(&response.output.case)
+            && income > threshold
+        {
+            response.output.potentially_eligible = false;
+            response.output.monthly_benefit = 0;
+            response.output.errors.push(i18n::render(
+                locale,
+                "error.income_threshold",
+                &[
+                    ("income", &self.config.money(income).formatted),
+                    ("threshold", &self.config.money(threshold).formatted),
+                    ("case", &response.output.case),
+                ],
+            ));
+        }
+
+        // Per-member means test, only applied when the household is known and the case has a configured limit
+        if let (Some(income), Some(size)) = (household_income, household_size)
+            && response.output.potentially_eligible
+            && let Some(threshold) = self.config.per_member_income_threshold(&response.output.case)
+        {
+            let per_member = income / size as f64;
+            if per_member > threshold {
+                response.output.potentially_eligible = false;
+                response.output.monthly_benefit = 0;
+                response.output.errors.push(i18n::render(
+                    locale,
+                    "error.per_member_income_threshold",
+                    &[
+                        ("per_member", &self.config.money(per_member).formatted),
+                        ("income", &self.config.money(income).formatted),
+                        ("size", &size),
+                        ("threshold", &self.config.money(threshold).formatted),
+                        ("case", &response.output.case),
+                    ],
+                ));
+            }
+        }
+        if household_size.is_some() && household_income.is_none() {
+            response.output.warnings.push(i18n::render(locale, "warning.household_size_without_income", &[]));
+        }
+
+        // Configured amounts replace the ones in the rule document for eligible cases
+        if response.output.potentially_eligible
+            && let Some(amount) = self.config.benefit_amount(&response.output.case)
+        {
+            response.output.monthly_benefit = amount;
+        }
+        // Regional adjustments apply to the national amount, before part-time scaling
+        if let Some(region) = region {
+            match self.config.region(&region) {
+                Some((name, adjustments)) => {
+                    if response.output.potentially_eligible {
+                        response.output.monthly_benefit = adjustments.amount(&response.output.case, response.output.monthly_benefit);
+                    }
+                    response.region = Some(name.to_string());
+                }
+                None => {
+                    response.output.warnings.push(i18n::render(locale, "warning.unknown_region", &[("region", &region.trim())]));
+                    response.region = Some(NATIONAL_REGION.to_string());
+                }
+            }
+        }
+        // The disability uplift raises the full-time amount of care cases, before part-time scaling
+        if has_disability {
+            let care = FAMILY_CARE_SITUATIONS.contains(&input.situation.as_str());
+            match &self.config.disability_uplift {
+                Some(uplift) if care && response.output.potentially_eligible => {
+                    let before = response.output.monthly_benefit;
+                    response.output.monthly_benefit = uplift.apply(before);
+                    response.output.warnings.push(i18n::render(
+                        locale,
+                        "warning.disability_uplift_applied",
+                        &[
+                            ("before", &self.config.money(before as f64).formatted),
+                            ("after", &self.config.money(response.output.monthly_benefit as f64).formatted),
+                        ],
+                    ));
+                }
+                Some(_) if !care => response.output.warnings.push(i18n::render(
+                    locale,
+                    "warning.disability_uplift_situation",
+                    &[("situations", &quoted_values(FAMILY_CARE_SITUATIONS))],
+                )),
+                None => response.output.warnings.push(i18n::render(locale, "warning.disability_uplift_not_configured", &[])),
+                Some(_) => {}
+            }
+        }
+        if let Some(percentage) = leave_percentage {
+            response.output.monthly_benefit = scale_by_percentage(response.output.monthly_benefit, percentage);
+        }
+        // Last, so the cap bounds whatever the amounts above add up to
+        let capped = self.config.capped_benefit(response.output.monthly_benefit);
+        if capped < response.output.monthly_benefit {
+            response.output.warnings.push(i18n::render(
+                locale,
+                "warning.benefit_capped",
+                &[
+                    ("amount", &self.config.money(response.output.monthly_benefit as f64).formatted),
+                    ("cap", &self.config.money(capped as f64).formatted),
+                ],
+            ));
+            response.output.monthly_benefit = capped;
+        }
+        response.monthly_benefit_money = Some(self.config.money(response.output.monthly_benefit as f64));
+        if let Some(days) = leave_duration_days {
+            response.leave_duration_days = Some(days);
+            response.total_benefit = Some(self.config.money(prorated_total(response.output.monthly_benefit, days)));
+        }
+        if let Some(include) = include_input {
+            response.input = include.then(|| input.clone());
+        }
+        // Set here rather than by the rule document, so clients can always rely on it
+        response.relationship_valid = Some(relationship_valid);
+        response.tenant_id = tenant_id;
+        response.confidence = Some(confidence);
+
+        // The rule document speaks English, its texts are translated to the requested language
+        if let Some(tag) = requested_locale.as_deref().filter(|tag| Locale::from_tag(tag).is_none()) {
+            response.output.warnings.push(i18n::render(
+                locale,
+                "warning.unsupported_locale",
+                &[("locale", &tag.trim()), ("locales", &quoted_values(i18n::SUPPORTED_LANGUAGES)), ("default", &locale)],
+            ));
+        }
+        response.output.description = i18n::rule_text(locale, &response.output.description);
+        response.output.additional_requirements = i18n::rule_text(locale, &response.output.additional_requirements);
+        for error in &mut response.output.errors {
+            *error = i18n::rule_text(locale, error);
+        }
+        response.output.requirements = case_requirements(&response.output.case, &input, locale);
+        response.locale = Some(locale.code().to_string());
+
+        response.output.tidy_messages();
+        if !response.output.potentially_eligible {
+            response.explanation = Some(explain_not_eligible(&input, &response.output, locale));
+        }
+        response.next_steps = next_steps(&response.output, locale);
+        // Generated per evaluation, cached results included
+        response.evaluation_id = Some(uuid::Uuid::new_v4().to_string());
+        if let Some(audit_log) = &self.audit_log {
+            audit_log.record(&input, &response, self.metrics.clone());
+        }
+        if let Some(webhook) = &self.webhook {
+            webhook.deliver(&response, self.metrics.clone());
+        }
+        if let Some(events) = &self.events {
+            events.publish(&input, &response, &self.metrics);
+        }
+        Ok(response)
+    }
+}
+
+#[tool_router]
+impl EligibilityEngine {
+    pub fn new() -> Self {
+        let strict_parsing = std::env::var(STRICT_PARSING_ENV)
+            .map(|value| matches!(value.to_lowercase().as_str(), "true" | "1"))
+            .unwrap_or(false);
+        let compact_json = std::env::var(COMPACT_JSON_ENV)
+            .map(|value| matches!(value.to_lowercase().as_str(), "true" | "1"))
+            .unwrap_or(false);
+        let evaluation_timeout = std::env::var(EVALUATION_TIMEOUT_ENV)
+            .ok()
+            .and_then(|value| value.trim().parse().ok())
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_EVALUATION_TIMEOUT);
+
+        Self {
+            tool_router: Self::tool_router(),
+            strict_parsing,
+            compact_json,
+            metrics: METRICS.clone(),
+            rulesets: default_rulesets().ok(),
+            tenants: TENANTS.as_ref().cloned().unwrap_or_default(),
+            config: current_config(),
+            evaluation_limit: EVALUATION_LIMIT.clone(),
+            evaluation_timeout,
+            retry_policy: RetryPolicy::from_env(),
+            result_cache: RESULT_CACHE.clone(),
+            audit_log: audit::current(),
+            webhook: webhook::current(),
+            events: events::current(),
+        }
+    }
+
+    /// Serves the tenants of `tenants` instead of the ones in `TENANT_RULES_DIR`
+    #[allow(dead_code)]
+    pub fn with_tenants(mut self, tenants: Arc<TenantRegistry>) -> Self {
+        self.tenants = tenants;
+        self
+    }
+
+    /// Reuses engine results from `cache` instead of the one sized by `RESULT_CACHE_CAPACITY`, `None` disables caching
+    #[allow(dead_code)]
+    pub fn with_result_cache(mut self, cache: Option<ResultCache>) -> Self {
+        self.result_cache = cache;
+        self
+    }
+
+    /// Records evaluations to `audit_log` instead of the one opened from `DATABASE_URL`, `None` disables auditing
+    #[allow(dead_code)]
+    pub fn with_audit_log(mut self, audit_log: Option<AuditLog>) -> Self {
+        self.audit_log = audit_log;
+        self
+    }
+
+    /// Delivers evaluations to `webhook` instead of the one configured from `WEBHOOK_URL`, `None` disables delivery
+    #[allow(dead_code)]
+    pub fn with_webhook(mut self, webhook: Option<Webhook>) -> Self {
+        self.webhook = webhook;
+        self
+    }
+
+    /// Publishes evaluations to `events` instead of the Kafka topic in `KAFKA_TOPIC`, `None` disables events
+    #[allow(dead_code)]
+    pub fn with_events(mut self, events: Option<EvaluationEvents>) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Abandons evaluations taking longer than `evaluation_timeout` instead of `EVALUATION_TIMEOUT_MS`
+    #[allow(dead_code)]
+    pub fn with_evaluation_timeout(mut self, evaluation_timeout: Duration) -> Self {
+        self.evaluation_timeout = evaluation_timeout;
+        self
+    }
+
+    /// Retries transient engine errors following `retry_policy` instead of `EVALUATION_RETRIES`
+    #[allow(dead_code)]
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Bounds concurrent evaluations with `limit` instead of `MAX_CONCURRENT_EVALUATIONS`
+    #[allow(dead_code)]
+    pub fn with_evaluation_limit(mut self, limit: Option<EvaluationLimit>) -> Self {
+        self.evaluation_limit = limit;
+        self
+    }
+
+    /// Applies `config` instead of the one loaded from `ELIGIBILITY_CONFIG`
+    #[allow(dead_code)]
+    pub fn with_config(mut self, config: Arc<EligibilityConfig>) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Evaluates against `rulesets` instead of the embedded rule documents
+    #[allow(dead_code)]
+    pub fn with_rulesets(mut self, rulesets: Arc<RulesetRegistry>) -> Self {
+        // Results of the previous rules are unreachable from now on, free them
+        if let Some(cache) = &self.result_cache {
+            cache.clear();
+        }
+        self.rulesets = Some(rulesets);
+        self
+    }
+
+    /// Records metrics in `metrics` instead of the process-wide `METRICS`
+    #[allow(dead_code)]
+    pub fn with_metrics(mut self, metrics: Arc<EligibilityMetrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Rejects inputs with unknown fields instead of silently ignoring them
+    #[allow(dead_code)]
+    pub fn with_strict_parsing(mut self, strict_parsing: bool) -> Self {
+        self.strict_parsing = strict_parsing;
+        self
+    }
+
+    /// Answers tool calls with compact JSON instead of pretty-printed JSON
+    #[allow(dead_code)]
+    pub fn with_compact_json(mut self, compact_json: bool) -> Self {
+        self.compact_json = compact_json;
+        self
+    }
+
+    /// Rulesets evaluated by this engine, the load error of the default rule documents if they have none
+    fn rulesets(&self) -> Result<Arc<RulesetRegistry>, UnpaidLeaveError> {
+        match &self.rulesets {
+            Some(rulesets) => Ok(rulesets.clone()),
+            None => default_rulesets(),
+        }
+    }
+
+    /// Tenant label of the metrics of `direct_params`, `None` under the shared rules. Tenants this
+    /// server does not know share one label so arbitrary ids do not create new series
+    fn tenant_label(&self, direct_params: &UnpaidLeaveDirectParams) -> Option<String> {
+        direct_params.normalized_tenant_id().map(|tenant_id| match self.tenants.get(&tenant_id) {
+            Some(_) => tenant_id,
+            None => UNKNOWN_TENANT_LABEL.to_string(),
+        })
+    }
+
+    /// Rulesets of `tenant_id`, the shared ones when `None`. Unknown tenants are a validation
+    /// error in `locale`, without listing the others
+    fn tenant_rulesets(&self, tenant_id: Option<&str>, locale: Locale) -> Result<Arc<RulesetRegistry>, UnpaidLeaveError> {
+        let Some(tenant_id) = tenant_id else {
+            return self.rulesets();
+        };
+        self.tenants.get(tenant_id).ok_or_else(|| {
+            UnpaidLeaveError::ValidationError(vec![ValidationError {
+                message: i18n::render(locale, "validation.unknown_tenant", &[("tenant_id", &tenant_id)]),
+                path: "/input/tenant_id".to_string(),
+            }])
+        })
+    }
+
+    /// Contents of the `RULES_RESOURCE_URI` resource: the version, effective date and JDM
+    /// document of the latest ruleset
+    pub fn rules_resource(&self) -> Result<ResourceContents, UnpaidLeaveError> {
+        let rulesets = self.rulesets()?;
+        let ruleset = rulesets
+            .select(None)
+            .ok_or_else(|| UnpaidLeaveError::RulesUnavailable("no ruleset loaded".to_string()))?;
+        let document = serde_json::json!({
+            "version": ruleset.version,
+            "effective_from": ruleset.effective_from,
+            "rules": ruleset.content(),
+        });
+        Ok(ResourceContents::TextResourceContents {
+            uri: RULES_RESOURCE_URI.to_string(),
+            mime_type: Some("application/json".to_string()),
+            text: self.to_json(&document)?,
+            meta: None,
+        })
+    }
+
+    /// Serializes a tool result, compact or pretty-printed depending on `COMPACT_JSON_OUTPUT`
+    fn to_json<T: Serialize>(&self, value: &T) -> serde_json::Result<String> {
+        if self.compact_json {
+            serde_json::to_string(value)
+        } else {
+            serde_json::to_string_pretty(value)
+        }
+    }
+
+    /// Evaluates unpaid leave assistance eligibility according to fictional regulations
+    /// 
+    /// IMPORTANT: Use the exact values specified in each parameter.
+    /// IMPORTANT: If number of children is greater than one, USE 'multiple_birth' or 'multiple_adoption' or 'multiple_foster_care'.
+    /// IMPORTANT: If no information regarding the family structure use always false.
+    /// IMPORTANT: If no information regarding the number of children use always 0.
+    #[tool(description = "Evaluates unpaid leave assistance eligibility according to legal regulations. Determines case (A-E) and monthly benefit, 0 when not eligible. CASES: A=Sick family care, B=Third child+, C=Adoption, D=Multiple, E=Single-parent. USE EXACT VALUES: relationship and situation take only the values listed in their parameter descriptions (or by list_eligibility_options), is_single_parent (true/false), total_children_after (number), effective_date (optional, YYYY-MM-DD).")]
+    pub async fn evaluate_unpaid_leave_eligibility(
+        &self, 
+        Parameters(mut direct_params): Parameters<UnpaidLeaveDirectParams>,
+        extensions: Extensions,
+    ) -> Result<CallToolResult, McpError> {
+        // The streamable-http transport hands over the HTTP request, other transports do not
+        let parts = extensions.get::<axum::http::request::Parts>();
+        let request_id = parts.and_then(|parts| request_id_from_headers(&parts.headers));
+        default_locale(&mut direct_params.locale, &extensions);
+        match self.evaluate_with_request_id(direct_params, request_id).await {
+            Ok(response) => {
+                // Serialize the response to JSON and return as success
+                match self.to_json(&response) {
+                    Ok(json_str) => Ok(CallToolResult::success(vec![Content::text(json_str)])),
+                    Err(e) => {
+                        let labels = MetricLabels::default().with_tenant(response.tenant_id.as_deref());
+                        self.metrics.increment_errors("serialization", &labels);
+                        Ok(CallToolResult::error(vec![Content::text(format!(
+                            "Error serializing response: {}", e
+                        ))]))
+                    }
+                }
+            },
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format_evaluation_error(&e))])),
+        }
+    }
+
+    /// Evaluates unpaid leave assistance eligibility and explains the decision path
+    #[tool(description = "Same as evaluate_unpaid_leave_eligibility (same parameters and result) but also returns 'trace', the node-by-node decision path followed by the rule engine, including the decision table rule that matched. Use it to explain WHY a case letter was assigned.")]
+    pub async fn evaluate_unpaid_leave_explain(
+        &self,
+        Parameters(mut direct_params): Parameters<UnpaidLeaveDirectParams>,
+        extensions: Extensions,
+    ) -> Result<CallToolResult, McpError> {
+        default_locale(&mut direct_params.locale, &extensions);
+        match self.evaluate_explained(direct_params).await {
+            Ok(response) => match self.to_json(&response) {
+                Ok(json_str) => Ok(CallToolResult::success(vec![Content::text(json_str)])),
+                Err(e) => {
+                    let labels = MetricLabels::new("evaluate_unpaid_leave_explain").with_tenant(response.tenant_id.as_deref());
+                    self.metrics.increment_errors("serialization", &labels);
+                    Ok(CallToolResult::error(vec![Content::text(format!(
+                        "Error serializing response: {}", e
+                    ))]))
+                }
+            },
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format_evaluation_error(&e))])),
+        }
+    }
+
+    /// Evaluates several inputs concurrently, reporting each result independently
+    #[tool(description = "Evaluates unpaid leave assistance eligibility for several inputs in one call (e.g. all applicants of a household). Takes 'inputs', a list of objects with the same fields as evaluate_unpaid_leave_eligibility. Returns one item per input, in order, with either its 'response' or its 'error' and 'validation_errors', so an invalid input does not fail the whole batch.")]
+    pub async fn evaluate_unpaid_leave_batch(
+        &self,
+        Parameters(mut batch): Parameters<UnpaidLeaveBatchParams>,
+        extensions: Extensions,
+    ) -> Result<CallToolResult, McpError> {
+        for input in &mut batch.inputs {
+            default_locale(&mut input.locale, &extensions);
+        }
+        if batch.inputs.len() > MAX_BATCH_SIZE {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "Batch too large: {} inputs received, the maximum is {}", batch.inputs.len(), MAX_BATCH_SIZE
+            ))]));
+        }
+
+        let evaluations = batch.inputs.into_iter().map(|direct_params| self.evaluate_for_tool(direct_params, "evaluate_unpaid_leave_batch"));
+        let items: Vec<UnpaidLeaveBatchItem> = futures::future::join_all(evaluations)
+            .await
+            .into_iter()
+            .enumerate()
+            .map(|(index, result)| UnpaidLeaveBatchItem::new(index, result))
+            .collect();
+
+        match self.to_json(&items) {
+            Ok(json_str) => Ok(CallToolResult::success(vec![Content::text(json_str)])),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Error serializing batch response: {}", e
+            ))])),
+        }
+    }
+
+    /// Evaluates two scenarios and reports how the result changes between them
+    #[tool(description = "Compares two unpaid leave scenarios, e.g. 'what if I have one more child?'. Takes 'before' and 'after', each with the same fields as evaluate_unpaid_leave_eligibility. Returns both responses plus 'changes': the case, monthly_benefit and potentially_eligible values that differ, and the warnings added or removed.")]
+    pub async fn evaluate_unpaid_leave_diff(
+        &self,
+        Parameters(mut diff): Parameters<UnpaidLeaveDiffParams>,
+        extensions: Extensions,
+    ) -> Result<CallToolResult, McpError> {
+        default_locale(&mut diff.before.locale, &extensions);
+        default_locale(&mut diff.after.locale, &extensions);
+        let (before, after) = futures::future::join(
+            self.evaluate_for_tool(diff.before, "evaluate_unpaid_leave_diff"),
+            self.evaluate_for_tool(diff.after, "evaluate_unpaid_leave_diff"),
+        ).await;
+        let (before, after) = match (before, after) {
+            (Ok(before), Ok(after)) => (before, after),
+            (Err(e), _) => return Ok(CallToolResult::error(vec![Content::text(format!(
+                "Error evaluating 'before' scenario: {}", format_evaluation_error(&e)
+            ))])),
+            (_, Err(e)) => return Ok(CallToolResult::error(vec![Content::text(format!(
+                "Error evaluating 'after' scenario: {}", format_evaluation_error(&e)
+            ))])),
+        };
+
+        let changes = UnpaidLeaveDiff::new(&before, &after);
+        let response = UnpaidLeaveDiffResponse { before, after, changes };
+        match self.to_json(&response) {
+            Ok(json_str) => Ok(CallToolResult::success(vec![Content::text(json_str)])),
+            Err(e) => {
+                self.metrics.increment_errors("serialization", &MetricLabels::new("evaluate_unpaid_leave_diff"));
+                Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Error serializing diff response: {}", e
+                ))]))
+            }
+        }
+    }
+
+    /// Checks unpaid leave input without evaluating eligibility
+    #[tool(description = "Validates unpaid leave assistance input WITHOUT evaluating eligibility. Takes the same parameters as evaluate_unpaid_leave_eligibility and returns whether they are well-formed (valid), the list of errors (with suggested values for likely typos) and warnings about implausible or incomplete combinations. Use it to check input before a full evaluation.")]
+    pub async fn validate_unpaid_leave_input(
+        &self,
+        Parameters(mut direct_params): Parameters<UnpaidLeaveDirectParams>,
+        extensions: Extensions,
+    ) -> Result<CallToolResult, McpError> {
+        default_locale(&mut direct_params.locale, &extensions);
+        let validation = direct_params.validate(self.strict_parsing);
+        match self.to_json(&validation) {
+            Ok(json_str) => Ok(CallToolResult::success(vec![Content::text(json_str)])),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Error serializing validation result: {}", e
+            ))])),
+        }
+    }
+
+    /// Records what actually happened to an evaluated application
+    #[tool(description = "Records the real-world outcome of a previous evaluation, used to improve the rules. Takes 'evaluation_id' (as returned by evaluate_unpaid_leave_eligibility), 'outcome' ('approved'/'denied'/'withdrawn') and optional 'notes'. Returns 'recorded': false when the server does not persist evaluations.")]
+    pub async fn record_eligibility_feedback(
+        &self,
+        Parameters(params): Parameters<EligibilityFeedbackParams>
+    ) -> Result<CallToolResult, McpError> {
+        match self.record_feedback(params).await {
+            Ok(response) => match self.to_json(&response) {
+                Ok(json_str) => Ok(CallToolResult::success(vec![Content::text(json_str)])),
+                Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Error serializing feedback response: {}", e
+                ))])),
+            },
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format_evaluation_error(&e))])),
+        }
+    }
+
+    /// Describes a case without evaluating any input
+    #[tool(description = "Returns the full details of one case without running an evaluation: its description, monthly_benefit and the list of additional_requirements the applicant must prove. Takes 'case' ('A'-'E') and optional 'locale' ('es' default, or 'en'). Texts are the same an evaluation falling under the case returns. Use it to explain a determination.")]
+    pub async fn describe_case(
+        &self,
+        Parameters(mut params): Parameters<DescribeCaseParams>,
+        extensions: Extensions,
+    ) -> Result<CallToolResult, McpError> {
+        default_locale(&mut params.locale, &extensions);
+        match self.case_description(params) {
+            Ok(description) => match self.to_json(&description) {
+                Ok(json_str) => Ok(CallToolResult::success(vec![Content::text(json_str)])),
+                Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Error serializing case description: {}", e
+                ))])),
+            },
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format_evaluation_error(&e))])),
+        }
+    }
+
+    /// Estimates the possible benefits of an incomplete input
+    #[tool(description = "Estimates the unpaid leave assistance possible for an INCOMPLETE input, e.g. before the user knows every detail. Takes the same fields as evaluate_unpaid_leave_eligibility (relationship, situation, is_single_parent, total_children_after, region, leave_percentage), all optional, but at least the relationship or the situation is needed. Returns min_monthly_benefit and max_monthly_benefit among the eligible outcomes, the candidate_cases, whether the input may_be_ineligible and the unknown_fields that were enumerated. Use evaluate_unpaid_leave_eligibility once the input is complete.")]
+    pub async fn estimate_unpaid_leave(
+        &self,
+        Parameters(params): Parameters<UnpaidLeaveEstimateParams>
+    ) -> Result<CallToolResult, McpError> {
+        match self.estimate(params).await {
+            Ok(estimate) => match self.to_json(&estimate) {
+                Ok(json_str) => Ok(CallToolResult::success(vec![Content::text(json_str)])),
+                Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Error serializing estimate: {}", e
+                ))])),
+            },
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format_evaluation_error(&e))])),
+        }
+    }
+
+    /// Evaluates an input against a rule document given in the call, for rule authors
+    #[tool(description = "ADMIN tool, only available to clients presenting the server API key. Evaluates an input against a rule document given in the call instead of the deployed rules, without storing it. Takes 'input', with the same fields as evaluate_unpaid_leave_eligibility, and 'rules', a full GoRules JDM decision document. Returns the 'response', or the 'error' and 'validation_errors' of the input, plus 'rule_errors', the problems that would prevent deploying the document (empty when it could be deployed).")]
+    pub async fn evaluate_with_rules(
+        &self,
+        Parameters(mut params): Parameters<EvaluateWithRulesParams>,
+        extensions: Extensions,
+    ) -> Result<CallToolResult, McpError> {
+        let parts = extensions.get::<axum::http::request::Parts>();
+        if !parts.is_some_and(|parts| http::is_admin(&parts.headers)) {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "evaluate_with_rules requires the server API key, configured with API_KEY and presented as 'Authorization: Bearer <key>' or 'X-API-Key: <key>'",
+            )]));
+        }
+
+        default_locale(&mut params.input.locale, &extensions);
+        let response = self.evaluate_against_rules(params).await;
+        match self.to_json(&response) {
+            Ok(json_str) => Ok(CallToolResult::success(vec![Content::text(json_str)])),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Error serializing response: {}", e
+            ))])),
+        }
+    }
+
+    /// Lists valid parameter values and the cases evaluated
+    #[tool(description = "Lists, as structured JSON, every valid value for the relationship and situation parameters and the cases A-E with their description, monthly benefit and additional requirements. Use it as the source of truth for the exact values accepted by evaluate_unpaid_leave_eligibility.")]
+    pub async fn list_eligibility_options(&self) -> Result<CallToolResult, McpError> {
+        match self.to_json(&EligibilityOptions::new()) {
+            Ok(json_str) => Ok(CallToolResult::success(vec![Content::text(json_str)])),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Error serializing eligibility options: {}", e
+            ))])),
+        }
+    }
+}
+
+#[tool_handler]
+impl ServerHandler for EligibilityEngine {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            instructions: Some(format!(
+                "Eligibility Engine for leave assistance according to legal regulations. \
+                 \n\n** IMPORTANT TOOL USAGE INSTRUCTIONS **\
+                 \n\n1. ALWAYS use the EXACT values specified for each parameter, in lowercase\
+                 \n\n2. For relationship, use ONLY: {}\
+                 \n\n3. For situation, use ONLY: {}. If number of children is greater than one, USE 'multiple_birth' or 'multiple_adoption' or 'multiple_foster_care'\
+                 \n\n4. For is_single_parent, use ONLY: true (for single-parent families) or false (for families with both parents). If no information regarding the family structure use always false\
+                 \n\n5. For total_children_after, use whole numbers (eg: 1, 2, 3, 4, 5). ONLY if situation is 'birth' or 'adoption' or 'foster_care' or 'multiple_birth' or 'multiple_adoption' or 'multiple_foster_care'
+                 \n\nCORRECT USAGE EXAMPLES:\
+                 \n• Single father with baby: relationship='father', situation='birth', is_single_parent=true, total_children_after=1\
+                 \n• Son caring for sick father: relationship='father', situation='illness', is_single_parent=false, total_children_after=0\
+                 \n• Family with third child: relationship='mother', situation='birth', is_single_parent=false, total_children_after=3\
+                 \n• Family with multiple children: relationship='mother', situation='multiple_birth', is_single_parent=false, total_children_after=3\
+                 \n• Family with multiple children: relationship='mother', situation='multiple_adoption', is_single_parent=false, total_children_after=3\
+                 \n• Family with multiple children: relationship='mother', situation='multiple_foster_care', is_single_parent=false, total_children_after=3\
+                 \n\nCASES EVALUATED:{}",
+                quoted_values(VALID_RELATIONSHIPS),
+                quoted_values(VALID_SITUATIONS),
+                self.cases_summary(),
+            )),
+            capabilities: ServerCapabilities::builder().enable_tools().enable_resources().enable_prompts().build(),
+            server_info: rmcp::model::Implementation {
+                name: SERVER_NAME.to_string(),
+                version: SERVER_VERSION.to_string(),
+                title: Some(SERVER_TITLE.to_string()), 
+                icons: None, 
+                website_url: None 
+            },
+            ..Default::default()
+        }
+    }
+
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, McpError> {
+        let mut resource = RawResource::new(RULES_RESOURCE_URI, "rules");
+        resource.title = Some("Eligibility rules".to_string());
+        resource.description = Some("JDM rule document of the regulation currently in effect, with its version".to_string());
+        resource.mime_type = Some("application/json".to_string());
+        Ok(ListResourcesResult {
+            resources: vec![resource.no_annotation()],
+            next_cursor: None,
+        })
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, McpError> {
+        if request.uri != RULES_RESOURCE_URI {
+            return Err(McpError::resource_not_found(
+                format!("Unknown resource '{}'", request.uri),
+                Some(serde_json::json!({ "uri": request.uri })),
+            ));
+        }
+        let contents = self
+            .rules_resource()
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(ReadResourceResult { contents: vec![contents] })
+    }
+
+    async fn list_prompts(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListPromptsResult, McpError> {
+        Ok(ListPromptsResult {
+            prompts: prompts::list(),
+            next_cursor: None,
+        })
+    }
+
+    async fn get_prompt(
+        &self,
+        request: GetPromptRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<GetPromptResult, McpError> {
+        prompts::get(&request.name).ok_or_else(|| {
+            McpError::invalid_params(format!("Unknown prompt '{}'", request.name), None)
+        })
+    }
+}
+
+/// Input with only a relationship and a situation, shared by the tests of every module
+#[cfg(test)]
+pub(crate) fn direct_params(relationship: &str, situation: &str) -> UnpaidLeaveDirectParams {
+    UnpaidLeaveDirectParams {
+        input: UnpaidLeaveInput {
+            relationship: relationship.to_string(),
+            situation: situation.to_string(),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// `params` asking for English texts, for tests checking the wording of messages
+#[cfg(test)]
+fn in_english(mut params: UnpaidLeaveDirectParams) -> UnpaidLeaveDirectParams {
+    params.locale = Some("en".to_string());
+    params
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_eligibility_engine_case_a() {
+        let eligibility_engine = EligibilityEngine::new();
+        let direct_params = UnpaidLeaveDirectParams {
+            input: UnpaidLeaveInput {
+                relationship: "mother".to_string(),
+                situation: "illness".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        
+        let result = eligibility_engine.evaluate_unpaid_leave_eligibility(Parameters(direct_params), Extensions::new()).await;
+        match result {
+            Ok(call_result) => {
+                // Check if it's a success result
+                println!("Resultado Supuesto A: {:?}", call_result);
+                let content = call_result.content;
+                assert!(!content.is_empty(), "Content should not be empty");
+                let raw_content = &content[0].raw;
+                // Extract the text from the raw content, it has to be a string
+                let json_text = &raw_content.as_text().unwrap().text;
+                let response: UnpaidLeaveResponse = serde_json::from_str(json_text).unwrap();
+                assert_eq!(response.output.case, "A");
+                assert!(response.output.potentially_eligible);
+                assert_eq!(response.output.monthly_benefit, 725);
+                
+            },
+            Err(e) => panic!("Error inesperado: {}", e),
+        }
+    }
+
+    #[tokio::test] 
+    async fn test_eligibility_engine_case_e() {
+        let eligibility_engine = EligibilityEngine::new();
+        let direct_params = UnpaidLeaveDirectParams {
+            input: UnpaidLeaveInput {
+                relationship: "mother".to_string(),
+                situation: "birth".to_string(),
+                is_single_parent: true,
+                total_children_after: Some(1.0),
+            },
+            ..Default::default()
+        };
+        
+        let result = eligibility_engine.evaluate_unpaid_leave_eligibility(Parameters(direct_params), Extensions::new()).await;
+        match result {
+            Ok(call_result) => {
+                println!("Resultado Supuesto E: {:?}", call_result);
+            },
+            Err(e) => panic!("Error inesperado: {}", e),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_eligibility_engine_case_b() {
+        let eligibility_engine = EligibilityEngine::new();
+        let direct_params = UnpaidLeaveDirectParams {
+            input: UnpaidLeaveInput {
+                relationship: "mother".to_string(),
+                situation: "birth".to_string(),
+                total_children_after: Some(3.0), // Third child
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        
+        let result = eligibility_engine.evaluate_unpaid_leave_eligibility(Parameters(direct_params), Extensions::new()).await;
+        match result {
+            Ok(call_result) => {
+                println!("Resultado Supuesto B: {:?}", call_result);
+            },
+            Err(e) => panic!("Error inesperado: {}", e),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_eligibility_engine_validation_error() {
+        let eligibility_engine = EligibilityEngine::new();
+        let direct_params = UnpaidLeaveDirectParams {
+            input: UnpaidLeaveInput {
+                relationship: "brother".to_string(), // Not valid
+                situation: "birth".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        
+        let result = eligibility_engine.evaluate_unpaid_leave_eligibility(Parameters(direct_params), Extensions::new()).await;
+        match result {
+            Ok(call_result) => {
+                // Should handle validation errors appropriately
+                println!("Validation result: {:?}", call_result);
+            },
+            Err(e) => panic!("Error inesperado: {}", e),
+        }
+    }
+
+    /// Evaluates the tool call and snapshots its text content under `src/common/snapshots/`.
+    /// The module prefix is dropped so the three binaries share the same snapshot files
+    async fn assert_tool_snapshot(name: &str, params: UnpaidLeaveDirectParams) {
+        let call_result = EligibilityEngine::new()
+            .evaluate_unpaid_leave_eligibility(Parameters(params), Extensions::new())
+            .await
+            .unwrap();
+        let text = &call_result.content[0].raw.as_text().unwrap().text;
+        insta::with_settings!({ prepend_module_to_snapshot => false }, {
+            match serde_json::from_str::<UnpaidLeaveResponse>(text) {
+                Ok(mut response) => {
+                    // Generated per evaluation
+                    response.evaluation_id = response.evaluation_id.map(|_| "[evaluation_id]".to_string());
+                    insta::assert_json_snapshot!(name, response)
+                }
+                Err(_) => insta::assert_snapshot!(name, text),
+            }
+        });
+    }
+
+    fn birth_params(situation: &str, is_single_parent: bool, total_children_after: f64) -> UnpaidLeaveDirectParams {
+        let mut params = direct_params("mother", situation);
+        params.input.is_single_parent = is_single_parent;
+        params.input.total_children_after = Some(total_children_after);
+        params
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_case_a_family_care() {
+        assert_tool_snapshot("case_a_family_care", direct_params("son", "accident")).await;
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_case_b_third_child() {
+        assert_tool_snapshot("case_b_third_child", birth_params("birth", false, 3.0)).await;
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_case_c_adoption() {
+        assert_tool_snapshot("case_c_adoption", birth_params("adoption", false, 1.0)).await;
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_case_d_multiple_birth() {
+        assert_tool_snapshot("case_d_multiple_birth", birth_params("multiple_birth", false, 2.0)).await;
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_case_e_single_parent() {
+        assert_tool_snapshot("case_e_single_parent", birth_params("birth", true, 1.0)).await;
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_not_eligible() {
+        assert_tool_snapshot("not_eligible_first_child", birth_params("birth", false, 1.0)).await;
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_validation_error() {
+        assert_tool_snapshot("validation_error", direct_params("brother", "birht")).await;
+    }
+
+    fn params_with_bogus_field() -> UnpaidLeaveDirectParams {
+        serde_json::from_value(serde_json::json!({
+            "relationship": "mother",
+            "situation": "illness",
+            "is_single_parent": false,
+            "foo": "bar"
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_direct_params_into_input() {
+        let mut params = direct_params(" Mom ", "Sickness");
+        params.input.is_single_parent = true;
+        params.input.total_children_after = Some(1.0);
+        params.leave_days = Some(15);
+
+        let input = UnpaidLeaveInput::from(params);
+        assert_eq!(input, UnpaidLeaveInput {
+            relationship: "mother".to_string(),
+            situation: "illness".to_string(),
+            is_single_parent: true,
+            total_children_after: Some(1.0),
+        });
+    }
+
+    #[test]
+    fn test_direct_params_round_trip() {
+        let mut params = direct_params("mother", "birth");
+        params.input.is_single_parent = true;
+        params.input.total_children_after = Some(2.0);
+        params.effective_date = Some("2025-03-01".to_string());
+        params.leave_days = Some(10);
+        params.leave_months = Some(2);
+        params.leave_percentage = Some(50);
+
+        // The MCP parameters stay flat on the wire
+        let value = serde_json::to_value(&params).unwrap();
+        assert_eq!(value["relationship"], "mother");
+        assert_eq!(value["total_children_after"], 2.0);
+        assert!(value.get("input").is_none());
+        let schema = serde_json::to_value(schemars::schema_for!(UnpaidLeaveDirectParams)).unwrap();
+        assert!(schema["properties"]["situation"].is_object());
+        assert!(schema["properties"].get("input").is_none());
+
+        let parsed: UnpaidLeaveDirectParams = serde_json::from_value(value).unwrap();
+        assert!(parsed.unknown_fields.is_empty());
+        assert_eq!(parsed, params);
+
+        let (request, _) = parsed.into_request();
+        assert_eq!(request.input, params.input);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_field_ignored_in_lenient_mode() {
+        let eligibility_engine = EligibilityEngine::new().with_strict_parsing(false);
+        let direct_params = params_with_bogus_field();
+        assert!(direct_params.unknown_fields.contains_key("foo"));
+
+        let call_result = eligibility_engine.evaluate_unpaid_leave_eligibility(Parameters(direct_params), Extensions::new()).await.unwrap();
+        assert_eq!(call_result.is_error, Some(false));
+        let json_text = &call_result.content[0].raw.as_text().unwrap().text;
+        let response: UnpaidLeaveResponse = serde_json::from_str(json_text).unwrap();
+        assert_eq!(response.output.case, "A");
+    }
+
+    #[tokio::test]
+    async fn test_unknown_field_rejected_in_strict_mode() {
+        let eligibility_engine = EligibilityEngine::new().with_strict_parsing(true);
+        let direct_params = params_with_bogus_field();
+
+        let call_result = eligibility_engine.evaluate_unpaid_leave_eligibility(Parameters(direct_params), Extensions::new()).await.unwrap();
+        assert_eq!(call_result.is_error, Some(true));
+        let error_text = &call_result.content[0].raw.as_text().unwrap().text;
+        assert!(error_text.contains("/input/foo"), "unexpected error: {}", error_text);
+        assert!(error_text.contains("campo desconocido 'foo'"), "unexpected error: {}", error_text);
+    }
+
+    #[test]
+    fn test_cross_field_warnings_for_implausible_pairs() {
+        let warnings = direct_params("son", "birth").cross_field_warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("'son'") && warnings[0].contains("'birth'"));
+
+        assert_eq!(direct_params("foster_parent", "multiple_birth").cross_field_warnings().len(), 1);
+        assert!(direct_params("mother", "birth").cross_field_warnings().is_empty());
+        assert!(direct_params("daughter", "illness").cross_field_warnings().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cross_field_warnings_in_response() {
+        let eligibility_engine = EligibilityEngine::new();
+        let direct_params = direct_params("daughter", "adoption");
+
+        let call_result = eligibility_engine.evaluate_unpaid_leave_eligibility(Parameters(direct_params), Extensions::new()).await.unwrap();
+        let json_text = &call_result.content[0].raw.as_text().unwrap().text;
+        let response: UnpaidLeaveResponse = serde_json::from_str(json_text).unwrap();
+        assert!(response.output.warnings.iter().any(|w| w.contains("'daughter'") && w.contains("'adoption'")));
+    }
+
+    #[test]
+    fn test_synonyms_normalized() {
+        for (relationship, situation, expected) in [
+            ("dad", "newborn", ("father", "birth")),
+            ("mom", "sickness", ("mother", "illness")),
+            ("kid", "injury", ("son", "accident")),
+            ("girlfriend", "twins", ("partner", "multiple_birth")),
+        ] {
+            let mut params = direct_params(relationship, situation);
+            let warnings = params.normalize();
+            assert_eq!((params.input.relationship.as_str(), params.input.situation.as_str()), expected);
+            assert_eq!(warnings.len(), 2);
+        }
+
+        // Canonical and unknown values are left untouched
+        let mut params = direct_params("mother", "brother");
+        assert!(params.normalize().is_empty());
+        assert_eq!(params.input.situation, "brother");
+    }
+
+    #[test]
+    fn test_case_and_whitespace_normalized() {
+        let mut params = in_english(direct_params("Father", " BIRTH "));
+        assert_eq!(params.normalize(), vec![
+            "relationship 'Father' was normalized to 'father'".to_string(),
+            "situation ' BIRTH ' was normalized to 'birth'".to_string(),
+        ]);
+        assert_eq!((params.input.relationship.as_str(), params.input.situation.as_str()), ("father", "birth"));
+
+        // Casing does not prevent synonyms from being recognized
+        let mut params = direct_params("  Mom", "Newborn");
+        params.normalize();
+        assert_eq!((params.input.relationship.as_str(), params.input.situation.as_str()), ("mother", "birth"));
+    }
+
+    #[test]
+    fn test_whitespace_collapsed() {
+        for (situation, expected) in [
+            ("\tadoption\n", "adoption"),
+            ("multiple \n birth", "multiple_birth"),
+            ("foster   care", "foster_care"),
+            ("not  a\nsituation", "not a situation"),
+        ] {
+            let mut params = direct_params("mother", situation);
+            assert_eq!(params.normalize().len(), 1, "{:?}", situation);
+            assert_eq!(params.input.situation, expected);
+        }
+
+        let mut params = direct_params(" foster\r\nparent ", "foster_care");
+        params.normalize();
+        assert_eq!(params.input.relationship, "foster_parent");
+        assert!(params.validate(false).valid);
+    }
+
+    #[tokio::test]
+    async fn test_mixed_case_input_evaluated() {
+        let eligibility_engine = EligibilityEngine::new();
+        for (relationship, situation) in [("Mother", "ILLNESS"), (" mother ", "Illness\n"), ("MOTHER", "illness")] {
+            let response = eligibility_engine.evaluate(direct_params(relationship, situation)).await.unwrap();
+            assert_eq!(response.output.case, "A", "{:?} / {:?}", relationship, situation);
+        }
+        assert!(direct_params("Foster_Parent", "Foster_Care").validate(false).valid);
+    }
+
+    #[tokio::test]
+    async fn test_synonyms_evaluated_and_reported() {
+        let eligibility_engine = EligibilityEngine::new();
+        let response = eligibility_engine.evaluate(direct_params("mom", "sickness")).await.unwrap();
+        assert_eq!(response.output.case, "A");
+        assert!(response.output.warnings.contains(&"relationship 'mom' se interpretó como 'mother'".to_string()));
+        assert!(response.output.warnings.contains(&"situation 'sickness' se interpretó como 'illness'".to_string()));
+
+        let validation = direct_params("dad", "newborn").validate(false);
+        assert!(validation.valid, "{:?}", validation.errors);
+        assert!(validation.warnings.iter().any(|w| w.contains("'dad'")));
+    }
+
+    #[tokio::test]
+    async fn test_confidence_reflects_normalization() {
+        for (relationship, situation, expected) in [
+            ("mother", "illness", 1.0),
+            (" Mother", "illness", 0.95),
+            ("MOTHER", "Illness ", 0.9),
+            ("mom", "illness", 0.8),
+            ("Mom", "sickness", 0.6),
+            ("brother", "illness", 0.5),
+            ("brother", "teleportation", 0.0),
+        ] {
+            assert_eq!(direct_params(relationship, situation).confidence(), expected, "{:?} / {:?}", relationship, situation);
+        }
+
+        let eligibility_engine = EligibilityEngine::new();
+        let exact = eligibility_engine.evaluate(direct_params("mother", "illness")).await.unwrap();
+        assert_eq!(exact.confidence, Some(1.0));
+        let interpreted = eligibility_engine.evaluate(direct_params("mom", "sickness")).await.unwrap();
+        assert_eq!(interpreted.output.case, exact.output.case);
+        assert_eq!(interpreted.confidence, Some(0.6));
+    }
+
+    #[tokio::test]
+    async fn test_messages_sorted_without_duplicates() {
+        let mut output = UnpaidLeaveOutputForSchema {
+            description: String::new(),
+            monthly_benefit: 0,
+            additional_requirements: String::new(),
+            requirements: vec![],
+            case: String::new(),
+            potentially_eligible: false,
+            errors: vec!["b".to_string(), "a".to_string(), "b".to_string()],
+            warnings: vec!["situation changed".to_string(), "relationship changed".to_string(), "situation changed".to_string()],
+        };
+        output.tidy_messages();
+        assert_eq!(output.errors, vec!["a", "b"]);
+        assert_eq!(output.warnings, vec!["relationship changed", "situation changed"]);
+
+        let mut params = direct_params("MOM", "Sickness");
+        params.input.total_children_after = Some(2.0);
+        let warnings = EligibilityEngine::new().evaluate(params).await.unwrap().output.warnings;
+        assert!(warnings.len() >= 3, "{:?}", warnings);
+        assert!(warnings.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", warnings);
+    }
+
+    #[test]
+    fn test_children_count_warnings() {
+        let mut care_params = direct_params("son", "illness");
+        care_params.input.total_children_after = Some(2.0);
+        let (request, warnings) = care_params.into_request();
+        assert_eq!(request.input.situation, "illness");
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("no se tiene en cuenta"), "{}", warnings[0]);
+
+        let mut care_params = direct_params("son", "accident");
+        care_params.input.total_children_after = Some(0.0);
+        assert!(care_params.into_request().1.is_empty());
+
+        let (_, warnings) = direct_params("mother", "adoption").into_request();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("no se indicó"), "{}", warnings[0]);
+
+        let mut birth_params = direct_params("mother", "birth");
+        birth_params.input.total_children_after = Some(1.0);
+        assert!(birth_params.into_request().1.is_empty());
+    }
+
+    #[test]
+    fn test_suggest_value() {
+        assert_eq!(suggest_value("birht", VALID_SITUATIONS), Some("birth"));
+        assert_eq!(suggest_value("Father", VALID_RELATIONSHIPS), Some("father"));
+        assert_eq!(suggest_value("adoptoin", VALID_SITUATIONS), Some("adoption"));
+        assert_eq!(suggest_value("brother", VALID_RELATIONSHIPS), None);
+        assert_eq!(suggest_value("cat", VALID_RELATIONSHIPS), None);
+    }
+
+    #[tokio::test]
+    async fn test_validate_unpaid_leave_input() {
+        let eligibility_engine = EligibilityEngine::new().with_strict_parsing(true);
+
+        let mut valid_params = direct_params("mother", "birth");
+        valid_params.input.total_children_after = Some(1.0);
+        let call_result = eligibility_engine.validate_unpaid_leave_input(Parameters(valid_params), Extensions::new()).await.unwrap();
+        let json_text = &call_result.content[0].raw.as_text().unwrap().text;
+        let validation: UnpaidLeaveValidationResult = serde_json::from_str(json_text).unwrap();
+        assert!(validation.valid);
+        assert!(validation.errors.is_empty());
+        assert!(validation.warnings.is_empty());
+
+        let mut invalid_params = params_with_bogus_field();
+        invalid_params.input.relationship = "brother".to_string();
+        invalid_params.input.situation = "birht".to_string();
+        let call_result = eligibility_engine.validate_unpaid_leave_input(Parameters(invalid_params), Extensions::new()).await.unwrap();
+        let json_text = &call_result.content[0].raw.as_text().unwrap().text;
+        let validation: UnpaidLeaveValidationResult = serde_json::from_str(json_text).unwrap();
+        assert!(!validation.valid);
+        let paths: Vec<&str> = validation.errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, vec!["/input/relationship", "/input/situation", "/input/foo"]);
+        assert!(validation.errors[1].message.contains("¿Quiso decir 'birth'?"), "{}", validation.errors[1].message);
+    }
+
+    #[tokio::test]
+    async fn test_default_params_are_invalid() {
+        let validation = UnpaidLeaveDirectParams::default().validate(true);
+        assert!(!validation.valid);
+        let paths: Vec<&str> = validation.errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, vec!["/input/relationship", "/input/situation"]);
+
+        let result = EligibilityEngine::new().evaluate(UnpaidLeaveDirectParams::default()).await;
+        assert!(matches!(result, Err(UnpaidLeaveError::ValidationError(_))));
+    }
+
+    #[test]
+    fn test_eligibility_cases_match_rule_document() {
+        let rules: serde_json::Value =
+            serde_json::from_str(include_str!("unpaid-leave-assistance-2025.json")).unwrap();
+        let unquote = |value: &serde_json::Value| value.as_str().unwrap().trim_matches('"').to_string();
+
+        let eligible_rows: Vec<(String, String, i32, Vec<String>)> = rules["nodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|node| node["name"] == "evaluate_unpaid_leave")
+            .flat_map(|node| node["content"]["rules"].as_array().unwrap().clone())
+            .filter(|rule| rule["output-tiene-derecho"] == "true")
+            .map(|rule| (
+                unquote(&rule["output-case"]),
+                unquote(&rule["output-description"]),
+                unquote(&rule["output-importe-mensual"]).parse().unwrap(),
+                Some(unquote(&rule["06347639-63c0-41bc-b2a0-09cea0cb669c"])).filter(|requirements| !requirements.is_empty()).into_iter().collect(),
+            ))
+            .collect();
+
+        assert_eq!(eligible_rows.len(), ELIGIBILITY_CASES.len());
+        for eligibility_case in ELIGIBILITY_CASES {
+            assert!(
+                eligible_rows.contains(&(
+                    eligibility_case.case.to_string(),
+                    eligibility_case.description.to_string(),
+                    eligibility_case.monthly_benefit,
+                    eligibility_case.additional_requirements.iter().map(|requirement| requirement.to_string()).collect(),
+                )),
+                "case {} is out of sync with the rule document", eligibility_case.case
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_case_descriptions_localized() {
+        let eligibility_engine = EligibilityEngine::new();
+        for scenario in crate::common::prompts::PROMPT_SCENARIOS {
+            let english = ELIGIBILITY_CASES.iter().find(|case| case.case == scenario.case).unwrap().description;
+            let spanish = i18n::message(Locale::Es, &format!("case_{}.description", scenario.case.to_lowercase())).unwrap();
+            assert_ne!(english, spanish);
+
+            for (locale, expected) in [(None, spanish), (Some("es"), spanish), (Some("en"), english), (Some("en-GB"), english)] {
+                let mut params = scenario.params();
+                params.locale = locale.map(str::to_string);
+                let response = eligibility_engine.evaluate(params).await.unwrap();
+                assert_eq!(response.output.description, expected, "case {} in {:?}", scenario.case, locale);
+            }
+        }
+
+        let mut params = direct_params("mother", "adoption");
+        params.locale = Some("fr".to_string());
+        let response = eligibility_engine.evaluate(params).await.unwrap();
+        assert_eq!(response.locale.as_deref(), Some("es"));
+        assert_eq!(response.output.additional_requirements, "En caso de acogimiento, su duración debe ser superior a un año");
+        assert!(response.output.warnings.iter().any(|warning| warning.starts_with("el idioma 'fr' no está soportado")));
+    }
+
+    #[tokio::test]
+    async fn test_accept_language_sets_default_locale() {
+        let eligibility_engine = EligibilityEngine::new();
+        let call = |locale: Option<&str>, accept_language: &str| {
+            let mut params = direct_params("mother", "adoption");
+            params.locale = locale.map(str::to_string);
+            let (parts, _) = axum::http::Request::builder()
+                .header("accept-language", accept_language)
+                .body(())
+                .unwrap()
+                .into_parts();
+            let mut extensions = Extensions::new();
+            extensions.insert(parts);
+            eligibility_engine.evaluate_unpaid_leave_eligibility(Parameters(params), extensions)
+        };
+        let locale = |call_result: CallToolResult| {
+            let response: UnpaidLeaveResponse = serde_json::from_str(&call_result.content[0].raw.as_text().unwrap().text).unwrap();
+            (response.locale.unwrap(), response.output.description)
+        };
+
+        let (code, description) = locale(call(None, "en-GB,en;q=0.9").await.unwrap());
+        assert_eq!((code.as_str(), description.as_str()), ("en", "Adoption or foster care"));
+        // An explicit locale wins over the header
+        assert_eq!(locale(call(Some("es"), "en").await.unwrap()).0, "es");
+        assert_eq!(locale(call(None, "fr").await.unwrap()).0, "es");
+    }
+
+    #[tokio::test]
+    async fn test_accept_language_applies_to_every_tool() {
+        let eligibility_engine = EligibilityEngine::new();
+        let extensions = || {
+            let (parts, _) = axum::http::Request::builder()
+                .header("accept-language", "en")
+                .body(())
+                .unwrap()
+                .into_parts();
+            let mut extensions = Extensions::new();
+            extensions.insert(parts);
+            extensions
+        };
+
+        let call_result = eligibility_engine
+            .describe_case(Parameters(DescribeCaseParams { case: "C".to_string(), locale: None }), extensions())
+            .await
+            .unwrap();
+        let description: CaseDescription = serde_json::from_str(&call_result.content[0].raw.as_text().unwrap().text).unwrap();
+        assert_eq!((description.locale.as_str(), description.description.as_str()), ("en", "Adoption or foster care"));
+
+        let params = direct_params("mom", "adoption");
+        let call_result = eligibility_engine.validate_unpaid_leave_input(Parameters(params), extensions()).await.unwrap();
+        let text = &call_result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("'mom' was interpreted as 'mother'"), "{}", text);
+    }
+
+    #[tokio::test]
+    async fn test_estimate_partial_input() {
+        let metrics = Arc::new(EligibilityMetrics::new());
+        let eligibility_engine = EligibilityEngine::new().with_metrics(metrics.clone());
+
+        // Only the family structure is unknown
+        let estimate = eligibility_engine
+            .estimate(UnpaidLeaveEstimateParams {
+                relationship: Some("mother".to_string()),
+                situation: Some("illness".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!((estimate.min_monthly_benefit, estimate.max_monthly_benefit), (725, 725));
+        assert_eq!(estimate.candidate_cases, vec!["A"]);
+        assert!(!estimate.may_be_ineligible);
+        assert_eq!(estimate.unknown_fields, vec!["is_single_parent", "total_children_after"]);
+        assert_eq!(estimate.combinations_evaluated, 2);
+
+        // A birth in an unknown family: case B from the third child, case E for single parents
+        let estimate = eligibility_engine
+            .estimate(UnpaidLeaveEstimateParams { situation: Some("birth".to_string()), ..Default::default() })
+            .await
+            .unwrap();
+        assert_eq!(estimate.candidate_cases, vec!["B", "E"]);
+        assert_eq!((estimate.min_monthly_benefit, estimate.max_monthly_benefit), (500, 500));
+        assert!(estimate.may_be_ineligible);
+        assert_eq!(estimate.combinations_evaluated, VALID_RELATIONSHIPS.len() * 2 * ESTIMATE_CHILDREN_COUNTS.len());
+
+        // Only the relationship known
+        let mother = || UnpaidLeaveEstimateParams { relationship: Some("mother".to_string()), ..Default::default() };
+        let estimate = eligibility_engine.estimate(mother()).await.unwrap();
+        assert_eq!(estimate.candidate_cases, vec!["A", "B", "C", "D", "E"]);
+        assert_eq!((estimate.min_monthly_benefit, estimate.max_monthly_benefit), (500, 725));
+        assert_eq!(estimate.unknown_fields.len(), 3);
+        assert_eq!(estimate.combinations_failed, 0);
+
+        // Known amounts adjustments apply to every outcome
+        let estimate = eligibility_engine
+            .estimate(UnpaidLeaveEstimateParams { leave_percentage: Some(50), ..mother() })
+            .await
+            .unwrap();
+        assert_eq!((estimate.min_monthly_benefit, estimate.max_monthly_benefit), (250, 363));
+
+        // Each estimate counts as a single request
+        #[cfg(feature = "metrics")]
+        assert_eq!(metrics.requests_total.with_label_values(&[ESTIMATE_TOOL_LABEL, "default"]).get(), 4.0);
+
+        // Nothing known at all is too many combinations
+        let error = eligibility_engine.estimate(UnpaidLeaveEstimateParams::default()).await.unwrap_err();
+        assert!(matches!(&error, UnpaidLeaveError::ValidationError(errors) if errors[0].message.starts_with("Input too open: 400 combinations")), "{:?}", error);
+        #[cfg(feature = "metrics")]
+        assert_eq!(metrics.errors_by_type_total.with_label_values(&["validation", ESTIMATE_TOOL_LABEL]).get(), 1.0);
+
+        let error = eligibility_engine
+            .estimate(UnpaidLeaveEstimateParams { total_children_after: Some(-1.0), ..Default::default() })
+            .await
+            .unwrap_err();
+        assert!(matches!(error, UnpaidLeaveError::ValidationError(_)));
+        let error = eligibility_engine
+            .estimate(UnpaidLeaveEstimateParams { leave_percentage: Some(0), ..Default::default() })
+            .await
+            .unwrap_err();
+        assert!(matches!(error, UnpaidLeaveError::ValidationError(_)));
+    }
+
+    #[tokio::test]
+    async fn test_estimate_skips_failed_combinations() {
+        let rulesets = function_rulesets(
+            "export const handler = async (input) => { \
+             if (input.input.situation === 'accident') { throw new Error('accident rules missing'); } \
+             return { output: { case: 'A', potentially_eligible: true, monthly_benefit: 725, description: 'Care' } }; };",
+        );
+        let limit = EvaluationLimit::new(1, Duration::from_millis(10));
+        let eligibility_engine = EligibilityEngine::new()
+            .with_result_cache(None)
+            .with_rulesets(rulesets)
+            .with_evaluation_limit(Some(limit.clone()));
+
+        let mother = |situation: Option<&str>| UnpaidLeaveEstimateParams {
+            relationship: Some("mother".to_string()),
+            situation: situation.map(str::to_string),
+            ..Default::default()
+        };
+        let estimate = eligibility_engine.estimate(mother(None)).await.unwrap();
+        assert_eq!(estimate.candidate_cases, vec!["A"]);
+        assert_eq!((estimate.combinations_evaluated, estimate.combinations_failed), (40, 2));
+
+        // Nothing left to estimate from
+        let error = eligibility_engine.estimate(mother(Some("accident"))).await.unwrap_err();
+        assert!(matches!(error, UnpaidLeaveError::ZenEngineError(_)), "{:?}", error);
+
+        // Completions wait for a slot of the evaluation limit like any evaluation
+        let _permit = limit.semaphore.clone().acquire_owned().await.unwrap();
+        let error = eligibility_engine.estimate(mother(Some("illness"))).await.unwrap_err();
+        assert!(matches!(error, UnpaidLeaveError::ServerBusy(1)), "{:?}", error);
+    }
+
+    #[tokio::test]
+    async fn test_describe_case_matches_evaluation() {
+        let eligibility_engine = EligibilityEngine::new();
+        for scenario in crate::common::prompts::PROMPT_SCENARIOS {
+            for locale in [None, Some("en")] {
+                let mut params = scenario.params();
+                params.locale = locale.map(str::to_string);
+                let output = eligibility_engine.evaluate(params).await.unwrap().output;
+                let description = eligibility_engine
+                    .case_description(DescribeCaseParams { case: scenario.case.to_lowercase(), locale: locale.map(str::to_string) })
+                    .unwrap();
+                assert_eq!(description.case, scenario.case);
+                assert_eq!(description.description, output.description, "case {} in {:?}", scenario.case, locale);
+                assert_eq!(description.monthly_benefit, output.monthly_benefit, "case {}", scenario.case);
+                assert_eq!(description.additional_requirements.join(" "), output.additional_requirements, "case {}", scenario.case);
+            }
+        }
+
+        let call_result = eligibility_engine
+            .describe_case(Parameters(DescribeCaseParams { case: "F".to_string(), locale: None }), Extensions::new())
+            .await
+            .unwrap();
+        assert_eq!(call_result.is_error, Some(true));
+        let text = &call_result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("/case"), "{}", text);
+    }
+
+    #[tokio::test]
+    async fn test_list_eligibility_options() {
+        let eligibility_engine = EligibilityEngine::new();
+        let call_result = eligibility_engine.list_eligibility_options().await.unwrap();
+        let json_text = &call_result.content[0].raw.as_text().unwrap().text;
+        let options: serde_json::Value = serde_json::from_str(json_text).unwrap();
+        assert_eq!(options["relationships"].as_array().unwrap().len(), VALID_RELATIONSHIPS.len());
+        assert_eq!(options["situations"].as_array().unwrap().len(), VALID_SITUATIONS.len());
+        assert_eq!(options["cases"][0]["case"], "A");
+        assert_eq!(options["cases"][0]["monthly_benefit"], 725);
+    }
+
+    #[tokio::test]
+    async fn test_compact_json_output() {
+        let batch = || UnpaidLeaveBatchParams {
+            inputs: vec![direct_params("mother", "illness"), direct_params("mother", "teleportation")],
+        };
+        let text = |call_result: CallToolResult| call_result.content[0].raw.as_text().unwrap().text.clone();
+
+        let pretty = text(EligibilityEngine::new().with_compact_json(false).evaluate_unpaid_leave_batch(Parameters(batch()), Extensions::new()).await.unwrap());
+        let compact = text(EligibilityEngine::new().with_compact_json(true).evaluate_unpaid_leave_batch(Parameters(batch()), Extensions::new()).await.unwrap());
+        assert!(pretty.contains('\n'));
+        assert!(!compact.contains('\n'));
+        assert!(compact.len() < pretty.len());
+
+        // Same values, including the error of the invalid input
+        let mut pretty: serde_json::Value = serde_json::from_str(&pretty).unwrap();
+        let mut compact: serde_json::Value = serde_json::from_str(&compact).unwrap();
+        // Only the generated evaluation ids differ
+        for items in [&mut pretty, &mut compact] {
+            items[0]["response"].as_object_mut().unwrap().remove("evaluation_id");
+        }
+        assert_eq!(pretty, compact);
+        assert!(compact[1]["validation_errors"].is_array());
+    }
+
+    #[test]
+    fn test_rules_resource() {
+        let eligibility_engine = EligibilityEngine::new();
+        let ResourceContents::TextResourceContents { uri, mime_type, text, .. } = eligibility_engine.rules_resource().unwrap() else {
+            panic!("rule document should be served as text");
+        };
+        assert_eq!(uri, RULES_RESOURCE_URI);
+        assert_eq!(mime_type.as_deref(), Some("application/json"));
+        let document: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(document["version"], RULES_VERSION);
+        assert_eq!(document["effective_from"], "2025-04-01");
+
+        // The served document is the one evaluations run on
+        let embedded: DecisionContent = serde_json::from_str(include_str!("unpaid-leave-assistance-2025.json")).unwrap();
+        let served: DecisionContent = serde_json::from_value(document["rules"].clone()).unwrap();
+        assert_eq!(served, embedded);
+        assert!(eligibility_engine.get_info().capabilities.resources.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_every_valid_value_is_accepted() {
+        let eligibility_engine = EligibilityEngine::new();
+        for situation in VALID_SITUATIONS {
+            let mut params = direct_params("mother", situation);
+            if CHILD_CARE_SITUATIONS.contains(situation) {
+                params.input.total_children_after = Some(2.0);
+            }
+            let result = eligibility_engine.evaluate(params).await;
+            assert!(result.is_ok(), "situation '{}' rejected: {:?}", situation, result.err());
+        }
+        for relationship in VALID_RELATIONSHIPS {
+            assert!(direct_params(relationship, "illness").validate(true).valid, "relationship '{}' rejected", relationship);
+        }
+
+        let schema = serde_json::to_value(schemars::schema_for!(UnpaidLeaveDirectParams)).unwrap();
+        let description = schema["properties"]["situation"]["description"].as_str().unwrap();
+        assert!(VALID_SITUATIONS.iter().all(|situation| description.contains(&format!("'{}'", situation))));
+        let instructions = eligibility_engine.get_info().instructions.unwrap();
+        assert!(VALID_RELATIONSHIPS.iter().all(|relationship| instructions.contains(&format!("'{}'", relationship))));
+
+        // The tool macro only takes a literal description, so the values are left to the parameter schema
+        let tool = EligibilityEngine::evaluate_unpaid_leave_eligibility_tool_attr();
+        let description = tool.description.unwrap();
+        assert!(!description.contains("'foster_parent'") && !description.contains("'multiple_birth'"), "{}", description);
+        let description = schema["properties"]["relationship"]["description"].as_str().unwrap();
+        assert!(VALID_RELATIONSHIPS.iter().all(|relationship| description.contains(&format!("'{}'", relationship))));
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_unpaid_leave_batch_partial_success() {
+        let eligibility_engine = EligibilityEngine::new();
+        let mut birth_params = direct_params("mother", "birth");
+        birth_params.input.total_children_after = Some(3.0);
+        let batch = UnpaidLeaveBatchParams {
+            inputs: vec![
+                direct_params("mother", "illness"),
+                direct_params("mother", "teleportation"), // Not valid
+                birth_params,
+            ],
+        };
+
+        let call_result = eligibility_engine.evaluate_unpaid_leave_batch(Parameters(batch), Extensions::new()).await.unwrap();
+        assert_eq!(call_result.is_error, Some(false));
+        let json_text = &call_result.content[0].raw.as_text().unwrap().text;
+        let items: Vec<UnpaidLeaveBatchItem> = serde_json::from_str(json_text).unwrap();
+        assert_eq!(items.len(), 3);
+
+        assert_eq!(items[0].index, 0);
+        assert_eq!(items[0].response.as_ref().unwrap().output.case, "A");
+
+        assert!(items[1].response.is_none());
+        assert!(items[1].error.as_ref().unwrap().contains("Validation errors"));
+        assert_eq!(items[1].validation_errors[0].path, "/input/situation");
+
+        assert_eq!(items[2].response.as_ref().unwrap().output.case, "B");
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_unpaid_leave_batch_too_large() {
+        let eligibility_engine = EligibilityEngine::new();
+        let batch = UnpaidLeaveBatchParams {
+            inputs: (0..=MAX_BATCH_SIZE).map(|_| direct_params("mother", "illness")).collect(),
+        };
+        let call_result = eligibility_engine.evaluate_unpaid_leave_batch(Parameters(batch), Extensions::new()).await.unwrap();
+        assert_eq!(call_result.is_error, Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_unpaid_leave_explain_returns_trace() {
+        let eligibility_engine = EligibilityEngine::new();
+
+        let response = eligibility_engine.evaluate(direct_params("mother", "illness")).await.unwrap();
+        assert!(response.trace.is_none());
+
+        let call_result = eligibility_engine
+            .evaluate_unpaid_leave_explain(Parameters(direct_params("mother", "illness")), Extensions::new())
+            .await
+            .unwrap();
+        let json_text = &call_result.content[0].raw.as_text().unwrap().text;
+        let response: UnpaidLeaveResponse = serde_json::from_str(json_text).unwrap();
+        assert_eq!(response.output.case, "A");
+
+        let trace = response.trace.unwrap();
+        let names: Vec<&str> = trace.iter().map(|step| step.name.as_str()).collect();
+        assert_eq!(names, vec!["request", "evaluate_relationship", "evaluate_unpaid_leave", "response"]);
+        let matched_rule = &trace[2].trace_data.as_ref().unwrap()["rule"];
+        assert_eq!(matched_rule["_id"], "regla-001");
+    }
+
+    #[cfg(feature = "metrics")]
+    #[tokio::test]
+    async fn test_case_metrics_incremented() {
+        let metrics = Arc::new(EligibilityMetrics::new());
+        let eligibility_engine = EligibilityEngine::new().with_metrics(metrics.clone());
+
+        eligibility_engine.evaluate(direct_params("mother", "illness")).await.unwrap();
+        eligibility_engine.evaluate(direct_params("brother", "illness")).await.unwrap();
+
+        assert_eq!(metrics.cases_total.with_label_values(&["A", "default"]).get(), 1.0);
+        assert_eq!(metrics.cases_total.with_label_values(&["none", "default"]).get(), 1.0);
+        assert_eq!(metrics.cases_total.with_label_values(&["B", "default"]).get(), 0.0);
+    }
+
+    #[cfg(feature = "metrics")]
+    #[tokio::test]
+    async fn test_validation_error_metric_by_type() {
+        let metrics = Arc::new(EligibilityMetrics::new());
+        let eligibility_engine = EligibilityEngine::new().with_metrics(metrics.clone());
+
+        let result = eligibility_engine.evaluate(direct_params("mother", "teleportation")).await;
+        assert!(matches!(result, Err(UnpaidLeaveError::ValidationError(_))));
+
+        let error_count = |error_type: &str| metrics.errors_by_type_total.with_label_values(&[error_type, DEFAULT_TOOL_LABEL]).get();
+        assert_eq!(error_count("validation"), 1.0);
+        assert_eq!(error_count("engine"), 0.0);
+        assert_eq!(error_count("serialization"), 0.0);
+        assert_eq!(error_count("join"), 0.0);
+        assert_eq!(metrics.errors_total.with_label_values(&[DEFAULT_TOOL_LABEL, "default"]).get(), 1.0);
+    }
+
+    #[cfg(feature = "metrics")]
+    #[tokio::test]
+    async fn test_injected_metrics_count_single_request() {
+        let metrics = Arc::new(EligibilityMetrics::new());
+        let eligibility_engine = EligibilityEngine::new().with_metrics(metrics.clone());
+
+        eligibility_engine
+            .evaluate_unpaid_leave_eligibility(Parameters(direct_params("mother", "illness")), Extensions::new())
+            .await
+            .unwrap();
+
+        let labels = [DEFAULT_TOOL_LABEL, "default"];
+        assert_eq!(metrics.requests_total.with_label_values(&labels).get(), 1.0);
+        assert_eq!(metrics.errors_total.with_label_values(&labels).get(), 0.0);
+        assert_eq!(metrics.request_duration.with_label_values(&labels).get_sample_count(), 1);
+        assert_eq!(metrics.active_requests.get(), 0.0);
+    }
+
+    #[cfg(feature = "metrics")]
+    #[tokio::test]
+    async fn test_health_check_passes_without_recording_metrics() {
+        let metrics = Arc::new(EligibilityMetrics::new());
+        let eligibility_engine = EligibilityEngine::new().with_metrics(metrics.clone());
+
+        assert_eq!(eligibility_engine.health_check().await, Ok(()));
+        assert!(!metrics.gather().unwrap().contains("eligibility_requests_total{"));
+    }
+
+    #[test]
+    fn test_rules_ready_after_load() {
+        assert_eq!(load_rules(), Ok(()));
+        assert!(rules_ready());
+    }
+
+    #[test]
+    fn test_version_info_matches_server_info() {
+        let info = EligibilityEngine::new().get_info();
+        let version_info = ServerVersionInfo::current();
+
+        assert_eq!(version_info.name, info.server_info.name);
+        assert_eq!(version_info.version, info.server_info.version);
+        // The version build_info exports
+        assert_eq!(version_info.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(version_info.rules_version, RULES_VERSION);
+        assert_eq!(version_info.git_sha, GIT_SHA);
+    }
+
+    #[tokio::test]
+    async fn test_response_includes_rules_version() {
+        let eligibility_engine = EligibilityEngine::new();
+        let response = eligibility_engine.evaluate(direct_params("mother", "illness")).await.unwrap();
+        assert_eq!(response.rules_version, RULES_VERSION);
+
+        // Responses recorded before the field existed still deserialize
+        let legacy: UnpaidLeaveResponse = serde_json::from_value(serde_json::json!({
+            "output": serde_json::to_value(&response.output).unwrap()
+        })).unwrap();
+        assert_eq!(legacy.rules_version, "");
+    }
+
+    #[test]
+    fn test_engines_share_the_decision() {
+        let eligibility_engine = EligibilityEngine::new();
+        let rulesets = eligibility_engine.rulesets().unwrap();
+        assert!(Arc::ptr_eq(&rulesets, &eligibility_engine.clone().rulesets().unwrap()));
+
+        // Separately created engines evaluate through the same parsed decision
+        let other = EligibilityEngine::new().rulesets().unwrap();
+        assert!(Arc::ptr_eq(&rulesets.select(None).unwrap().decision, &other.select(None).unwrap().decision));
+    }
+
+    #[tokio::test]
+    async fn test_broken_rule_document_rejected() {
+        let effective_from = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let path = std::env::temp_dir().join(format!("broken-rules-{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(&path, r#"{ "nodes": ["#).unwrap();
+        let error = ruleset_from_file(&path, effective_from).unwrap_err();
+        let _ = std::fs::remove_file(&path);
+        assert!(error.starts_with("Failed to parse rule document"), "{}", error);
+
+        // Well-formed, but case A was renamed: the canary catches it
+        let rules = include_str!("unpaid-leave-assistance-2025.json")
+            .replace(r#""output-case": "\"A\"""#, r#""output-case": "\"Z\"""#);
+        let ruleset = Ruleset::from_json("renamed-case", effective_from, &rules).unwrap();
+        let error = verify_rulesets(Arc::new(RulesetRegistry::new().with_ruleset(ruleset))).await.unwrap_err();
+        assert!(error.contains("returned case 'Z', expected 'A'"), "{}", error);
+
+        assert_eq!(verify_rules().await, Ok(()));
+    }
+
+    fn two_year_rulesets() -> Arc<RulesetRegistry> {
+        let rules = include_str!("unpaid-leave-assistance-2025.json");
+        let from_2024 = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let from_2025 = NaiveDate::from_ymd_opt(2025, 4, 1).unwrap();
+        // Added out of order on purpose, the registry keeps them sorted
+        Arc::new(RulesetRegistry::new()
+            .with_ruleset(Ruleset::from_json("rules-2025", from_2025, rules).unwrap())
+            .with_ruleset(Ruleset::from_json("rules-2024", from_2024, rules).unwrap()))
+    }
+
+    /// Tenants "acme", paying 800 € in case A, and "globex", paying 650 €
+    fn two_tenants() -> Arc<TenantRegistry> {
+        let rules = include_str!("unpaid-leave-assistance-2025.json");
+        let effective_from = NaiveDate::from_ymd_opt(2025, 4, 1).unwrap();
+        let tenant = |amount: &str| {
+            let rules = rules.replace(r#""output-importe-mensual": "725""#, &format!(r#""output-importe-mensual": "{}""#, amount));
+            Arc::new(RulesetRegistry::new().with_ruleset(Ruleset::from_json("tenant-rules", effective_from, &rules).unwrap()))
+        };
+        Arc::new(TenantRegistry::new().with_tenant("acme", tenant("800")).with_tenant("globex", tenant("650")))
+    }
+
+    #[tokio::test]
+    async fn test_tenant_rulesets() {
+        let metrics = Arc::new(EligibilityMetrics::new());
+        let eligibility_engine = EligibilityEngine::new().with_metrics(metrics.clone()).with_tenants(two_tenants());
+        let for_tenant = |tenant_id: Option<&str>| {
+            let mut params = direct_params("mother", "illness");
+            params.tenant_id = tenant_id.map(str::to_string);
+            params
+        };
+
+        for (tenant_id, expected_benefit) in [(Some("acme"), 800), (Some(" Globex "), 650), (None, 725)] {
+            let response = eligibility_engine.evaluate(for_tenant(tenant_id)).await.unwrap();
+            assert_eq!(response.output.case, "A");
+            assert_eq!(response.output.monthly_benefit, expected_benefit, "tenant {:?}", tenant_id);
+            assert_eq!(response.tenant_id.as_deref(), tenant_id.map(|tenant_id| tenant_id.trim().to_lowercase()).as_deref());
+        }
+        #[cfg(feature = "metrics")]
+        assert_eq!(metrics.cases_total.with_label_values(&["A", "acme"]).get(), 1.0);
+        #[cfg(feature = "metrics")]
+        assert_eq!(metrics.cases_total.with_label_values(&["A", "globex"]).get(), 1.0);
+        #[cfg(feature = "metrics")]
+        assert_eq!(metrics.cases_total.with_label_values(&["A", "default"]).get(), 1.0);
+
+        match eligibility_engine.evaluate(for_tenant(Some("initech"))).await {
+            Err(UnpaidLeaveError::ValidationError(errors)) => {
+                assert_eq!(errors[0].path, "/input/tenant_id");
+                assert!(errors[0].message.starts_with("organización 'initech' desconocida"), "{}", errors[0].message);
+                // Other tenants are not disclosed
+                assert!(!errors[0].message.contains("acme"));
+            }
+            other => panic!("Expected a validation error, got {:?}", other),
+        }
+        // Unknown tenant ids share one label
+        #[cfg(feature = "metrics")]
+        assert_eq!(metrics.errors_total.with_label_values(&[DEFAULT_TOOL_LABEL, UNKNOWN_TENANT_LABEL]).get(), 1.0);
+        #[cfg(feature = "metrics")]
+        assert_eq!(metrics.requests_total.with_label_values(&[DEFAULT_TOOL_LABEL, "globex"]).get(), 1.0);
+    }
+
+    /// Rule document granting case A to mothers and nothing to anyone else
+    fn minimal_rules() -> serde_json::Value {
+        let expression = |key: &str, value: &str| serde_json::json!({ "id": key, "key": key, "value": value });
+        serde_json::json!({
+            "nodes": [
+                { "id": "request", "name": "request", "type": "inputNode" },
+                {
+                    "id": "mothers",
+                    "name": "mothers",
+                    "type": "expressionNode",
+                    "content": { "expressions": [
+                        expression("output.case", "input.relationship == 'mother' ? 'A' : ''"),
+                        expression("output.potentially_eligible", "input.relationship == 'mother'"),
+                        expression("output.monthly_benefit", "input.relationship == 'mother' ? 900 : 0"),
+                        expression("output.description", "'Mothers only'"),
+                    ]}
+                },
+                { "id": "response", "name": "response", "type": "outputNode" }
+            ],
+            "edges": [
+                { "id": "e1", "sourceId": "request", "targetId": "mothers" },
+                { "id": "e2", "sourceId": "mothers", "targetId": "response" }
+            ]
+        })
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_against_rules() {
+        let metrics = Arc::new(EligibilityMetrics::new());
+        let eligibility_engine = EligibilityEngine::new().with_metrics(metrics.clone()).with_tenants(two_tenants());
+        let with_rules = |relationship: &str, rules: serde_json::Value| {
+            let mut input = direct_params(relationship, "illness");
+            input.tenant_id = Some("acme".to_string());
+            EvaluateWithRulesParams { input, rules }
+        };
+
+        let result = eligibility_engine.evaluate_against_rules(with_rules("mother", minimal_rules())).await;
+        assert_eq!(result.rule_errors, Vec::<String>::new());
+        let response = result.response.unwrap();
+        assert_eq!((response.output.case.as_str(), response.output.monthly_benefit), ("A", 900));
+        assert_eq!(response.rules_version, AD_HOC_RULES_VERSION);
+        assert_eq!(response.tenant_id, None);
+
+        let result = eligibility_engine.evaluate_against_rules(with_rules("brother", minimal_rules())).await;
+        assert!(!result.response.unwrap().output.potentially_eligible);
+
+        // The deployed rules are left untouched, and the evaluations are counted apart
+        let response = eligibility_engine.evaluate(direct_params("mother", "illness")).await.unwrap();
+        assert_eq!(response.output.monthly_benefit, 725);
+        #[cfg(feature = "metrics")]
+        assert_eq!(metrics.requests_total.with_label_values(&[DEFAULT_TOOL_LABEL, "default"]).get(), 1.0);
+        #[cfg(feature = "metrics")]
+        assert_eq!(metrics.requests_total.with_label_values(&["evaluate_with_rules", "default"]).get(), 2.0);
+
+        // Rules granting nothing fail the startup check but still evaluate
+        let mut rules = minimal_rules();
+        rules["nodes"][1]["content"]["expressions"][0]["value"] = serde_json::json!("''");
+        let result = eligibility_engine.evaluate_against_rules(with_rules("mother", rules)).await;
+        assert_eq!(result.response.unwrap().output.case, "");
+        assert_eq!(result.rule_errors, vec!["Canary evaluation returned case '', expected 'A'"]);
+
+        let result = eligibility_engine.evaluate_against_rules(with_rules("mother", serde_json::json!({ "nodes": "none" }))).await;
+        assert!(result.response.is_none());
+        assert!(result.rule_errors[0].starts_with("Invalid rule document"), "{:?}", result.rule_errors);
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_against_rules_is_limited() {
+        let limit = EvaluationLimit::new(1, Duration::from_millis(10));
+        let eligibility_engine = EligibilityEngine::new()
+            .with_evaluation_timeout(Duration::from_millis(50))
+            .with_retry_policy(RetryPolicy { max_retries: 0, initial_backoff: Duration::from_millis(1) })
+            .with_evaluation_limit(Some(limit.clone()));
+        let with_rules = |rules: serde_json::Value| EvaluateWithRulesParams { input: direct_params("mother", "illness"), rules };
+
+        // Hung rules fail both the canary and the evaluation once the timeout is reached
+        let slow_rules = serde_json::to_value(function_rules(&slow_source(500))).unwrap();
+        let result = eligibility_engine.evaluate_against_rules(with_rules(slow_rules)).await;
+        assert_eq!(result.rule_errors, vec!["Canary evaluation failed: Evaluation timed out after 50 ms"]);
+        assert_eq!(result.error.as_deref(), Some("Evaluation error: Evaluation timed out after 50 ms"));
+
+        // Waits for a slot like any evaluation
+        let _permit = limit.semaphore.clone().acquire_owned().await.unwrap();
+        let result = eligibility_engine.evaluate_against_rules(with_rules(minimal_rules())).await;
+        assert_eq!(result.error.as_deref(), Some("Evaluation error: Server busy: 1 evaluations already running, try again later"));
+    }
+
+    #[tokio::test]
+    async fn test_inconsistent_rule_output_flagged() {
+        // Everyone is marked potentially eligible, but only mothers get a case
+        let mut rules = minimal_rules();
+        rules["nodes"][1]["content"]["expressions"][1]["value"] = serde_json::json!("true");
+        let ruleset = Ruleset::from_content("inconsistent", NaiveDate::MIN, serde_json::from_value(rules).unwrap());
+        let metrics = Arc::new(EligibilityMetrics::new());
+        let eligibility_engine = EligibilityEngine::new()
+            .with_result_cache(None)
+            .with_metrics(metrics.clone())
+            .with_rulesets(Arc::new(RulesetRegistry::new().with_ruleset(ruleset)));
+
+        let response = eligibility_engine.evaluate(direct_params("mother", "illness")).await.unwrap();
+        assert!(response.output.warnings.is_empty(), "{:?}", response.output.warnings);
+
+        let response = eligibility_engine.evaluate(in_english(direct_params("brother", "illness"))).await.unwrap();
+        assert_eq!((response.output.case.as_str(), response.output.potentially_eligible), ("", true));
+        assert!(
+            response.output.warnings.contains(&"Inconsistent rule output: potentially eligible without a case".to_string()),
+            "{:?}",
+            response.output.warnings
+        );
+        #[cfg(feature = "metrics")]
+        assert_eq!(metrics.inconsistent_outputs_total.with_label_values(&["inconsistent"]).get(), 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_requirements_checklist() {
+        let eligibility_engine = EligibilityEngine::new();
+        let mut params = direct_params("mother", "illness");
+        params.locale = Some("en".to_string());
+        let response = eligibility_engine.evaluate(params).await.unwrap();
+        assert_eq!(
+            response.output.requirements,
+            vec![Requirement {
+                id: "hospitalized_continued_care".to_string(),
+                description: "The person must have been hospitalized and the care of the person must be continued".to_string(),
+                status: RequirementStatus::Unknown,
+            }]
+        );
+        // The free text carries the same requirement
+        assert_eq!(response.output.additional_requirements, response.output.requirements[0].description);
+
+        // Every catalog requirement has an identifier
+        for eligibility_case in ELIGIBILITY_CASES {
+            assert_eq!(eligibility_case.requirement_ids.len(), eligibility_case.additional_requirements.len(), "case {}", eligibility_case.case);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_next_steps() {
+        let eligibility_engine = EligibilityEngine::new();
+        let mut params = direct_params("mother", "illness");
+        params.locale = Some("en".to_string());
+        let response = eligibility_engine.evaluate(params).await.unwrap();
+        assert_eq!(
+            response.next_steps,
+            vec![
+                "Submit the assistance application within 15 days of the start of the leave",
+                "Provide a medical certificate of the hospitalization and of the need for continued care",
+            ]
+        );
+
+        // Not eligible, even with a case: what to do instead, in Spanish by default
+        let response = eligibility_engine.evaluate(birth_params("birth", false, 1.0)).await.unwrap();
+        assert_eq!((response.output.case.as_str(), response.output.potentially_eligible), ("B", false));
+        assert_eq!(
+            response.next_steps,
+            vec![
+                "Revise los requisitos no cumplidos y vuelva a evaluar si su situación cambia",
+                "Consulte con la administración si cree que su situación da derecho a la ayuda",
+            ]
+        );
+
+        // Every case has its steps, and every step a text in both catalogs
+        for eligibility_case in ELIGIBILITY_CASES {
+            let (_, keys) = NEXT_STEPS.iter().find(|(case, _)| *case == eligibility_case.case).unwrap();
+            for key in keys.iter().chain(NOT_ELIGIBLE_NEXT_STEPS) {
+                assert!(i18n::message(Locale::Es, key).is_some() && i18n::message(Locale::En, key).is_some(), "{}", key);
+            }
+        }
+    }
+
+    #[test]
+    fn test_case_eligibility_mismatch() {
+        let output = |case: &str, potentially_eligible: bool| UnpaidLeaveOutputForSchema {
+            description: String::new(),
+            monthly_benefit: 0,
+            additional_requirements: String::new(),
+            requirements: vec![],
+            case: case.to_string(),
+            potentially_eligible,
+            errors: vec![],
+            warnings: vec![],
+        };
+        assert_eq!(output("A", true).case_eligibility_mismatch(), None);
+        assert_eq!(output("", false).case_eligibility_mismatch(), None);
+        assert_eq!(output("B", false).case_eligibility_mismatch(), None);
+        assert!(output(" ", true).case_eligibility_mismatch().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_validate_decision_content() {
+        let parse = |rules: serde_json::Value| serde_json::from_value::<DecisionContent>(rules).unwrap();
+        let embedded = serde_json::from_str(include_str!("unpaid-leave-assistance-2025.json")).unwrap();
+        assert_eq!(validate_decision_content(&embedded), Ok(()));
+        assert_eq!(validate_decision_content(&parse(minimal_rules())), Ok(()));
+
+        // No output node, and an input schema that is not the one the server sends
+        let mut broken = minimal_rules();
+        broken["nodes"][0]["content"] = serde_json::json!({ "schema": "{\"properties\": {\"request\": {}}}" });
+        broken["nodes"].as_array_mut().unwrap().pop();
+        broken["edges"].as_array_mut().unwrap().pop();
+        assert_eq!(
+            validate_decision_content(&parse(broken)),
+            Err(vec![
+                "The schema of node 'request' does not declare the 'input' object".to_string(),
+                "The decision graph has no output node".to_string(),
+            ])
+        );
+
+        let mut cyclic = minimal_rules();
+        cyclic["edges"].as_array_mut().unwrap().push(serde_json::json!({ "id": "e3", "sourceId": "response", "targetId": "mothers" }));
+        let problems = validate_decision_content(&parse(cyclic)).unwrap_err();
+        assert_eq!(problems, vec!["The decision graph is not valid: Cyclic graph detected"]);
+    }
+
+    #[tokio::test]
+    async fn test_validate_decision_content_within_deadline() {
+        let deadline = Duration::from_millis(50);
+        assert_eq!(validate_decision_content_within(&function_rules(&slow_source(0)), deadline).await, Ok(()));
+
+        // A canary running past the deadline is reported, not waited for
+        let started = Instant::now();
+        let problems = validate_decision_content_within(&function_rules(&slow_source(500)), deadline).await.unwrap_err();
+        assert_eq!(problems, vec!["Canary evaluation failed: Evaluation timed out after 50 ms"]);
+        assert!(started.elapsed() < Duration::from_millis(400), "{:?}", started.elapsed());
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_with_rules_requires_api_key() {
+        let params = EvaluateWithRulesParams { input: direct_params("mother", "illness"), rules: minimal_rules() };
+        let result = EligibilityEngine::new()
+            .evaluate_with_rules(Parameters(params), Extensions::new())
+            .await
+            .unwrap();
+        assert_eq!(result.is_error, Some(true));
+    }
+
+    #[test]
+    fn test_rules_checksum_changes_with_rule_file() {
+        let dir = std::env::temp_dir().join(format!("eligibility-checksum-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let rules = include_str!("unpaid-leave-assistance-2025.json");
+        std::fs::write(dir.join("current.json"), rules).unwrap();
+        std::fs::write(dir.join("next.json"), rules.replace("725", "800")).unwrap();
+        let effective_from = NaiveDate::from_ymd_opt(2025, 4, 1).unwrap();
+
+        let current = ruleset_from_file(&dir.join("current.json"), effective_from).unwrap();
+        let next = ruleset_from_file(&dir.join("next.json"), effective_from).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(current.checksum().len(), 64);
+        assert_eq!(current.checksum(), Ruleset::from_json(RULES_VERSION, effective_from, rules).unwrap().checksum());
+        assert_ne!(current.checksum(), next.checksum());
+
+        // Loading other rules replaces the exported checksum
+        #[cfg(feature = "metrics")]
+        {
+            let metrics = EligibilityMetrics::new();
+            let series = |ruleset: &Ruleset| {
+                format!("eligibility_rules_info{{rules_checksum=\"{}\",rules_version=\"{}\",tenant=\"default\"}} 1", ruleset.checksum(), ruleset.version)
+            };
+            record_rules(&metrics, &RulesetRegistry::new().with_ruleset(current.clone()), &TenantRegistry::new());
+            assert!(metrics.gather().unwrap().contains(&series(&current)));
+            let tenants = TenantRegistry::new().with_tenant("acme", Arc::new(RulesetRegistry::new().with_ruleset(current.clone())));
+            record_rules(&metrics, &RulesetRegistry::new().with_ruleset(next.clone()), &tenants);
+            let output = metrics.gather().unwrap();
+            assert!(output.contains(&series(&next)));
+            assert!(!output.contains(&series(&current)));
+            assert!(output.contains(&format!("rules_checksum=\"{}\",rules_version=\"current\",tenant=\"acme\"}} 1", current.checksum())));
+        }
+    }
+
+    #[test]
+    fn test_tenants_from_dir() {
+        let dir = std::env::temp_dir().join(format!("eligibility-tenants-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let rules = include_str!("unpaid-leave-assistance-2025.json");
+        std::fs::write(dir.join("acme.json"), rules).unwrap();
+        std::fs::write(dir.join("globex.json"), rules).unwrap();
+        std::fs::write(dir.join("README.md"), "not a rule document").unwrap();
+        let effective_from = NaiveDate::from_ymd_opt(2025, 4, 1).unwrap();
+
+        let tenants = TenantRegistry::from_dir(&dir, effective_from).unwrap();
+        let tenant_ids: Vec<&str> = tenants.iter().map(|(tenant_id, _)| tenant_id).collect();
+        assert_eq!(tenant_ids, vec!["acme", "globex"]);
+        assert_eq!(tenants.get("acme").unwrap().select(None).unwrap().version, "acme");
+
+        std::fs::write(dir.join("Bad Tenant.json"), rules).unwrap();
+        assert!(TenantRegistry::from_dir(&dir, effective_from).unwrap_err().contains("Invalid tenant id 'Bad Tenant'"));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_effective_date_selects_ruleset() {
+        let eligibility_engine = EligibilityEngine::new().with_rulesets(two_year_rulesets());
+
+        for (effective_date, expected_version) in [
+            (Some("2024-06-15"), "rules-2024"),
+            (Some("2025-03-31"), "rules-2024"),
+            (Some("2025-04-01"), "rules-2025"),
+            (None, "rules-2025"),
+        ] {
+            let mut params = direct_params("mother", "illness");
+            params.effective_date = effective_date.map(str::to_string);
+            let response = eligibility_engine.evaluate(params).await.unwrap();
+            assert_eq!(response.rules_version, expected_version, "effective_date {:?}", effective_date);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_effective_date_without_ruleset_is_rejected() {
+        let eligibility_engine = EligibilityEngine::new().with_rulesets(two_year_rulesets());
+
+        for effective_date in ["2023-12-31", "31/12/2023", "2024-13-01"] {
+            let mut params = direct_params("mother", "illness");
+            params.effective_date = Some(effective_date.to_string());
+            match eligibility_engine.evaluate(params).await {
+                Err(UnpaidLeaveError::ValidationError(errors)) => {
+                    assert_eq!(errors[0].path, "/input/effective_date");
+                },
+                other => panic!("Expected a validation error for {}, got {:?}", effective_date, other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_unpaid_leave_diff_one_more_child() {
+        let eligibility_engine = EligibilityEngine::new();
+        let mut before = direct_params("mother", "birth");
+        before.input.total_children_after = Some(2.0);
+        let mut after = direct_params("mother", "birth");
+        after.input.total_children_after = Some(3.0);
+
+        let call_result = eligibility_engine
+            .evaluate_unpaid_leave_diff(Parameters(UnpaidLeaveDiffParams { before, after }), Extensions::new())
+            .await
+            .unwrap();
+        assert_eq!(call_result.is_error, Some(false));
+        let json_text = &call_result.content[0].raw.as_text().unwrap().text;
+        let diff: UnpaidLeaveDiffResponse = serde_json::from_str(json_text).unwrap();
+
+        // Both land on the third-child row, only the third child makes it eligible
+        assert_eq!(diff.after.output.case, "B");
+        assert_eq!(diff.changes.case, None);
+        assert_eq!(diff.changes.monthly_benefit, Some(FieldChange { before: 0, after: 500 }));
+        assert_eq!(diff.changes.potentially_eligible, Some(FieldChange { before: false, after: true }));
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_unpaid_leave_diff_identical_scenarios() {
+        let eligibility_engine = EligibilityEngine::new();
+        let before = eligibility_engine.evaluate(direct_params("mother", "illness")).await.unwrap();
+        let after = eligibility_engine.evaluate(direct_params("father", "accident")).await.unwrap();
+        let changes = UnpaidLeaveDiff::new(&before, &after);
+        assert_eq!(changes.case, None);
+        assert_eq!(changes.monthly_benefit, None);
+        assert_eq!(changes.potentially_eligible, None);
+    }
+
+    #[test]
+    fn test_output_errors_accepts_legacy_key() {
+        let output = |errors_key: &str| serde_json::json!({
+            "description": "Not eligible",
+            "monthly_benefit": 0,
+            "case": "NONE",
+            "potentially_eligible": false,
+            errors_key: ["Missing requirement"],
+        });
+
+        for key in ["errores", "errors"] {
+            let parsed: UnpaidLeaveOutputForSchema = serde_json::from_value(output(key)).unwrap();
+            assert_eq!(parsed.errors, vec!["Missing requirement".to_string()]);
+
+            let serialized = serde_json::to_value(&parsed).unwrap();
+            assert_eq!(serialized["errors"], serde_json::json!(["Missing requirement"]));
+            assert!(serialized.get("errores").is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_configured_benefit_amount_applied() {
+        let config = EligibilityConfig::from_toml("[benefit_amounts]\nA = 800\nB = 550\n").unwrap();
+        let eligibility_engine = EligibilityEngine::new().with_config(Arc::new(config));
+
+        let response = eligibility_engine.evaluate(direct_params("mother", "illness")).await.unwrap();
+        assert_eq!(response.output.case, "A");
+        assert_eq!(response.output.monthly_benefit, 800);
+        assert_eq!(response.monthly_benefit_money.unwrap().formatted, "800,00 €");
+
+        // Not eligible results keep their 0€ amount
+        let mut params = direct_params("mother", "birth");
+        params.input.total_children_after = Some(2.0);
+        let response = eligibility_engine.evaluate(params).await.unwrap();
+        assert!(!response.output.potentially_eligible);
+        assert_eq!(response.output.monthly_benefit, 0);
+
+        // The instructions given to the model quote the configured amounts
+        let instructions = eligibility_engine.get_info().instructions.unwrap();
+        assert!(instructions.contains("A) Sick/injured family care (800,00 €/month)"), "{}", instructions);
+        assert!(instructions.contains("B) Third child+ with newborn (550,00 €/month)"), "{}", instructions);
+        assert!(instructions.contains("C) Adoption/foster care (500,00 €/month)"), "{}", instructions);
+
+        let config = EligibilityConfig::from_toml("currency = \"USD\"\nlocale = \"en-US\"\n").unwrap();
+        let instructions = EligibilityEngine::new().with_config(Arc::new(config)).get_info().instructions.unwrap();
+        assert!(instructions.contains("A) Sick/injured family care ($725.00/month)"), "{}", instructions);
+        let description = EligibilityEngine::evaluate_unpaid_leave_eligibility_tool_attr().description.unwrap();
+        assert!(!description.contains('€'), "{}", description);
+    }
+
+    #[tokio::test]
+    async fn test_household_income_threshold() {
+        let config = EligibilityConfig::from_toml("[income_thresholds]\nA = 30000\n").unwrap();
+        let eligibility_engine = EligibilityEngine::new().with_config(Arc::new(config));
+        let with_income = |household_income: Option<f64>| {
+            let mut params = direct_params("mother", "illness");
+            params.household_income = household_income;
+            params
+        };
+
+        let response = eligibility_engine.evaluate(with_income(Some(29999.0))).await.unwrap();
+        assert!(response.output.potentially_eligible);
+        assert_eq!(response.output.monthly_benefit, 725);
+
+        let response = eligibility_engine.evaluate(with_income(Some(42000.0))).await.unwrap();
+        assert!(!response.output.potentially_eligible);
+        assert_eq!(response.output.monthly_benefit, 0);
+        assert_eq!(response.output.case, "A");
+        assert!(response.output.errors.iter().any(|error| error.contains("42.000,00 € superan el límite de 30.000,00 € del supuesto A")));
+
+        // No income, no means test
+        let response = eligibility_engine.evaluate(with_income(None)).await.unwrap();
+        assert!(response.output.potentially_eligible);
+
+        assert!(matches!(
+            eligibility_engine.evaluate(with_income(Some(-1.0))).await,
+            Err(UnpaidLeaveError::ValidationError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_per_member_income_threshold() {
+        let config = EligibilityConfig::from_toml("[per_member_income_thresholds]\nE = 10000\n").unwrap();
+        let eligibility_engine = EligibilityEngine::new().with_config(Arc::new(config));
+        let with_household = |household_income: Option<f64>, household_size: Option<u32>| {
+            let mut params = direct_params("mother", "birth");
+            params.input.is_single_parent = true;
+            params.household_income = household_income;
+            params.household_size = household_size;
+            params
         };
 
-        // Use tokio::task::spawn_blocking for operations that are not Send
-        let result = tokio::task::spawn_blocking(move || {
-            // Create a tokio runtime for the async operation inside the blocking block
-            let rt = tokio::runtime::Runtime::new().unwrap();
-            rt.block_on(async move {
-                let engine = UnpaidLeaveDecisionEngine::new();
-                engine.evaluate_unpaid_leave(&request).await
-            })
-        }).await;
-        
-        match result {
-            Ok(eval_result) => {
-                match eval_result {
-                    Ok(response) => {
-                        // Serialize the response to JSON and return as success
-                        match serde_json::to_string_pretty(&response) {
-                            Ok(json_str) => Ok(CallToolResult::success(vec![Content::text(json_str)])),
-                            Err(e) => {
-                                increment_errors();
-                                Ok(CallToolResult::error(vec![Content::text(format!(
-                                    "Error serializing response: {}", e
-                                ))]))
-                            }
-                        }
-                    },
-                    Err(e) => {
-                        increment_errors();
-                        let error_msg = match e {
-                            UnpaidLeaveError::ValidationError(validation_errors) => {
-                                let mut msg = "Validation errors:\n".to_string();
-                                for error in validation_errors {
-                                    msg.push_str(&format!("  - Field '{}': {}\n", error.path, error.message));
-                                }
-                                msg
-                            },
-                            _ => format!("Evaluation error: {}", e)
-                        };
-                        Ok(CallToolResult::error(vec![Content::text(error_msg)]))
-                    }
-                }
-            },
-            Err(join_error) => {
-                increment_errors();
-                Ok(CallToolResult::error(vec![Content::text(format!(
-                    "Internal error: {}", join_error
-                ))]))
-            }
+        // 9.000 € per member
+        let response = eligibility_engine.evaluate(with_household(Some(36000.0), Some(4))).await.unwrap();
+        assert_eq!(response.output.case, "E");
+        assert!(response.output.potentially_eligible);
+        let eligible_benefit = response.output.monthly_benefit;
+        assert!(eligible_benefit > 0);
+
+        // 12.000 € per member
+        let response = eligibility_engine.evaluate(with_household(Some(36000.0), Some(3))).await.unwrap();
+        assert!(!response.output.potentially_eligible);
+        assert_eq!(response.output.monthly_benefit, 0);
+        assert_eq!(
+            response.explanation.unwrap(),
+            "Los ingresos por miembro del hogar de 12.000,00 € (36.000,00 € entre 3 miembros) superan el límite de 10.000,00 € del supuesto E."
+        );
+
+        // Exactly at the limit is still eligible
+        let response = eligibility_engine.evaluate(with_household(Some(20000.0), Some(2))).await.unwrap();
+        assert!(response.output.potentially_eligible);
+
+        // Without both values, or for other cases, nothing changes
+        let response = eligibility_engine.evaluate(with_household(Some(36000.0), None)).await.unwrap();
+        assert_eq!(response.output.monthly_benefit, eligible_benefit);
+        let response = eligibility_engine.evaluate(with_household(None, Some(1))).await.unwrap();
+        assert_eq!(response.output.monthly_benefit, eligible_benefit);
+        assert!(response.output.warnings.iter().any(|warning| warning == "household_size no tiene efecto sin household_income"));
+        let mut params = direct_params("mother", "illness");
+        params.household_income = Some(90000.0);
+        params.household_size = Some(1);
+        assert!(eligibility_engine.evaluate(params).await.unwrap().output.potentially_eligible);
+
+        match eligibility_engine.evaluate(with_household(Some(36000.0), Some(0))).await {
+            Err(UnpaidLeaveError::ValidationError(errors)) => assert_eq!(errors[0].path, "/input/household_size"),
+            other => panic!("Expected a validation error, got {:?}", other),
         }
     }
-}
 
-#[tool_handler]
-impl ServerHandler for EligibilityEngine {
-    fn get_info(&self) -> ServerInfo {
-        // Read basic information from .env file (replaced by sync script during release)
-        let name = "eligibility-engine-mcp-rs".to_string();
-        let version = "1.1.3".to_string();
-        let title = "Eligibility Engine MCP Server".to_string();
+    #[tokio::test]
+    async fn test_max_monthly_benefit() {
+        let config = EligibilityConfig::from_toml("max_monthly_benefit = 800\n[disability_uplift]\namount = 200\n").unwrap();
+        let eligibility_engine = EligibilityEngine::new().with_config(Arc::new(config));
+        let mut params = in_english(direct_params("mother", "illness"));
+        params.has_disability = true;
 
-        ServerInfo {
-            instructions: Some(
-                "Eligibility Engine for leave assistance according to legal regulations. \
-                 \n\n** IMPORTANT TOOL USAGE INSTRUCTIONS **\
-                 \n\n1. ALWAYS use the EXACT values specified for each parameter, CASE SENSITIVE\
-                 \n\n2. For relationship, use ONLY: 'father', 'mother', 'parent', 'son', 'daughter', 'spouse', 'partner', 'husband', 'wife', 'foster_parent'\
-                 \n\n3. For situation, use ONLY: 'birth', 'adoption', 'foster_care', 'multiple_birth', 'multiple_adoption', 'multiple_foster_care', 'illness', 'accident'. If number of children is greater than one, USE 'multiple_birth' or 'multiple_adoption' or 'multiple_foster_care'\
-                 \n\n4. For is_single_parent, use ONLY: true (for single-parent families) or false (for families with both parents). If no information regarding the family structure use always false\
-                 \n\n5. For total_children_after, use whole numbers (eg: 1, 2, 3, 4, 5). ONLY if situation is 'birth' or 'adoption' or 'foster_care' or 'multiple_birth' or 'multiple_adoption' or 'multiple_foster_care'
-                 \n\nCORRECT USAGE EXAMPLES:\
-                 \n• Single father with baby: relationship='father', situation='birth', is_single_parent=true, total_children_after=1\
-                 \n• Son caring for sick father: relationship='father', situation='illness', is_single_parent=false, total_children_after=0\
-                 \n• Family with third child: relationship='mother', situation='birth', is_single_parent=false, total_children_after=3\
-                 \n• Family with multiple children: relationship='mother', situation='multiple_birth', is_single_parent=false, total_children_after=3\
-                 \n• Family with multiple children: relationship='mother', situation='multiple_adoption', is_single_parent=false, total_children_after=3\
-                 \n• Family with multiple children: relationship='mother', situation='multiple_foster_care', is_single_parent=false, total_children_after=3\
-                 \n\nCASES EVALUATED:\
-                 \nA) Sick/injured family care (725€/month)\
-                 \nB) Third child+ with newborn (500€/month)\
-                 \nC) Adoption/foster care (500€/month)\
-                 \nD) Multiple births/adoptions (500€/month)\
-                 \nE) Single-parent families (500€/month)".into()
-            ),
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
-            server_info: rmcp::model::Implementation {
-                name: name,
-                version: version, 
-                title: Some(title), 
-                icons: None, 
-                website_url: None 
-            },
-            ..Default::default()
-        }
+        // 725 € plus the 200 € uplift exceeds the cap
+        let response = eligibility_engine.evaluate(params).await.unwrap();
+        assert_eq!(response.output.monthly_benefit, 800);
+        assert_eq!(response.monthly_benefit_money.unwrap().amount, 800.0);
+        assert!(response.output.warnings.iter().any(|warning| warning == "monthly_benefit of 925,00 € capped at the maximum of 800,00 €"));
+
+        // Below the cap nothing changes
+        let response = eligibility_engine.evaluate(direct_params("mother", "illness")).await.unwrap();
+        assert_eq!(response.output.monthly_benefit, 725);
+        assert!(!response.output.warnings.iter().any(|warning| warning.contains("capped")));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[tokio::test]
+    async fn test_not_eligible_explanation() {
+        let eligibility_engine = EligibilityEngine::new();
+
+        let response = eligibility_engine.evaluate(in_english(direct_params("brother", "illness"))).await.unwrap();
+        assert!(!response.output.potentially_eligible);
+        let explanation = response.explanation.unwrap();
+        assert!(explanation.starts_with("'brother' is not a first-degree relationship"), "{}", explanation);
+        let explanation = eligibility_engine.evaluate(direct_params("brother", "illness")).await.unwrap().explanation.unwrap();
+        assert!(explanation.starts_with("'brother' no es un parentesco de primer grado"), "{}", explanation);
+
+        let mut params = in_english(direct_params("mother", "birth"));
+        params.input.total_children_after = Some(2.0);
+        let explanation = eligibility_engine.evaluate(params).await.unwrap().explanation.unwrap();
+        assert!(explanation.contains("only qualifies from the third child, but total_children_after is 2."), "{}", explanation);
+
+        let config = EligibilityConfig::from_toml("[income_thresholds]\nA = 30000\n").unwrap();
+        let mut params = in_english(direct_params("mother", "illness"));
+        params.household_income = Some(42000.0);
+        let response = eligibility_engine.with_config(Arc::new(config)).evaluate(params).await.unwrap();
+        assert_eq!(response.explanation.unwrap(), "Household income of 42.000,00 € exceeds the 30.000,00 € limit for case A.");
+
+        // Eligible results need no explanation
+        let response = EligibilityEngine::new().evaluate(direct_params("mother", "illness")).await.unwrap();
+        assert_eq!(response.explanation, None);
+    }
 
     #[tokio::test]
-    async fn test_eligibility_engine_case_a() {
+    async fn test_regional_benefit_amounts() {
+        let config = EligibilityConfig::from_toml(
+            "[regions.catalonia.multipliers]\nA = 1.2\n\n[regions.navarre.benefit_amounts]\nA = 900\nB = 650\n",
+        )
+        .unwrap();
+        let eligibility_engine = EligibilityEngine::new().with_config(Arc::new(config));
+        let in_region = |region: Option<&str>| {
+            let mut params = in_english(direct_params("mother", "illness"));
+            params.region = region.map(str::to_string);
+            params
+        };
+
+        let response = eligibility_engine.evaluate(in_region(Some("Catalonia"))).await.unwrap();
+        assert_eq!(response.output.monthly_benefit, 870);
+        assert_eq!(response.region.as_deref(), Some("catalonia"));
+
+        let mut params = in_region(Some("navarre"));
+        params.leave_percentage = Some(50);
+        let response = eligibility_engine.evaluate(params).await.unwrap();
+        assert_eq!(response.output.monthly_benefit, 450);
+        assert_eq!(response.region.as_deref(), Some("navarre"));
+
+        let response = eligibility_engine.evaluate(in_region(Some("atlantis"))).await.unwrap();
+        assert_eq!(response.output.monthly_benefit, 725);
+        assert_eq!(response.region.as_deref(), Some("national"));
+        assert!(response.output.warnings.iter().any(|warning| warning.contains("Unknown region 'atlantis'")));
+
+        let response = eligibility_engine.evaluate(in_region(None)).await.unwrap();
+        assert_eq!(response.output.monthly_benefit, 725);
+        assert_eq!(response.region, None);
+    }
+
+    #[tokio::test]
+    async fn test_disability_uplift() {
+        let config = EligibilityConfig::from_toml("[disability_uplift]\npercentage = 10.0\namount = 25\n").unwrap();
+        let eligibility_engine = EligibilityEngine::new().with_config(Arc::new(config));
+        let with_disability = |situation: &str, has_disability: bool| {
+            let mut params = in_english(direct_params("mother", situation));
+            params.has_disability = has_disability;
+            params
+        };
+
+        // 725 * 1.1 = 797.5, rounded to 798, plus 25
+        let response = eligibility_engine.evaluate(with_disability("illness", true)).await.unwrap();
+        assert_eq!(response.output.monthly_benefit, 823);
+        assert!(response.output.warnings.iter().any(|warning| warning == "Disability uplift applied: monthly_benefit raised from 725,00 € to 823,00 €"));
+
+        // Applied to the full-time amount, then scaled
+        let mut params = with_disability("accident", true);
+        params.leave_percentage = Some(50);
+        assert_eq!(eligibility_engine.evaluate(params).await.unwrap().output.monthly_benefit, 412);
+
+        let response = eligibility_engine.evaluate(with_disability("illness", false)).await.unwrap();
+        assert_eq!(response.output.monthly_benefit, 725);
+        assert!(!response.output.warnings.iter().any(|warning| warning.contains("isability")));
+
+        let response = eligibility_engine.evaluate(with_disability("adoption", true)).await.unwrap();
+        assert_eq!(response.output.monthly_benefit, 500);
+        assert!(response.output.warnings.iter().any(|warning| warning.starts_with("has_disability only raises the benefit")));
+
+        let response = EligibilityEngine::new().evaluate(with_disability("illness", true)).await.unwrap();
+        assert_eq!(response.output.monthly_benefit, 725);
+        assert!(response.output.warnings.iter().any(|warning| warning.starts_with("has_disability has no effect")));
+    }
+
+    #[tokio::test]
+    async fn test_total_benefit_prorated_over_leave() {
         let eligibility_engine = EligibilityEngine::new();
-        let direct_params = UnpaidLeaveDirectParams {
-            relationship: "mother".to_string(),
-            situation: "illness".to_string(),
-            is_single_parent: false,
-            total_children_after: None,
+
+        let mut params = direct_params("mother", "illness");
+        params.leave_months = Some(2);
+        params.leave_days = Some(10);
+        let response = eligibility_engine.evaluate(params).await.unwrap();
+        assert_eq!(response.leave_duration_days, Some(70));
+        // 725€ * 70 / 30 = 1691.666...
+        let total = response.total_benefit.unwrap();
+        assert_eq!(total.amount, 1691.67);
+        assert_eq!(total.formatted, "1.691,67 €");
+
+        let response = eligibility_engine.evaluate(direct_params("mother", "illness")).await.unwrap();
+        assert_eq!(response.leave_duration_days, None);
+        assert!(response.total_benefit.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_leave_period() {
+        let eligibility_engine = EligibilityEngine::new();
+        let with_period = |start: &str, end: &str| {
+            let mut params: UnpaidLeaveDirectParams = serde_json::from_value(serde_json::json!({
+                "relationship": "mother",
+                "situation": "illness",
+                "is_single_parent": false,
+                "leave_start": start,
+                "leave_end": end,
+                "locale": "en",
+            }))
+            .unwrap();
+            params.normalize();
+            params
         };
-        
-        let result = eligibility_engine.evaluate_unpaid_leave_eligibility(Parameters(direct_params)).await;
-        match result {
-            Ok(call_result) => {
-                // Check if it's a success result
-                println!("Resultado Supuesto A: {:?}", call_result);
-                let content = call_result.content;
-                assert!(!content.is_empty(), "Content should not be empty");
-                let raw_content = &content[0].raw;
-                // Extract the text from the raw content, it has to be a string
-                let json_text = &raw_content.as_text().unwrap().text;
-                let response: UnpaidLeaveResponse = serde_json::from_str(json_text).unwrap();
-                assert_eq!(response.output.case, "A");
-                assert!(response.output.potentially_eligible);
-                assert_eq!(response.output.monthly_benefit, 725);
-                
-            },
-            Err(e) => panic!("Error inesperado: {}", e),
+
+        // Both days included: March has 31 days, 31 + 30 = 61 days
+        let response = eligibility_engine.evaluate(with_period("2025-03-01", " 2025-04-30 ")).await.unwrap();
+        assert_eq!(response.leave_duration_days, Some(61));
+        // 725€ * 61 / 30 = 1474.166...
+        assert_eq!(response.total_benefit.unwrap().amount, 1474.17);
+        assert_eq!(with_period("2025-03-01", "2025-03-01").leave_duration_days(), Some(1));
+
+        let path = |params: UnpaidLeaveDirectParams| params.leave_period_error().map(|error| error.path);
+        assert_eq!(path(with_period("2025-03-01", "2025-02-28")), Some("/input/leave_end".to_string()));
+        assert_eq!(path(with_period("2025-01-01", "2028-01-01")), None);
+        assert_eq!(path(with_period("2025-01-01", "2028-01-02")), Some("/input/leave_end".to_string()));
+        let mut overlapping = with_period("2025-03-01", "2025-03-31");
+        overlapping.leave_days = Some(10);
+        assert_eq!(path(overlapping.clone()), Some("/input/leave_start".to_string()));
+        let mut open_ended = with_period("2025-03-01", "2025-03-31");
+        open_ended.leave_end = None;
+        assert_eq!(path(open_ended), Some("/input/leave_end".to_string()));
+
+        let result = eligibility_engine.evaluate(with_period("2025-03-01", "2020-03-01")).await;
+        assert!(matches!(result, Err(UnpaidLeaveError::ValidationError(errors)) if errors[0].message.contains("is before leave_start")));
+        assert!(!overlapping.validate(false).valid);
+    }
+
+    #[test]
+    fn test_parse_date_formats() {
+        let march_first = NaiveDate::from_ymd_opt(2025, 3, 1);
+        for accepted in ["2025-03-01", " 2025/03/01 ", "01/03/2025", "1/3/2025", "01-03-2025", "2025-03-01T10:30:00Z", "2025-03-01T23:30:00-05:00", "2025-03-01T10:30:00"] {
+            assert_eq!(parse_date(accepted), march_first, "{}", accepted);
+        }
+        for rejected in ["", "2025-02-30", "03/31/2025", "2025-3", "1 March 2025", "20250301", "2025-03-01 garbage"] {
+            assert_eq!(parse_date(rejected), None, "{}", rejected);
         }
     }
 
-    #[tokio::test] 
-    async fn test_eligibility_engine_case_e() {
-        let eligibility_engine = EligibilityEngine::new();
-        let direct_params = UnpaidLeaveDirectParams {
-            relationship: "mother".to_string(),
-            situation: "birth".to_string(),
-            is_single_parent: true,
-            total_children_after: Some(1.0),
+    #[test]
+    fn test_malformed_leave_dates_rejected() {
+        let parse = |leave_start: serde_json::Value| {
+            serde_json::from_value::<UnpaidLeaveDirectParams>(serde_json::json!({
+                "relationship": "mother",
+                "situation": "illness",
+                "is_single_parent": false,
+                "leave_start": leave_start,
+            }))
         };
-        
-        let result = eligibility_engine.evaluate_unpaid_leave_eligibility(Parameters(direct_params)).await;
-        match result {
-            Ok(call_result) => {
-                println!("Resultado Supuesto E: {:?}", call_result);
-            },
-            Err(e) => panic!("Error inesperado: {}", e),
+        assert_eq!(parse(serde_json::json!("2025-03-01")).unwrap().leave_start, NaiveDate::from_ymd_opt(2025, 3, 1));
+        assert_eq!(parse(serde_json::Value::Null).unwrap().leave_start, None);
+        for malformed in [serde_json::json!("2025-02-30"), serde_json::json!("soon"), serde_json::json!(20250301)] {
+            let error = parse(malformed.clone()).unwrap_err().to_string();
+            assert!(error.contains("expected"), "{}: {}", malformed, error);
         }
+
+        // Dates serialize back to the format they are read in
+        let params = parse(serde_json::json!("2025-03-01")).unwrap();
+        assert_eq!(serde_json::to_value(&params).unwrap()["leave_start"], "2025-03-01");
     }
 
     #[tokio::test]
-    async fn test_eligibility_engine_case_b() {
+    async fn test_include_input_echoes_normalized_input() {
         let eligibility_engine = EligibilityEngine::new();
-        let direct_params = UnpaidLeaveDirectParams {
-            relationship: "mother".to_string(),
-            situation: "birth".to_string(),
-            is_single_parent: false,
-            total_children_after: Some(3.0), // Third child
+
+        let mut params = direct_params(" Mom ", "Sickness");
+        params.include_input = Some(true);
+        let response = eligibility_engine.evaluate(params.clone()).await.unwrap();
+        let input = response.input.unwrap();
+        assert_eq!((input.relationship.as_str(), input.situation.as_str()), ("mother", "illness"));
+
+        params.include_input = Some(false);
+        assert_eq!(eligibility_engine.evaluate(params.clone()).await.unwrap().input, None);
+
+        // Omitted, the embedded rule document does not echo the input
+        params.include_input = None;
+        assert_eq!(eligibility_engine.evaluate(params).await.unwrap().input, None);
+    }
+
+    #[tokio::test]
+    async fn test_relationship_valid_is_always_set() {
+        let eligibility_engine = EligibilityEngine::new();
+        let valid = eligibility_engine.evaluate(direct_params("mother", "illness")).await.unwrap();
+        assert_eq!(valid.relationship_valid, Some(true));
+        // Checked after normalization
+        let synonym = eligibility_engine.evaluate(direct_params("Dad", "illness")).await.unwrap();
+        assert_eq!(synonym.relationship_valid, Some(true));
+        let invalid = eligibility_engine.evaluate(direct_params("brother", "illness")).await.unwrap();
+        assert_eq!(invalid.relationship_valid, Some(false));
+    }
+
+    #[tokio::test]
+    async fn test_leave_percentage_scales_benefit() {
+        let eligibility_engine = EligibilityEngine::new();
+
+        for (percentage, expected) in [(Some(50), 363), (Some(100), 725), (Some(1), 7), (None, 725)] {
+            let mut params = direct_params("mother", "illness");
+            params.leave_percentage = percentage;
+            let response = eligibility_engine.evaluate(params).await.unwrap();
+            assert_eq!(response.output.monthly_benefit, expected, "leave_percentage {:?}", percentage);
+        }
+
+        for percentage in [0, 101] {
+            let mut params = direct_params("mother", "illness");
+            params.leave_percentage = Some(percentage);
+            match eligibility_engine.evaluate(params).await {
+                Err(UnpaidLeaveError::ValidationError(errors)) => assert_eq!(errors[0].path, "/input/leave_percentage"),
+                other => panic!("Expected a validation error for {}, got {:?}", percentage, other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_evaluation_limit_enforced() {
+        let metrics = Arc::new(EligibilityMetrics::new());
+        let limit = EvaluationLimit::new(1, Duration::from_millis(10));
+        let eligibility_engine = EligibilityEngine::new()
+            .with_metrics(metrics.clone())
+            .with_evaluation_limit(Some(limit.clone()));
+
+        // Hold the only slot, as a long-running evaluation would
+        let permit = limit.acquire().await.unwrap();
+        match eligibility_engine.evaluate(direct_params("mother", "illness")).await {
+            Err(UnpaidLeaveError::ServerBusy(1)) => {},
+            other => panic!("Expected the server to be busy, got {:?}", other),
+        }
+        #[cfg(feature = "metrics")]
+        assert_eq!(metrics.rejected_total.get(), 1.0);
+        #[cfg(feature = "metrics")]
+        assert_eq!(metrics.errors_by_type_total.with_label_values(&["busy", DEFAULT_TOOL_LABEL]).get(), 1.0);
+
+        drop(permit);
+        assert!(eligibility_engine.evaluate(direct_params("mother", "illness")).await.is_ok());
+        #[cfg(feature = "metrics")]
+        assert_eq!(metrics.rejected_total.get(), 1.0);
+    }
+
+    /// Rules answering case A after `millis`, as a hung rule would
+    fn slow_rulesets(millis: u64) -> Arc<RulesetRegistry> {
+        function_rulesets(&slow_source(millis))
+    }
+
+    /// Function node source answering case A after `millis`
+    fn slow_source(millis: u64) -> String {
+        format!(
+            "export const handler = async (input) => {{ await console.sleep({}); \
+             return {{ output: {{ case: 'A', potentially_eligible: true, monthly_benefit: 725, description: 'Slow' }} }}; }};",
+            millis
+        )
+    }
+
+    /// Rules made of a single function node running `source`
+    fn function_rulesets(source: &str) -> Arc<RulesetRegistry> {
+        Arc::new(RulesetRegistry::new().with_ruleset(Ruleset::from_content("slow-rules", NaiveDate::MIN, function_rules(source))))
+    }
+
+    /// Rule document made of a single function node running `source`
+    fn function_rules(source: &str) -> DecisionContent {
+        let rules = serde_json::json!({
+            "nodes": [
+                { "id": "request", "name": "request", "type": "inputNode" },
+                { "id": "slow", "name": "slow", "type": "functionNode", "content": { "source": source } },
+                { "id": "response", "name": "response", "type": "outputNode" }
+            ],
+            "edges": [
+                { "id": "e1", "sourceId": "request", "targetId": "slow" },
+                { "id": "e2", "sourceId": "slow", "targetId": "response" }
+            ]
+        });
+        serde_json::from_value(rules).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_timed_out_evaluation_keeps_its_slot() {
+        let limit = EvaluationLimit::new(1, Duration::from_millis(10));
+        let eligibility_engine = EligibilityEngine::new()
+            .with_result_cache(None)
+            .with_rulesets(slow_rulesets(1000))
+            .with_evaluation_timeout(Duration::from_millis(50))
+            .with_evaluation_limit(Some(limit.clone()));
+
+        match eligibility_engine.evaluate(direct_params("mother", "illness")).await {
+            Err(UnpaidLeaveError::Timeout(_)) => {},
+            other => panic!("Expected a timeout, got {:?}", other),
+        }
+        // The rule is still running, so there is no slot for another evaluation
+        match eligibility_engine.evaluate(direct_params("mother", "illness")).await {
+            Err(UnpaidLeaveError::ServerBusy(1)) => {},
+            other => panic!("Expected the server to be busy, got {:?}", other),
+        }
+
+        // Released once the rule is done
+        let permit = tokio::time::timeout(Duration::from_secs(5), limit.semaphore.clone().acquire_owned()).await;
+        assert!(permit.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_slow_evaluation_times_out() {
+        let slow_evaluation = async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            "never returned"
         };
-        
-        let result = eligibility_engine.evaluate_unpaid_leave_eligibility(Parameters(direct_params)).await;
-        match result {
-            Ok(call_result) => {
-                println!("Resultado Supuesto B: {:?}", call_result);
-            },
-            Err(e) => panic!("Error inesperado: {}", e),
+        match with_deadline(Duration::from_millis(10), slow_evaluation).await {
+            Err(UnpaidLeaveError::Timeout(deadline)) => assert_eq!(deadline, Duration::from_millis(10)),
+            other => panic!("Expected a timeout, got {:?}", other),
         }
+        assert_eq!(with_deadline(Duration::from_secs(1), async { 42 }).await.unwrap(), 42);
+    }
+
+    fn transient_error() -> UnpaidLeaveError {
+        UnpaidLeaveError::InternalError("cannot create evaluation runtime: Resource temporarily unavailable".to_string())
+    }
+
+    #[test]
+    fn test_transient_errors() {
+        assert!(transient_error().is_transient());
+        assert!(UnpaidLeaveError::Timeout(Duration::from_millis(10)).is_transient());
+        let loader_error = zen_engine::loader::LoaderError::Internal {
+            key: "unpaid-leave".to_string(),
+            source: anyhow::anyhow!("resource temporarily unavailable"),
+        };
+        assert!(!UnpaidLeaveError::ZenEngineError(EvaluationError::LoaderError(Box::new(loader_error))).is_transient());
+        assert!(!UnpaidLeaveError::ZenEngineError(EvaluationError::DepthLimitExceeded).is_transient());
+        assert!(!UnpaidLeaveError::ValidationError(vec![]).is_transient());
+        assert!(!UnpaidLeaveError::Panicked.is_transient());
+        assert!(!UnpaidLeaveError::ServerBusy(1).is_transient());
+
+        let policy = RetryPolicy { max_retries: 3, initial_backoff: Duration::from_millis(10) };
+        let backoffs: Vec<u128> = (0..3).map(|retry| policy.backoff(retry).as_millis()).collect();
+        assert_eq!(backoffs, vec![10, 20, 40]);
     }
 
     #[tokio::test]
-    async fn test_eligibility_engine_validation_error() {
+    async fn test_transient_error_retried() {
+        let metrics = EligibilityMetrics::new();
+        let policy = RetryPolicy { max_retries: 2, initial_backoff: Duration::from_millis(1) };
+
+        // Flaky evaluation succeeding on the second attempt
+        let mut attempts = 0;
+        let result = policy
+            .run(&metrics, || {
+                attempts += 1;
+                let result = if attempts == 1 { Err(transient_error()) } else { Ok("case A") };
+                async move { result }
+            })
+            .await;
+        assert_eq!(result.unwrap(), "case A");
+        assert_eq!(attempts, 2);
+        #[cfg(feature = "metrics")]
+        assert_eq!(metrics.retries_total.get(), 1.0);
+
+        // Validation errors are never retried
+        let mut attempts = 0;
+        let result: Result<(), _> = policy
+            .run(&metrics, || {
+                attempts += 1;
+                async { Err(UnpaidLeaveError::ValidationError(vec![])) }
+            })
+            .await;
+        assert!(matches!(result, Err(UnpaidLeaveError::ValidationError(_))));
+        assert_eq!(attempts, 1);
+
+        // Transient errors give up once the retries are exhausted
+        let mut attempts = 0;
+        let result: Result<(), _> = policy
+            .run(&metrics, || {
+                attempts += 1;
+                async { Err(transient_error()) }
+            })
+            .await;
+        assert!(result.unwrap_err().is_transient());
+        assert_eq!(attempts, 3);
+        #[cfg(feature = "metrics")]
+        assert_eq!(metrics.retries_total.get(), 3.0);
+    }
+
+    #[tokio::test]
+    async fn test_timed_out_evaluation_retried() {
+        let metrics = Arc::new(EligibilityMetrics::new());
+        let eligibility_engine = EligibilityEngine::new()
+            .with_metrics(metrics.clone())
+            .with_result_cache(None)
+            .with_rulesets(slow_rulesets(200))
+            .with_evaluation_timeout(Duration::from_millis(50))
+            .with_retry_policy(RetryPolicy { max_retries: 2, initial_backoff: Duration::from_millis(1) });
+
+        // Every attempt gets its own deadline
+        let started = Instant::now();
+        match eligibility_engine.evaluate(direct_params("mother", "illness")).await {
+            Err(UnpaidLeaveError::Timeout(deadline)) => assert_eq!(deadline, Duration::from_millis(50)),
+            other => panic!("Expected a timeout, got {:?}", other),
+        }
+        assert!(started.elapsed() >= Duration::from_millis(150), "{:?}", started.elapsed());
+        #[cfg(feature = "metrics")]
+        assert_eq!(metrics.retries_total.get(), 2.0);
+
+        // Evaluations finishing in time are not retried
+        let eligibility_engine = eligibility_engine.with_evaluation_timeout(Duration::from_secs(5));
+        assert_eq!(eligibility_engine.evaluate(direct_params("mother", "illness")).await.unwrap().output.case, "A");
+        #[cfg(feature = "metrics")]
+        assert_eq!(metrics.retries_total.get(), 2.0);
+    }
+
+    #[test]
+    fn test_rules_unavailable_error_type() {
+        let error = UnpaidLeaveError::RulesUnavailable("Failed to parse rule document".to_string());
+        assert_eq!(error.error_type(), "rules");
+        assert!(!error.is_transient());
+        assert_eq!(format_evaluation_error(&error), "Rules unavailable: Failed to parse rule document");
+    }
+
+    #[test]
+    fn test_timeout_error_type() {
+        let error = UnpaidLeaveError::Timeout(Duration::from_millis(250));
+        assert_eq!(error.error_type(), "timeout");
+        assert_eq!(error.to_string(), "Evaluation timed out after 250 ms");
+    }
+
+    #[tokio::test]
+    async fn test_panicking_evaluation_returns_clean_error() {
+        let metrics = Arc::new(EligibilityMetrics::new());
+        let eligibility_engine = EligibilityEngine::new().with_metrics(metrics.clone());
+
+        let join_error = tokio::task::spawn_blocking(|| panic!("decision table exploded")).await.unwrap_err();
+        let error = eligibility_engine.join_error(join_error);
+        assert!(matches!(error, UnpaidLeaveError::Panicked));
+        assert_eq!(error.error_type(), "panic");
+        assert_eq!(format_evaluation_error(&error), "Internal error: the evaluation failed unexpectedly");
+        #[cfg(feature = "metrics")]
+        assert_eq!(metrics.panics_total.get(), 1.0);
+
+        // The engine keeps serving after the panic
+        let response = eligibility_engine.evaluate(direct_params("mother", "illness")).await.unwrap();
+        assert_eq!(response.output.case, "A");
+    }
+
+    #[tokio::test]
+    async fn test_result_cache_reuses_engine_result() {
+        let metrics = Arc::new(EligibilityMetrics::new());
+        let cache = ResultCache::new(NonZeroUsize::new(8).unwrap());
+        let eligibility_engine = EligibilityEngine::new()
+            .with_metrics(metrics.clone())
+            .with_result_cache(Some(cache.clone()));
+
+        let first = eligibility_engine.evaluate(direct_params("mother", "illness")).await.unwrap();
+        let mut second = eligibility_engine.evaluate(direct_params("mother", "illness")).await.unwrap();
+        // Same result, under its own evaluation id
+        assert_ne!(first.evaluation_id, second.evaluation_id);
+        second.evaluation_id = first.evaluation_id.clone();
+        assert_eq!(first, second);
+        #[cfg(feature = "metrics")]
+        assert_eq!(metrics.cache_misses_total.get(), 1.0);
+        #[cfg(feature = "metrics")]
+        assert_eq!(metrics.cache_hits_total.get(), 1.0);
+        assert_eq!(cache.len(), 1);
+
+        // Fields applied after the engine are still honoured on a hit
+        let mut params = direct_params("mother", "illness");
+        params.leave_percentage = Some(50);
+        let response = eligibility_engine.evaluate(params).await.unwrap();
+        assert_eq!(response.output.monthly_benefit, 363);
+        #[cfg(feature = "metrics")]
+        assert_eq!(metrics.cache_hits_total.get(), 2.0);
+
+        // Explained results carry a trace, so they are cached separately
+        #[cfg(feature = "metrics")]
+        metrics.reset();
+        let explained = eligibility_engine.evaluate_explained(direct_params("mother", "illness")).await.unwrap();
+        assert!(explained.trace.is_some());
+        #[cfg(feature = "metrics")]
+        assert_eq!((metrics.cache_misses_total.get(), metrics.cache_hits_total.get()), (1.0, 0.0));
+    }
+
+    #[tokio::test]
+    async fn test_cached_result_served_on_engine_error() {
+        let metrics = Arc::new(EligibilityMetrics::new());
+        let cache = ResultCache::new(NonZeroUsize::new(8).unwrap());
+        let eligibility_engine = EligibilityEngine::new()
+            .with_metrics(metrics.clone())
+            .with_result_cache(Some(cache.clone()));
+        let warm = eligibility_engine.evaluate(direct_params("mother", "illness")).await.unwrap();
+
+        // Rules reloaded with a defect, the engine no longer answers in time
+        let eligibility_engine = eligibility_engine
+            .with_rulesets(slow_rulesets(1000))
+            .with_evaluation_timeout(Duration::from_millis(50));
+        assert_eq!(cache.len(), 0);
+
+        let response = eligibility_engine.evaluate(in_english(direct_params("mother", "illness"))).await.unwrap();
+        assert_eq!(response.output.case, warm.output.case);
+        assert_eq!(response.output.monthly_benefit, warm.output.monthly_benefit);
+        // Made under the rules served before
+        assert_eq!(response.rules_version, warm.rules_version);
+        assert_ne!(response.evaluation_id, warm.evaluation_id);
+        assert!(response.output.warnings.iter().any(|warning| {
+            warning.starts_with("Evaluation timed out after 50 ms") && warning.contains("served from the cache")
+        }));
+        #[cfg(feature = "metrics")]
+        assert_eq!(metrics.served_from_cache_on_error_total.get(), 1.0);
+
+        // Never evaluated before, the error stands
+        match eligibility_engine.evaluate(direct_params("mother", "birth")).await {
+            Err(UnpaidLeaveError::Timeout(_)) => {},
+            other => panic!("Expected a timeout, got {:?}", other),
+        }
+        // Results are not shared between tenants
+        let mut params = direct_params("mother", "illness");
+        params.tenant_id = Some("acme".to_string());
+        let tenant_engine = eligibility_engine.clone().with_tenants(Arc::new(
+            TenantRegistry::new().with_tenant("acme", slow_rulesets(1000)),
+        ));
+        assert!(matches!(tenant_engine.evaluate(params).await, Err(UnpaidLeaveError::Timeout(_))));
+
+        // Only engine failures fall back to the cache
+        let (request, _) = direct_params("mother", "illness").into_request();
+        let cache_key = ResultCacheKey::new(eligibility_engine.rulesets().unwrap().select(None).unwrap(), None, &request.input, false);
+        let error = eligibility_engine.cached_on_error(&cache_key, UnpaidLeaveError::ServerBusy(1), Locale::En).unwrap_err();
+        assert!(matches!(error, UnpaidLeaveError::ServerBusy(1)));
+
+        // Nor without a cache
+        let uncached = eligibility_engine.with_result_cache(None);
+        assert!(matches!(uncached.evaluate(direct_params("mother", "illness")).await, Err(UnpaidLeaveError::Timeout(_))));
+        #[cfg(feature = "metrics")]
+        assert_eq!(metrics.served_from_cache_on_error_total.get(), 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_result_cache_cleared_when_rules_change() {
+        let cache = ResultCache::new(NonZeroUsize::new(8).unwrap());
+        let eligibility_engine = EligibilityEngine::new().with_result_cache(Some(cache.clone()));
+        eligibility_engine.evaluate(direct_params("mother", "illness")).await.unwrap();
+        assert_eq!(cache.len(), 1);
+
+        let eligibility_engine = eligibility_engine.with_rulesets(two_year_rulesets());
+        assert_eq!(cache.len(), 0);
+        let response = eligibility_engine.evaluate(direct_params("mother", "illness")).await.unwrap();
+        assert_eq!(response.rules_version, "rules-2025");
+    }
+
+    #[test]
+    fn test_request_id_from_headers() {
+        let mut headers = axum::http::HeaderMap::new();
+        assert_eq!(request_id_from_headers(&headers), None);
+
+        headers.insert(REQUEST_ID_HEADER, " req-42 ".parse().unwrap());
+        assert_eq!(request_id_from_headers(&headers), Some("req-42".to_string()));
+
+        headers.insert(REQUEST_ID_HEADER, "x".repeat(MAX_REQUEST_ID_LEN + 1).parse().unwrap());
+        assert_eq!(request_id_from_headers(&headers), None);
+    }
+
+    #[tokio::test]
+    async fn test_evaluation_id_is_unique() {
         let eligibility_engine = EligibilityEngine::new();
-        let direct_params = UnpaidLeaveDirectParams {
-            relationship: "brother".to_string(), // Not valid
-            situation: "birth".to_string(),
-            is_single_parent: false,
-            total_children_after: None,
-        };
-        
-        let result = eligibility_engine.evaluate_unpaid_leave_eligibility(Parameters(direct_params)).await;
-        match result {
-            Ok(call_result) => {
-                // Should handle validation errors appropriately
-                println!("Validation result: {:?}", call_result);
-            },
-            Err(e) => panic!("Error inesperado: {}", e),
+        let mut ids = Vec::new();
+        for _ in 0..2 {
+            let call_result = eligibility_engine
+                .evaluate_unpaid_leave_eligibility(Parameters(direct_params("mother", "illness")), Extensions::new())
+                .await
+                .unwrap();
+            let response: serde_json::Value = serde_json::from_str(&call_result.content[0].raw.as_text().unwrap().text).unwrap();
+            let id = response["evaluation_id"].as_str().expect("evaluation_id should be present").to_string();
+            assert!(uuid::Uuid::parse_str(&id).is_ok());
+            ids.push(id);
+        }
+        assert_ne!(ids[0], ids[1]);
+    }
+
+    mod deserializers {
+        use super::*;
+        use proptest::prelude::*;
+        use serde_json::Value;
+
+        fn as_bool(value: Value) -> Result<bool, serde_json::Error> {
+            deserialize_bool_or_string(value)
+        }
+
+        fn as_f64(value: Value) -> Result<Option<f64>, serde_json::Error> {
+            deserialize_f64_or_string(value)
+        }
+
+        proptest! {
+            #[test]
+            fn bool_round_trips(value: bool) {
+                prop_assert_eq!(as_bool(Value::Bool(value)).unwrap(), value);
+                prop_assert_eq!(as_bool(Value::from(value.to_string())).unwrap(), value);
+                prop_assert_eq!(as_bool(Value::from(value.to_string().to_uppercase())).unwrap(), value);
+            }
+
+            #[test]
+            fn bool_rejects_other_strings(text in "[a-zA-Z0-9 ]{0,12}") {
+                prop_assume!(!matches!(text.to_lowercase().as_str(), "true" | "false"));
+                prop_assert!(as_bool(Value::from(text)).is_err());
+            }
+
+            #[test]
+            fn bool_rejects_numbers(value: i64) {
+                prop_assert!(as_bool(Value::from(value)).is_err());
+            }
+
+            #[test]
+            fn integer_round_trips(value: i64) {
+                prop_assert_eq!(as_f64(Value::from(value)).unwrap(), Some(value as f64));
+                prop_assert_eq!(as_f64(Value::from(value.to_string())).unwrap(), Some(value as f64));
+            }
+
+            #[test]
+            fn leading_zeros_are_ignored(value in 0u32..1_000_000, width in 1usize..12) {
+                let padded = format!("{:0width$}", value, width = width);
+                prop_assert_eq!(as_f64(Value::from(padded)).unwrap(), Some(value as f64));
+            }
+
+            #[test]
+            fn float_round_trips(value in proptest::num::f64::NORMAL | proptest::num::f64::ZERO) {
+                prop_assert_eq!(as_f64(Value::from(value)).unwrap(), Some(value));
+                prop_assert_eq!(as_f64(Value::from(value.to_string())).unwrap(), Some(value));
+                // Scientific notation, e.g. "1.5e3"
+                prop_assert_eq!(as_f64(Value::from(format!("{:e}", value))).unwrap(), Some(value));
+            }
+
+            #[test]
+            fn locale_separators_are_rejected(integer in 0u32..1000, fraction in 0u32..1000) {
+                let decimal_comma = format!("{},{}", integer, fraction);
+                let grouped = format!("1.{:03},{}", integer, fraction);
+                prop_assert!(as_f64(Value::from(decimal_comma)).is_err());
+                prop_assert!(as_f64(Value::from(grouped)).is_err());
+            }
+
+            #[test]
+            fn garbage_strings_are_rejected(text in "[a-zA-Z0-9 ]{0,6}[,;/#][a-zA-Z0-9 ]{0,6}") {
+                prop_assert!(as_f64(Value::from(text)).is_err());
+            }
+        }
+
+        #[test]
+        fn null_is_none() {
+            assert_eq!(as_f64(Value::Null).unwrap(), None);
+            assert!(as_f64(Value::from("")).is_err());
         }
     }
 }
@@ -0,0 +1,212 @@
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use std::sync::Arc;
+
+use super::eligibility_engine::{UnpaidLeaveInput, UnpaidLeaveResponse};
+use super::metrics::EligibilityMetrics;
+
+/// Comma-separated Kafka bootstrap servers evaluation events are produced to, e.g. `kafka-1:9092,kafka-2:9092`.
+/// Events are not produced when unset
+pub const KAFKA_BROKERS_ENV: &str = "KAFKA_BROKERS";
+/// Topic evaluation events are produced to, required with `KAFKA_BROKERS`
+pub const KAFKA_TOPIC_ENV: &str = "KAFKA_TOPIC";
+
+/// Event stream configured at startup from `KAFKA_BROKERS` and `KAFKA_TOPIC`
+static EVENTS: OnceCell<EvaluationEvents> = OnceCell::new();
+
+/// Connects the Kafka producer when `KAFKA_BROKERS` is set. Fails when the topic is missing,
+/// the producer cannot be created or the server was built without the `kafka` feature
+pub fn init() -> anyhow::Result<()> {
+    let brokers = match std::env::var(KAFKA_BROKERS_ENV) {
+        Ok(brokers) if !brokers.trim().is_empty() => brokers,
+        _ => return Ok(()),
+    };
+    let topic = std::env::var(KAFKA_TOPIC_ENV)
+        .ok()
+        .filter(|topic| !topic.trim().is_empty())
+        .ok_or_else(|| anyhow::anyhow!("{} is set but {} is not", KAFKA_BROKERS_ENV, KAFKA_TOPIC_ENV))?;
+    let events = EvaluationEvents::new(kafka::producer(brokers.trim(), topic.trim())?);
+    tracing::info!("Producing evaluation events to Kafka topic {} on {}", topic.trim(), brokers.trim());
+    let _ = EVENTS.set(events);
+    Ok(())
+}
+
+/// Event stream configured by `init`, `None` when Kafka is not configured
+pub fn current() -> Option<EvaluationEvents> {
+    EVENTS.get().cloned()
+}
+
+/// Queues messages for a broker without waiting for them to be delivered
+pub trait EventProducer: Send + Sync + std::fmt::Debug {
+    /// Queues `payload` under `key`. Fails right away when the message cannot be queued,
+    /// failures after queueing are the producer's to report
+    fn send(&self, key: &str, payload: &[u8]) -> Result<(), String>;
+}
+
+/// One evaluation as produced to the event stream
+#[derive(Debug, Serialize)]
+pub struct EvaluationEvent<'a> {
+    pub evaluation_id: Option<&'a str>,
+    pub evaluated_at: String,
+    pub input: &'a UnpaidLeaveInput,
+    pub case: &'a str,
+    pub potentially_eligible: bool,
+    pub monthly_benefit: i32,
+    pub rules_version: &'a str,
+    pub tenant_id: Option<&'a str>,
+}
+
+/// Produces an event for every evaluation
+#[derive(Debug, Clone)]
+pub struct EvaluationEvents {
+    producer: Arc<dyn EventProducer>,
+}
+
+impl EvaluationEvents {
+    pub fn new(producer: Arc<dyn EventProducer>) -> Self {
+        Self { producer }
+    }
+
+    /// Queues the event of the evaluation of `input`, keyed by evaluation id. A message that cannot
+    /// be queued is logged and counted in `metrics`, it never affects the evaluation itself
+    pub fn publish(&self, input: &UnpaidLeaveInput, response: &UnpaidLeaveResponse, metrics: &EligibilityMetrics) {
+        let event = EvaluationEvent {
+            evaluation_id: response.evaluation_id.as_deref(),
+            evaluated_at: chrono::Utc::now().to_rfc3339(),
+            input,
+            case: &response.output.case,
+            potentially_eligible: response.output.potentially_eligible,
+            monthly_benefit: response.output.monthly_benefit,
+            rules_version: &response.rules_version,
+            tenant_id: response.tenant_id.as_deref(),
+        };
+        let payload = serde_json::to_vec(&event).unwrap_or_default();
+        if let Err(e) = self.producer.send(event.evaluation_id.unwrap_or_default(), &payload) {
+            tracing::warn!(error = %e, "Failed to queue the evaluation event");
+            metrics.increment_event_failures();
+        }
+    }
+}
+
+#[cfg(feature = "kafka")]
+mod kafka {
+    use rdkafka::ClientContext;
+    use rdkafka::config::ClientConfig;
+    use rdkafka::producer::{BaseRecord, DeliveryResult, ProducerContext, ThreadedProducer};
+    use std::sync::Arc;
+
+    use super::EventProducer;
+    use crate::common::metrics;
+
+    /// Counts the messages librdkafka gave up delivering
+    struct DeliveryContext;
+
+    impl ClientContext for DeliveryContext {}
+
+    impl ProducerContext for DeliveryContext {
+        type DeliveryOpaque = ();
+
+        fn delivery(&self, delivery_result: &DeliveryResult<'_>, _: ()) {
+            if let Err((e, _)) = delivery_result {
+                tracing::warn!(error = %e, "Failed to deliver the evaluation event to Kafka");
+                metrics::METRICS.increment_event_failures();
+            }
+        }
+    }
+
+    /// Producer buffering messages in librdkafka's queue, polled by a background thread
+    struct KafkaProducer {
+        producer: ThreadedProducer<DeliveryContext>,
+        topic: String,
+    }
+
+    impl std::fmt::Debug for KafkaProducer {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("KafkaProducer").field("topic", &self.topic).finish_non_exhaustive()
+        }
+    }
+
+    impl EventProducer for KafkaProducer {
+        fn send(&self, key: &str, payload: &[u8]) -> Result<(), String> {
+            self.producer
+                .send(BaseRecord::<str, [u8]>::to(&self.topic).key(key).payload(payload))
+                .map_err(|(e, _)| e.to_string())
+        }
+    }
+
+    pub fn producer(brokers: &str, topic: &str) -> anyhow::Result<Arc<dyn EventProducer>> {
+        let producer = ClientConfig::new()
+            .set("bootstrap.servers", brokers)
+            .set("message.timeout.ms", "30000")
+            .create_with_context(DeliveryContext)
+            .map_err(|e| anyhow::anyhow!("Cannot create the Kafka producer for {}: {}", brokers, e))?;
+        Ok(Arc::new(KafkaProducer { producer, topic: topic.to_string() }))
+    }
+}
+
+#[cfg(not(feature = "kafka"))]
+mod kafka {
+    use std::sync::Arc;
+
+    use super::{EventProducer, KAFKA_BROKERS_ENV};
+
+    pub fn producer(_brokers: &str, _topic: &str) -> anyhow::Result<Arc<dyn EventProducer>> {
+        anyhow::bail!("{} is set but this server was built without the kafka feature", KAFKA_BROKERS_ENV)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::eligibility_engine::{direct_params, EligibilityEngine};
+    use std::sync::Mutex;
+
+    /// Producer keeping the queued messages, or refusing them all like a full queue
+    #[derive(Debug, Default)]
+    struct MockProducer {
+        full: bool,
+        messages: Mutex<Vec<(String, serde_json::Value)>>,
+    }
+
+    impl EventProducer for MockProducer {
+        fn send(&self, key: &str, payload: &[u8]) -> Result<(), String> {
+            if self.full {
+                return Err("Local: Queue full".to_string());
+            }
+            self.messages.lock().unwrap().push((key.to_string(), serde_json::from_slice(payload).unwrap()));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_evaluations_are_published() {
+        let producer = Arc::new(MockProducer::default());
+        let eligibility_engine = EligibilityEngine::new().with_events(Some(EvaluationEvents::new(producer.clone())));
+
+        let response = eligibility_engine.evaluate(direct_params("Mother", "illness")).await.unwrap();
+        let messages = producer.messages.lock().unwrap();
+        assert_eq!(messages.len(), 1);
+        let (key, event) = &messages[0];
+        assert_eq!(Some(key.as_str()), response.evaluation_id.as_deref());
+        assert_eq!(event["evaluation_id"], serde_json::json!(response.evaluation_id));
+        assert_eq!(event["case"], "A");
+        assert_eq!(event["monthly_benefit"], 725);
+        // The normalized input, as in the audit log
+        assert_eq!(event["input"]["relationship"], "mother");
+    }
+
+    #[tokio::test]
+    async fn test_queue_failures_are_counted() {
+        let metrics = Arc::new(EligibilityMetrics::new());
+        let producer = Arc::new(MockProducer { full: true, ..Default::default() });
+        let eligibility_engine = EligibilityEngine::new()
+            .with_metrics(metrics.clone())
+            .with_events(Some(EvaluationEvents::new(producer)));
+
+        // The evaluation still succeeds
+        let response = eligibility_engine.evaluate(direct_params("Mother", "illness")).await.unwrap();
+        assert_eq!(response.output.case, "A");
+        #[cfg(feature = "metrics")]
+        assert_eq!(metrics.event_failures_total.get(), 1.0);
+    }
+}
@@ -0,0 +1,890 @@
+use axum::{
+    Json, Router,
+    extract::{Request, State},
+    http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode, header},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use std::io::ErrorKind;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tower_http::classify::{ServerErrorsAsFailures, SharedClassifier};
+use tower_http::compression::CompressionLayer;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tower_http::limit::RequestBodyLimitLayer;
+use tower_http::trace::{DefaultMakeSpan, DefaultOnRequest, DefaultOnResponse, TraceLayer};
+use tower_http::LatencyUnit;
+use tracing::Level;
+use rmcp::schemars;
+use serde::{Deserialize, Serialize};
+
+use super::config;
+use super::i18n;
+use super::eligibility_engine::{
+    self, EligibilityEngine, UnpaidLeaveDirectParams, UnpaidLeaveError, UnpaidLeaveResponse, ValidationError,
+};
+use super::metrics::{self, EligibilityMetrics};
+
+/// Address from the `BIND_ADDRESS` environment variable, or `default`
+pub fn bind_address(default: &str) -> String {
+    std::env::var("BIND_ADDRESS").unwrap_or_else(|_| default.to_string())
+}
+
+/// Parses a host:port bind address, resolving host names such as "localhost"
+pub fn parse_bind_address(address: &str) -> anyhow::Result<SocketAddr> {
+    let invalid = || anyhow::anyhow!("invalid BIND_ADDRESS '{}': expected host:port", address);
+    if let Ok(address) = address.parse::<SocketAddr>() {
+        return Ok(address);
+    }
+    address.to_socket_addrs().map_err(|_| invalid())?.next().ok_or_else(invalid)
+}
+
+/// Binds a listener on `address`, explaining the usual failures instead of the raw OS error
+pub async fn bind_listener(address: SocketAddr) -> anyhow::Result<tokio::net::TcpListener> {
+    tokio::net::TcpListener::bind(address).await.map_err(|e| match e.kind() {
+        ErrorKind::AddrInUse => anyhow::anyhow!(
+            "Port {} is already in use, stop the process listening on {} or choose another BIND_ADDRESS",
+            address.port(), address
+        ),
+        ErrorKind::AddrNotAvailable => anyhow::anyhow!("Address {} does not belong to this host", address.ip()),
+        ErrorKind::PermissionDenied => anyhow::anyhow!(
+            "Permission denied binding to {}, ports below 1024 require elevated privileges", address
+        ),
+        _ => anyhow::anyhow!("Cannot bind to {}: {}", address, e),
+    })
+}
+
+/// Level of the access log lines, "off" disables the access log
+pub const ACCESS_LOG_LEVEL_ENV: &str = "ACCESS_LOG_LEVEL";
+
+/// Logs method, URI, status and latency of every request. Headers and bodies are never logged
+pub type AccessLogLayer = TraceLayer<SharedClassifier<ServerErrorsAsFailures>>;
+
+/// Access log at the level in `ACCESS_LOG_LEVEL` (default: info), `None` when it is "off"
+pub fn access_log_layer() -> anyhow::Result<Option<AccessLogLayer>> {
+    access_log_layer_from(std::env::var(ACCESS_LOG_LEVEL_ENV).ok())
+}
+
+fn access_log_layer_from(level: Option<String>) -> anyhow::Result<Option<AccessLogLayer>> {
+    let level = match level.as_deref().map(str::trim) {
+        None | Some("") => Level::INFO,
+        Some(level) if level.eq_ignore_ascii_case("off") => return Ok(None),
+        Some(level) => level.parse().map_err(|_| anyhow::anyhow!(
+            "invalid {} '{}': expected trace, debug, info, warn, error or off", ACCESS_LOG_LEVEL_ENV, level
+        ))?,
+    };
+
+    // One line per request, emitted with the response
+    Ok(Some(
+        TraceLayer::new_for_http()
+            .make_span_with(DefaultMakeSpan::new().level(level).include_headers(false))
+            .on_request(DefaultOnRequest::new().level(Level::TRACE))
+            .on_response(DefaultOnResponse::new().level(level).latency_unit(LatencyUnit::Millis)),
+    ))
+}
+
+/// Environment variable that turns response compression off with "off"
+pub const COMPRESSION_ENV: &str = "COMPRESSION";
+
+/// Compresses responses with gzip or deflate when the client sends `Accept-Encoding`, `None` when
+/// `COMPRESSION` is "off". Event streams and bodies of 32 bytes or less are left alone
+pub fn compression_layer() -> anyhow::Result<Option<CompressionLayer>> {
+    compression_layer_from(std::env::var(COMPRESSION_ENV).ok())
+}
+
+fn compression_layer_from(value: Option<String>) -> anyhow::Result<Option<CompressionLayer>> {
+    match value.as_deref().map(str::trim) {
+        None | Some("") => Ok(Some(CompressionLayer::new())),
+        Some(value) if value.eq_ignore_ascii_case("on") => Ok(Some(CompressionLayer::new())),
+        Some(value) if value.eq_ignore_ascii_case("off") => Ok(None),
+        Some(value) => Err(anyhow::anyhow!("invalid {} '{}': expected on or off", COMPRESSION_ENV, value)),
+    }
+}
+
+/// Environment variable with the largest request body accepted, in bytes
+pub const MAX_REQUEST_BODY_BYTES_ENV: &str = "MAX_REQUEST_BODY_BYTES";
+/// Far above a full batch evaluation, small enough to bound the memory a client can make us buffer
+const DEFAULT_MAX_REQUEST_BODY_BYTES: usize = 64 * 1024;
+
+/// Answers 413 Payload Too Large to requests whose body exceeds `MAX_REQUEST_BODY_BYTES` (default: 64 KiB)
+pub fn body_limit_layer() -> anyhow::Result<RequestBodyLimitLayer> {
+    max_request_body_bytes_from(std::env::var(MAX_REQUEST_BODY_BYTES_ENV).ok()).map(RequestBodyLimitLayer::new)
+}
+
+fn max_request_body_bytes_from(limit: Option<String>) -> anyhow::Result<usize> {
+    match limit.as_deref().map(str::trim) {
+        None | Some("") => Ok(DEFAULT_MAX_REQUEST_BODY_BYTES),
+        Some(limit) => limit.parse().ok().filter(|limit| *limit > 0).ok_or_else(|| {
+            anyhow::anyhow!("invalid {} '{}': expected a positive number of bytes", MAX_REQUEST_BODY_BYTES_ENV, limit)
+        }),
+    }
+}
+
+/// Environment variable with the origins allowed to call the server from a browser
+pub const ALLOWED_ORIGINS_ENV: &str = "ALLOWED_ORIGINS";
+
+/// Methods used by MCP clients (DELETE ends a streamable-http session) and the REST endpoints
+const CORS_METHODS: [Method; 4] = [Method::GET, Method::POST, Method::DELETE, Method::OPTIONS];
+
+/// Request headers browser clients may send
+const CORS_REQUEST_HEADERS: [&str; 7] = [
+    "content-type", "authorization", "x-api-key", "mcp-session-id", "mcp-protocol-version", "last-event-id",
+    eligibility_engine::REQUEST_ID_HEADER,
+];
+
+/// CORS layer for the origins in `ALLOWED_ORIGINS`, `None` to send no CORS headers
+pub fn cors_layer() -> anyhow::Result<Option<CorsLayer>> {
+    cors_layer_from(std::env::var(ALLOWED_ORIGINS_ENV).ok())
+}
+
+/// `allowed_origins` is a comma-separated list of origins, or `*` for any origin
+fn cors_layer_from(allowed_origins: Option<String>) -> anyhow::Result<Option<CorsLayer>> {
+    let Some(allowed_origins) = allowed_origins.filter(|value| !value.trim().is_empty()) else {
+        return Ok(None);
+    };
+
+    let allow_origin = if allowed_origins.trim() == "*" {
+        AllowOrigin::any()
+    } else {
+        let origins = allowed_origins
+            .split(',')
+            .map(|origin| origin.trim())
+            .filter(|origin| !origin.is_empty())
+            .map(|origin| {
+                HeaderValue::from_str(origin).map_err(|_| anyhow::anyhow!("Invalid origin '{}' in {}", origin, ALLOWED_ORIGINS_ENV))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        AllowOrigin::list(origins)
+    };
+
+    Ok(Some(
+        CorsLayer::new()
+            .allow_origin(allow_origin)
+            .allow_methods(CORS_METHODS)
+            .allow_headers(CORS_REQUEST_HEADERS.map(HeaderName::from_static))
+            .expose_headers([HeaderName::from_static("mcp-session-id")]),
+    ))
+}
+
+/// Certificate and private key files, in PEM format, used to serve HTTPS
+#[derive(Debug, Clone, PartialEq)]
+pub struct TlsPaths {
+    pub cert_path: String,
+    pub key_path: String,
+}
+
+/// TLS files from `TLS_CERT_PATH` and `TLS_KEY_PATH`, `None` to serve plain HTTP
+pub fn tls_paths() -> anyhow::Result<Option<TlsPaths>> {
+    tls_paths_from(std::env::var("TLS_CERT_PATH").ok(), std::env::var("TLS_KEY_PATH").ok())
+}
+
+fn tls_paths_from(cert_path: Option<String>, key_path: Option<String>) -> anyhow::Result<Option<TlsPaths>> {
+    let non_empty = |value: Option<String>| value.filter(|value| !value.trim().is_empty());
+    match (non_empty(cert_path), non_empty(key_path)) {
+        (Some(cert_path), Some(key_path)) => Ok(Some(TlsPaths { cert_path, key_path })),
+        (None, None) => Ok(None),
+        (Some(_), None) => Err(anyhow::anyhow!("TLS_CERT_PATH is set but TLS_KEY_PATH is not, both are required to enable TLS")),
+        (None, Some(_)) => Err(anyhow::anyhow!("TLS_KEY_PATH is set but TLS_CERT_PATH is not, both are required to enable TLS")),
+    }
+}
+
+/// Environment variable with the seconds in-flight requests get to finish on shutdown
+pub const SHUTDOWN_TIMEOUT_ENV: &str = "SHUTDOWN_TIMEOUT_SECS";
+const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Time in-flight requests get to finish once shutdown starts
+pub fn shutdown_timeout() -> Duration {
+    std::env::var(SHUTDOWN_TIMEOUT_ENV)
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT)
+}
+
+/// Resolves on ctrl_c or, on Unix, on SIGTERM as sent by Kubernetes
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Cannot listen for ctrl_c: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Cannot listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+    tracing::info!(
+        "Shutting down, draining {} in-flight evaluations for up to {:?}",
+        metrics::METRICS.active_requests.get(),
+        shutdown_timeout()
+    );
+}
+
+/// Logs the evaluations abandoned because the drain timeout expired
+pub fn log_abandoned_requests() {
+    tracing::warn!(
+        "Drain timeout expired, abandoning {} in-flight evaluations",
+        metrics::METRICS.active_requests.get()
+    );
+}
+
+/// Serves `router` until a shutdown signal, then gives in-flight requests `shutdown_timeout()` to finish
+pub async fn serve_with_drain<L>(listener: L, router: Router) -> anyhow::Result<()>
+where
+    L: axum::serve::Listener,
+    L::Addr: std::fmt::Debug,
+{
+    serve_until(listener, router, shutdown_signal()).await
+}
+
+async fn serve_until<L>(listener: L, router: Router, signal: impl Future<Output = ()>) -> anyhow::Result<()>
+where
+    L: axum::serve::Listener,
+    L::Addr: std::fmt::Debug,
+{
+    let drain = CancellationToken::new();
+    let shutdown = drain.clone();
+    let server = axum::serve(listener, router).with_graceful_shutdown(async move { shutdown.cancelled().await });
+    let mut server = std::pin::pin!(server.into_future());
+
+    tokio::select! {
+        result = &mut server => return Ok(result?),
+        _ = signal => drain.cancel(),
+    }
+
+    match tokio::time::timeout(shutdown_timeout(), server).await {
+        Ok(result) => Ok(result?),
+        Err(_) => {
+            log_abandoned_requests();
+            Ok(())
+        }
+    }
+}
+
+/// Environment variable with the Unix domain socket path the streamable-http server listens on instead of TCP
+pub const BIND_UDS_ENV: &str = "BIND_UDS";
+
+/// Serves `router` on a Unix domain socket at `path` like `serve_with_drain`, removing the socket file once done
+#[cfg(unix)]
+pub async fn serve_unix_with_drain(path: &std::path::Path, router: Router) -> anyhow::Result<()> {
+    serve_unix_until(path, router, shutdown_signal()).await
+}
+
+#[cfg(unix)]
+async fn serve_unix_until(path: &std::path::Path, router: Router, signal: impl Future<Output = ()>) -> anyhow::Result<()> {
+    let listener = bind_unix_listener(path)?;
+    let result = serve_until(listener, router, signal).await;
+    if let Err(e) = std::fs::remove_file(path) {
+        tracing::warn!("Cannot remove socket {}: {}", path.display(), e);
+    }
+    result
+}
+
+/// Binds a Unix domain socket at `path`, replacing the socket a server that did not shut down cleanly left behind
+#[cfg(unix)]
+fn bind_unix_listener(path: &std::path::Path) -> anyhow::Result<tokio::net::UnixListener> {
+    use std::os::unix::fs::FileTypeExt;
+
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if !metadata.file_type().is_socket() => {
+            anyhow::bail!("Cannot bind to {}: the file exists and is not a socket", path.display())
+        }
+        Ok(_) if std::os::unix::net::UnixStream::connect(path).is_ok() => {
+            anyhow::bail!("Socket {} is already in use, stop the process listening on it or choose another {}", path.display(), BIND_UDS_ENV)
+        }
+        Ok(_) => std::fs::remove_file(path)
+            .map_err(|e| anyhow::anyhow!("Cannot remove stale socket {}: {}", path.display(), e))?,
+        Err(_) => {}
+    }
+    tokio::net::UnixListener::bind(path).map_err(|e| anyhow::anyhow!("Cannot bind to {}: {}", path.display(), e))
+}
+
+/// Startup steps shared by the HTTP servers, to run before accepting requests
+pub fn init() -> anyhow::Result<()> {
+    // Start the uptime clock now rather than on the first scrape
+    once_cell::sync::Lazy::force(&metrics::START_TIME);
+
+    // Abort on an invalid configuration file rather than serving default amounts
+    config::load_config().map_err(|e| anyhow::anyhow!("{}", e))?;
+    metrics::request_duration_buckets().map_err(|e| anyhow::anyhow!(e))?;
+    Ok(())
+}
+
+/// Probe and metadata routes, always reachable so orchestrators can check the server
+pub fn public_routes() -> Router {
+    Router::new()
+        .route("/health", get(health_handler))
+        .route("/healthz", get(healthz_handler))
+        .route("/readyz", get(readyz_handler))
+        .route("/version", get(version_handler))
+        .route("/schema/input", get(input_schema_handler))
+        .route("/schema/output", get(output_schema_handler))
+}
+
+/// Path the Prometheus metrics are served on unless configured otherwise
+pub const DEFAULT_METRICS_PATH: &str = "/metrics";
+
+/// Metrics and REST evaluation routes, served next to the MCP transport behind `with_api_key`.
+/// `metrics_path` must start with '/', the JSON version of the metrics is served at `<metrics_path>.json`.
+/// Without the `metrics` feature only the evaluation route is served
+pub fn protected_routes(metrics_path: &str) -> Router {
+    protected_routes_with_metrics(metrics_path, metrics::METRICS.clone())
+}
+
+/// `protected_routes` recording evaluations in, and serving, `metrics` instead of the process-wide `METRICS`
+fn protected_routes_with_metrics(metrics_path: &str, metrics: Arc<EligibilityMetrics>) -> Router {
+    let router = Router::new().route("/evaluate", post(evaluate_handler));
+    #[cfg(feature = "metrics")]
+    let router = router
+        .route(metrics_path, get(metrics_handler))
+        .route(&format!("{}.json", metrics_path.trim_end_matches('/')), get(metrics_json_handler));
+    #[cfg(not(feature = "metrics"))]
+    let _ = metrics_path;
+    router.with_state(metrics)
+}
+
+/// Environment variable holding the key clients must present, authentication is disabled when unset
+pub const API_KEY_ENV: &str = "API_KEY";
+
+/// Key from `API_KEY`, `None` when authentication is disabled
+pub fn api_key() -> Option<String> {
+    std::env::var(API_KEY_ENV).ok().filter(|key| !key.is_empty())
+}
+
+/// Requires `api_key` on every route of `router`, left untouched when `api_key` is `None`
+pub fn with_api_key(router: Router, api_key: Option<String>) -> Router {
+    match api_key {
+        Some(api_key) => router.layer(middleware::from_fn_with_state(Arc::<str>::from(api_key), check_api_key)),
+        None => router,
+    }
+}
+
+/// Whether `headers` carry the key from `API_KEY`. Always false when authentication is disabled,
+/// so the admin tools are never open to every client
+pub fn is_admin(headers: &HeaderMap) -> bool {
+    match api_key() {
+        Some(expected) => presented_api_key(headers).is_some_and(|key| constant_time_eq(key.as_bytes(), expected.as_bytes())),
+        None => false,
+    }
+}
+
+/// Accepts the key as `Authorization: Bearer <key>` or `X-API-Key: <key>`, answering 401 otherwise
+async fn check_api_key(State(expected): State<Arc<str>>, request: Request, next: Next) -> Response {
+    match presented_api_key(request.headers()) {
+        Some(key) if constant_time_eq(key.as_bytes(), expected.as_bytes()) => next.run(request).await,
+        _ => (StatusCode::UNAUTHORIZED, "Missing or invalid API key").into_response(),
+    }
+}
+
+fn presented_api_key(headers: &HeaderMap) -> Option<&str> {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    bearer.or_else(|| headers.get("x-api-key").and_then(|value| value.to_str().ok()))
+}
+
+/// Compares without short-circuiting so the time taken does not reveal how much of the key matched
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |difference, (x, y)| difference | (x ^ y)) == 0
+}
+
+/// Handler for the /metrics endpoint
+#[cfg(feature = "metrics")]
+async fn metrics_handler(State(metrics): State<Arc<EligibilityMetrics>>) -> impl IntoResponse {
+    match metrics.gather() {
+        Ok(output) => (StatusCode::OK, output),
+        Err(e) => {
+            tracing::error!("Failed to encode metrics: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Failed to encode metrics".to_string())
+        }
+    }
+}
+
+/// Handler for /metrics.json, the same metrics as a JSON object
+#[cfg(feature = "metrics")]
+async fn metrics_json_handler(State(metrics): State<Arc<EligibilityMetrics>>) -> Json<serde_json::Value> {
+    Json(metrics.gather_json())
+}
+
+/// Handler for the /health endpoint
+async fn health_handler() -> impl IntoResponse {
+    let output = "OK";
+    (StatusCode::OK, output)
+}
+
+/// Handler for the /healthz endpoint, evaluates a canary input through the decision engine
+async fn healthz_handler() -> impl IntoResponse {
+    match EligibilityEngine::new().health_check().await {
+        Ok(()) => (StatusCode::OK, "OK".to_string()),
+        Err(e) => {
+            tracing::error!("Health check failed: {}", e);
+            (StatusCode::SERVICE_UNAVAILABLE, e)
+        }
+    }
+}
+
+/// Handler for the /readyz endpoint. Unlike /healthz, which checks that evaluations
+/// produce the right result, this only reports whether the default and tenant rules
+/// loaded. Rules are not reloaded, so readiness is the same as startup success.
+async fn readyz_handler() -> impl IntoResponse {
+    if eligibility_engine::rules_ready() {
+        (StatusCode::OK, "READY")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "NOT READY")
+    }
+}
+
+/// Handler for the /version endpoint
+async fn version_handler() -> impl IntoResponse {
+    Json(eligibility_engine::ServerVersionInfo::current())
+}
+
+/// Handler for the /schema/input endpoint, the JSON Schema of the evaluation parameters
+async fn input_schema_handler() -> impl IntoResponse {
+    Json(schemars::schema_for!(UnpaidLeaveDirectParams))
+}
+
+/// Handler for the /schema/output endpoint, the JSON Schema of the evaluation response
+async fn output_schema_handler() -> impl IntoResponse {
+    Json(schemars::schema_for!(UnpaidLeaveResponse))
+}
+
+/// Body returned by the REST endpoints when an evaluation fails
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ErrorResponse {
+    pub error: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub validation_errors: Vec<ValidationError>,
+}
+
+/// Handler for POST /evaluate, the MCP evaluation tool as a plain JSON endpoint.
+/// Invalid input is answered with 400, a busy server or missing rules with 503, an evaluation
+/// running past its deadline with 504, any other failure with 500.
+pub async fn evaluate_handler(
+    State(metrics): State<Arc<EligibilityMetrics>>,
+    headers: HeaderMap,
+    Json(mut direct_params): Json<UnpaidLeaveDirectParams>,
+) -> impl IntoResponse {
+    let request_id = eligibility_engine::request_id_from_headers(&headers);
+    i18n::default_locale(&mut direct_params.locale, &headers);
+    match EligibilityEngine::new().with_metrics(metrics).evaluate_with_request_id(direct_params, request_id).await {
+        Ok(response) => (StatusCode::OK, Json(response)).into_response(),
+        Err(e) => evaluation_error_response(e),
+    }
+}
+
+/// Response of /evaluate for an evaluation that failed with `error`
+fn evaluation_error_response(error: UnpaidLeaveError) -> Response {
+    let status = match error {
+        UnpaidLeaveError::ValidationError(validation_errors) => {
+            let body = ErrorResponse { error: "Invalid input".to_string(), validation_errors };
+            return (StatusCode::BAD_REQUEST, Json(body)).into_response();
+        }
+        UnpaidLeaveError::ServerBusy(_) | UnpaidLeaveError::RulesUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+        UnpaidLeaveError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+        _ => {
+            tracing::error!("Evaluation failed: {}", error);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    };
+    let body = ErrorResponse { error: error.to_string(), validation_errors: Vec::new() };
+    (status, Json(body)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request};
+    #[cfg(feature = "metrics")]
+    use rmcp::{handler::server::wrapper::Parameters, model::Extensions};
+    use tower::ServiceExt;
+
+    async fn post_evaluate(body: serde_json::Value) -> (StatusCode, Vec<u8>) {
+        let router = protected_routes(DEFAULT_METRICS_PATH);
+        let request = Request::post("/evaluate")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, bytes.to_vec())
+    }
+
+    async fn get_metrics(api_key: Option<&str>, headers: &[(&str, &str)]) -> StatusCode {
+        let router = with_api_key(protected_routes(DEFAULT_METRICS_PATH), api_key.map(str::to_string));
+        let mut request = Request::get("/metrics");
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        router.oneshot(request.body(Body::empty()).unwrap()).await.unwrap().status()
+    }
+
+    #[cfg(feature = "metrics")]
+    #[tokio::test]
+    async fn test_api_key_accepted() {
+        assert_eq!(get_metrics(Some("secret"), &[("authorization", "Bearer secret")]).await, StatusCode::OK);
+        assert_eq!(get_metrics(Some("secret"), &[("x-api-key", "secret")]).await, StatusCode::OK);
+        // Authentication is disabled without a configured key
+        assert_eq!(get_metrics(None, &[]).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_api_key_rejected() {
+        assert_eq!(get_metrics(Some("secret"), &[]).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(get_metrics(Some("secret"), &[("authorization", "Bearer wrong")]).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(get_metrics(Some("secret"), &[("x-api-key", "secre")]).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(get_metrics(Some("secret"), &[("authorization", "secret")]).await, StatusCode::UNAUTHORIZED);
+    }
+
+    async fn preflight(allowed_origins: Option<&str>, origin: &str) -> Response {
+        let mut router = public_routes();
+        if let Some(cors) = cors_layer_from(allowed_origins.map(str::to_string)).unwrap() {
+            router = router.layer(cors);
+        }
+        let request = Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/version")
+            .header(header::ORIGIN, origin)
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+            .header(header::ACCESS_CONTROL_REQUEST_HEADERS, "content-type,mcp-session-id,x-request-id")
+            .body(Body::empty())
+            .unwrap();
+        router.oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_cors_allowed_origins() {
+        let response = preflight(Some("https://a.example, https://b.example"), "https://b.example").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "https://b.example");
+        let allowed_headers = response.headers()[header::ACCESS_CONTROL_ALLOW_HEADERS].to_str().unwrap();
+        assert!(allowed_headers.split(',').any(|name| name.trim() == "x-request-id"), "{}", allowed_headers);
+
+        let response = preflight(Some("https://a.example"), "https://evil.example").await;
+        assert!(response.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+
+        let response = preflight(Some("*"), "https://any.example").await;
+        assert_eq!(response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+
+        // No CORS headers unless configured
+        let response = preflight(None, "https://a.example").await;
+        assert!(response.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+        assert!(cors_layer_from(Some("https://a.example,bad\norigin".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_parse_bind_address() {
+        assert_eq!(parse_bind_address("0.0.0.0:8001").unwrap(), "0.0.0.0:8001".parse().unwrap());
+        assert_eq!(parse_bind_address("[::1]:8001").unwrap(), "[::1]:8001".parse().unwrap());
+        assert_eq!(parse_bind_address("localhost:8001").unwrap().port(), 8001);
+        for address in ["8001", "127.0.0.1", "127.0.0.1:port", "127.0.0.1:70000", ""] {
+            let error = parse_bind_address(address).unwrap_err().to_string();
+            assert_eq!(error, format!("invalid BIND_ADDRESS '{}': expected host:port", address));
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_serve_over_unix_socket() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let path = std::env::temp_dir().join(format!("eligibility-{}.sock", uuid::Uuid::new_v4()));
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn({
+            let path = path.clone();
+            async move { serve_unix_until(&path, public_routes(), async { stopped.await.unwrap_or(()) }).await }
+        });
+        while !path.exists() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(bind_unix_listener(&path).unwrap_err().to_string().contains("already in use"));
+
+        let mut stream = tokio::net::UnixStream::connect(&path).await.unwrap();
+        stream.write_all(b"GET /healthz HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+
+        stop.send(()).unwrap();
+        server.await.unwrap().unwrap();
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_bind_listener_reports_port_in_use() {
+        let listener = bind_listener("127.0.0.1:0".parse().unwrap()).await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let error = bind_listener(address).await.unwrap_err().to_string();
+        assert!(error.starts_with(&format!("Port {} is already in use", address.port())), "{}", error);
+    }
+
+    #[test]
+    fn test_access_log_level() {
+        let level = |value: &str| Some(value.to_string());
+        assert!(access_log_layer_from(None).unwrap().is_some());
+        assert!(access_log_layer_from(level("DEBUG")).unwrap().is_some());
+        assert!(access_log_layer_from(level("off")).unwrap().is_none());
+        assert!(access_log_layer_from(level("verbose")).is_err());
+    }
+
+    #[test]
+    fn test_tls_paths_require_both_files() {
+        let path = |value: &str| Some(value.to_string());
+        assert_eq!(tls_paths_from(None, None).unwrap(), None);
+        assert_eq!(
+            tls_paths_from(path("cert.pem"), path("key.pem")).unwrap(),
+            Some(TlsPaths { cert_path: "cert.pem".to_string(), key_path: "key.pem".to_string() })
+        );
+        assert!(tls_paths_from(path("cert.pem"), None).is_err());
+        assert!(tls_paths_from(path(""), path("key.pem")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_endpoint() {
+        let (status, body) = post_evaluate(serde_json::json!({
+            "relationship": "mother",
+            "situation": "illness",
+            "is_single_parent": false
+        })).await;
+        assert_eq!(status, StatusCode::OK);
+        let response: UnpaidLeaveResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(response.output.case, "A");
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_endpoint_accepts_decimal_numbers() {
+        // Parsed from raw bytes, so the flattened fields see serde_json's arbitrary-precision numbers
+        let (status, body) = post_evaluate(serde_json::json!({
+            "relationship": "mother",
+            "situation": "birth",
+            "is_single_parent": false,
+            "total_children_after": 3.0
+        })).await;
+        assert_eq!(status, StatusCode::OK);
+        let response: UnpaidLeaveResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(response.output.case, "B");
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_endpoint_honors_accept_language() {
+        let post = |accept_language: Option<&'static str>| async move {
+            let mut request = Request::post("/evaluate").header("content-type", "application/json");
+            if let Some(accept_language) = accept_language {
+                request = request.header("accept-language", accept_language);
+            }
+            let body = serde_json::json!({ "relationship": "mother", "situation": "illness", "is_single_parent": false });
+            let response = protected_routes(DEFAULT_METRICS_PATH)
+                .oneshot(request.body(Body::from(body.to_string())).unwrap())
+                .await
+                .unwrap();
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<UnpaidLeaveResponse>(&bytes).unwrap()
+        };
+
+        let response = post(Some("en")).await;
+        assert_eq!(response.locale.as_deref(), Some("en"));
+        assert_eq!(response.output.description, "First-degree family care sick or accident victim");
+        assert_eq!(post(None).await.locale.as_deref(), Some("es"));
+        assert_eq!(post(Some("de-DE")).await.locale.as_deref(), Some("es"));
+    }
+
+    #[test]
+    fn test_compression_setting() {
+        let value = |value: &str| Some(value.to_string());
+        assert!(compression_layer_from(None).unwrap().is_some());
+        assert!(compression_layer_from(value("ON")).unwrap().is_some());
+        assert!(compression_layer_from(value("off")).unwrap().is_none());
+        assert!(compression_layer_from(value("brotli")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_large_response_compressed() {
+        use std::io::Read;
+
+        let router = protected_routes(DEFAULT_METRICS_PATH).layer(compression_layer_from(None).unwrap().unwrap());
+        let post = |accept_encoding: Option<&'static str>| {
+            let router = router.clone();
+            async move {
+                let mut request = Request::post("/evaluate").header("content-type", "application/json");
+                if let Some(accept_encoding) = accept_encoding {
+                    request = request.header(header::ACCEPT_ENCODING, accept_encoding);
+                }
+                let body = serde_json::json!({"relationship": "mother", "situation": "illness", "is_single_parent": false});
+                let response = router.oneshot(request.body(Body::from(body.to_string())).unwrap()).await.unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                let encoding = response.headers().get(header::CONTENT_ENCODING).cloned();
+                (encoding, axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap())
+            }
+        };
+
+        let (encoding, plain) = post(None).await;
+        assert_eq!(encoding, None);
+        let plain = serde_json::from_slice::<UnpaidLeaveResponse>(&plain).unwrap();
+
+        let (encoding, compressed) = post(Some("gzip")).await;
+        assert_eq!(encoding.as_ref().map(|value| value.to_str().unwrap()), Some("gzip"));
+        let mut decompressed = Vec::new();
+        flate2::read::GzDecoder::new(&compressed[..]).read_to_end(&mut decompressed).unwrap();
+        assert!(compressed.len() < decompressed.len());
+        // Same evaluation, only the evaluation id differs between the two responses
+        let decompressed = serde_json::from_slice::<UnpaidLeaveResponse>(&decompressed).unwrap();
+        assert_eq!(decompressed.output, plain.output);
+    }
+
+    #[test]
+    fn test_max_request_body_bytes() {
+        assert_eq!(max_request_body_bytes_from(None).unwrap(), 64 * 1024);
+        assert_eq!(max_request_body_bytes_from(Some(" ".to_string())).unwrap(), 64 * 1024);
+        assert_eq!(max_request_body_bytes_from(Some("1048576".to_string())).unwrap(), 1024 * 1024);
+        assert!(max_request_body_bytes_from(Some("0".to_string())).is_err());
+        assert!(max_request_body_bytes_from(Some("64k".to_string())).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_oversized_body_rejected() {
+        let router = protected_routes(DEFAULT_METRICS_PATH).layer(body_limit_layer().unwrap());
+        let body = serde_json::json!({
+            "relationship": "mother",
+            "situation": "illness",
+            "is_single_parent": false,
+            "padding": "x".repeat(DEFAULT_MAX_REQUEST_BODY_BYTES),
+        });
+        let request = Request::post("/evaluate")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        // Also enforced on streamed bodies, which carry no Content-Length
+        let chunks = futures::stream::iter((0..80).map(|_| Ok::<_, std::io::Error>("x".repeat(1024))));
+        let request = Request::post("/evaluate")
+            .header("content-type", "application/json")
+            .body(Body::from_stream(chunks))
+            .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let body = serde_json::json!({ "relationship": "mother", "situation": "illness", "is_single_parent": false });
+        let request = Request::post("/evaluate")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        assert_eq!(router.oneshot(request).await.unwrap().status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_endpoint_rejects_invalid_input() {
+        let (status, body) = post_evaluate(serde_json::json!({
+            "relationship": "mother",
+            "situation": "illness",
+            "is_single_parent": false,
+            "leave_percentage": 150
+        })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error.validation_errors[0].path, "/input/leave_percentage");
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_endpoint_overload_statuses() {
+        let status_and_error = |error: UnpaidLeaveError| async move {
+            let response = evaluation_error_response(error);
+            let status = response.status();
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            (status, serde_json::from_slice::<ErrorResponse>(&bytes).unwrap().error)
+        };
+
+        let (status, error) = status_and_error(UnpaidLeaveError::ServerBusy(4)).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(error.starts_with("Server busy"), "{}", error);
+        let (status, error) = status_and_error(UnpaidLeaveError::Timeout(Duration::from_millis(250))).await;
+        assert_eq!(status, StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(error, "Evaluation timed out after 250 ms");
+        let (status, _) = status_and_error(UnpaidLeaveError::InternalError("boom".to_string())).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[cfg(feature = "metrics")]
+    #[tokio::test]
+    async fn test_metrics_json_endpoint() {
+        let request = Request::get("/metrics.json").body(Body::empty()).unwrap();
+        let response = protected_routes(DEFAULT_METRICS_PATH).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let metrics: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(metrics["eligibility_requests_total"]["type"], "counter");
+    }
+
+    #[cfg(feature = "metrics")]
+    /// Value of the sample `name` with the default tool and tenant labels in the Prometheus text output
+    fn sample(output: &str, name: &str) -> f64 {
+        let series = format!("{}{{tenant=\"default\",tool=\"evaluate_unpaid_leave_eligibility\"}} ", name);
+        output
+            .lines()
+            .find_map(|line| line.strip_prefix(series.as_str()))
+            .unwrap_or_else(|| panic!("{} missing from metrics", series))
+            .parse()
+            .unwrap()
+    }
+
+    #[cfg(feature = "metrics")]
+    #[tokio::test]
+    async fn test_metrics_reflect_evaluations() {
+        // Isolated metrics, so evaluations made by concurrent tests are not counted.
+        // Each call is awaited before the next one, the scrape sees all three completed
+        let metrics = Arc::new(EligibilityMetrics::new());
+        let router = protected_routes_with_metrics(DEFAULT_METRICS_PATH, metrics.clone());
+        let post = |body: serde_json::Value| {
+            Request::post("/evaluate")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let valid = post(serde_json::json!({"relationship": "mother", "situation": "illness", "is_single_parent": false}));
+        assert_eq!(router.clone().oneshot(valid).await.unwrap().status(), StatusCode::OK);
+        let invalid = post(serde_json::json!({"relationship": "brother", "situation": "birht", "is_single_parent": false}));
+        assert_eq!(router.clone().oneshot(invalid).await.unwrap().status(), StatusCode::BAD_REQUEST);
+
+        // The MCP tool records into the same metrics as the REST endpoint
+        let engine = EligibilityEngine::new().with_metrics(metrics);
+        let tool_params = serde_json::from_value(serde_json::json!({"relationship": "son", "situation": "accident", "is_single_parent": false})).unwrap();
+        let call_result = engine
+            .evaluate_unpaid_leave_eligibility(Parameters(tool_params), Extensions::new())
+            .await
+            .unwrap();
+        assert_eq!(call_result.is_error, Some(false));
+
+        let response = router.oneshot(Request::get(DEFAULT_METRICS_PATH).body(Body::empty()).unwrap()).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let output = String::from_utf8(body.to_vec()).unwrap();
+        assert_eq!(sample(&output, "eligibility_requests_total"), 3.0);
+        assert_eq!(sample(&output, "eligibility_errors_total"), 1.0);
+        assert_eq!(sample(&output, "eligibility_request_duration_seconds_count"), 3.0);
+    }
+}
@@ -0,0 +1,367 @@
+use std::fmt;
+
+/// Language of the human-readable texts of a response
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Locale {
+    /// Spanish, the language of the regulation
+    #[default]
+    Es,
+    En,
+}
+
+/// Languages of the texts, as accepted in the `locale` input
+pub const SUPPORTED_LANGUAGES: &[&str] = &["es", "en"];
+
+impl Locale {
+    /// Locale of a language tag such as "en", "en-GB" or "ES_es", matched on its language
+    /// subtag. `None` for languages without a catalog
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let language = tag.trim().split(['-', '_']).next().unwrap_or_default().to_lowercase();
+        match language.as_str() {
+            "es" => Some(Locale::Es),
+            "en" => Some(Locale::En),
+            _ => None,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::Es => "es",
+            Locale::En => "en",
+        }
+    }
+
+    fn catalog(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Locale::Es => ES,
+            Locale::En => EN,
+        }
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+/// Language the client prefers most among those with a catalog, from an `Accept-Language`
+/// value such as "fr-CH, fr;q=0.9, en;q=0.8". Quality values are honored, ties go to the
+/// first listed language. `None` when no listed language is supported
+pub fn locale_from_accept_language(value: &str) -> Option<Locale> {
+    let mut preferred: Option<(Locale, f32)> = None;
+    for entry in value.split(',') {
+        let mut parameters = entry.split(';');
+        let Some(locale) = parameters.next().and_then(Locale::from_tag) else {
+            continue;
+        };
+        let quality = parameters
+            .find_map(|parameter| parameter.trim().strip_prefix("q="))
+            .map(|quality| quality.trim().parse().unwrap_or(0.0))
+            .unwrap_or(1.0);
+        if quality > 0.0 && preferred.is_none_or(|(_, best)| quality > best) {
+            preferred = Some((locale, quality));
+        }
+    }
+    preferred.map(|(locale, _)| locale)
+}
+
+/// Locale requested by the `Accept-Language` header, if any supported language is listed
+pub fn locale_from_headers(headers: &axum::http::HeaderMap) -> Option<Locale> {
+    headers
+        .get(axum::http::header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(locale_from_accept_language)
+}
+
+/// Fills a missing `locale` input with the language of the `Accept-Language` header, so the
+/// texts of any tool follow the client's language unless the call asks for another one
+pub fn default_locale(locale: &mut Option<String>, headers: &axum::http::HeaderMap) {
+    if locale.is_none() {
+        *locale = locale_from_headers(headers).map(|locale| locale.code().to_string());
+    }
+}
+
+/// Message `key` in `locale`, falling back to the default locale when `locale` has no
+/// translation for it. `None` for unknown keys
+pub fn message(locale: Locale, key: &str) -> Option<&'static str> {
+    [locale, Locale::default()]
+        .into_iter()
+        .find_map(|locale| locale.catalog().iter().find(|(entry, _)| *entry == key).map(|(_, text)| *text))
+}
+
+/// Message `key` in `locale` with every `{name}` placeholder replaced by the value given for
+/// `name` in `args`. Unknown keys are returned as they are
+pub fn render(locale: Locale, key: &str, args: &[(&str, &dyn fmt::Display)]) -> String {
+    let mut text = message(locale, key).unwrap_or(key).to_string();
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    text
+}
+
+/// Keys of the texts produced by the embedded rule document, which is written in English
+const RULE_TEXT_KEYS: &[(&str, &str)] = &[
+    ("First-degree family care sick or accident victim", "case_a.description"),
+    ("The person must have been hospitalized and the care of the person must be continued", "case_a.requirements"),
+    ("Third child or more with newborn", "case_b.description"),
+    (
+        "The number of children must be 3 or more, the ages of at least 2 of the minors must be less than 6, if there is disability greater than 33% then the limit is 9 years",
+        "case_b.requirements",
+    ),
+    ("The number of children must be 3 or more, must consult with administration", "case_b.not_enough_children"),
+    ("Adoption or foster care", "case_c.description"),
+    ("In the foster care case the duration must be longer than one year", "case_c.requirements"),
+    ("Delivery, adoption or foster care multiple", "case_d.description"),
+    ("Single-parent family with newborn", "case_e.description"),
+    ("The single-parent status must be documented", "case_e.requirements"),
+    ("No case applies", "no_case"),
+    ("Not applicable by relationship (first degree)", "invalid_relationship"),
+];
+
+/// `text` produced by the rule document, translated to `locale`. Texts without a key,
+/// e.g. from a custom rule document, are returned unchanged
+pub fn rule_text(locale: Locale, text: &str) -> String {
+    RULE_TEXT_KEYS
+        .iter()
+        .find(|(rule_text, _)| *rule_text == text)
+        .and_then(|(_, key)| message(locale, key))
+        .unwrap_or(text)
+        .to_string()
+}
+
+const ES: &[(&str, &str)] = &[
+    ("case_a.description", "Cuidado de familiar de primer grado enfermo o accidentado"),
+    ("case_a.requirements", "La persona debe haber estado hospitalizada y necesitar cuidados continuados"),
+    ("case_b.description", "Tercer hijo o más con recién nacido"),
+    (
+        "case_b.requirements",
+        "Debe haber 3 o más hijos y al menos 2 de los menores deben tener menos de 6 años; si hay una discapacidad superior al 33% el límite es de 9 años",
+    ),
+    ("case_b.not_enough_children", "Debe haber 3 o más hijos, consulte con la administración"),
+    ("case_c.description", "Adopción o acogimiento"),
+    ("case_c.requirements", "En caso de acogimiento, su duración debe ser superior a un año"),
+    ("case_d.description", "Parto, adopción o acogimiento múltiple"),
+    ("case_e.description", "Familia monoparental con recién nacido"),
+    ("case_e.requirements", "La condición de familia monoparental debe acreditarse"),
+    ("no_case", "No aplica ningún supuesto"),
+    ("invalid_relationship", "No aplica por parentesco (primer grado)"),
+    ("next_steps.apply", "Presente la solicitud de ayuda en los 15 días siguientes al inicio de la excedencia"),
+    ("next_steps.medical_certificate", "Aporte un informe médico de la hospitalización y de la necesidad de cuidados continuados"),
+    ("next_steps.children_certificates", "Aporte el libro de familia o los certificados de nacimiento de todos los hijos"),
+    ("next_steps.adoption_resolution", "Aporte la resolución de adopción o de acogimiento"),
+    ("next_steps.single_parent_certificate", "Aporte el documento que acredite la condición de familia monoparental"),
+    ("next_steps.review_errors", "Revise los requisitos no cumplidos y vuelva a evaluar si su situación cambia"),
+    ("next_steps.contact_administration", "Consulte con la administración si cree que su situación da derecho a la ayuda"),
+    ("validation.unknown_field", "campo desconocido '{field}'"),
+    ("validation.invalid_value", "'{value}' no es un valor válido de {field}. Valores válidos: {values}"),
+    ("validation.did_you_mean", "¿Quiso decir '{suggestion}'?"),
+    ("validation.total_children_after", "total_children_after debe ser un número entero mayor o igual que 0, se recibió {value}"),
+    ("validation.leave_end_required", "leave_end es obligatorio cuando se indica leave_start"),
+    ("validation.leave_start_required", "leave_start es obligatorio cuando se indica leave_end"),
+    ("validation.leave_period_combined", "leave_start y leave_end no se pueden combinar con leave_days ni leave_months"),
+    ("validation.leave_end_before_start", "leave_end {end} es anterior a leave_start {start}"),
+    ("validation.leave_too_long", "la excedencia del {start} al {end} supera el máximo de {max} días"),
+    ("validation.household_income", "household_income debe ser mayor o igual que 0, se recibió {value}"),
+    ("validation.household_size", "household_size debe ser al menos 1, se recibió 0"),
+    ("validation.leave_percentage", "leave_percentage debe estar entre 1 y 100, se recibió {value}"),
+    ("validation.effective_date", "'{value}' no es una effective_date válida, el formato esperado es AAAA-MM-DD"),
+    ("validation.no_regulation", "No hay ninguna normativa en vigor el {date}"),
+    ("validation.no_regulation_any_date", "No hay ninguna normativa en vigor"),
+    ("validation.unknown_tenant", "organización '{tenant_id}' desconocida, compruebe el tenant_id con el operador del servidor"),
+    ("validation.unknown_case", "el supuesto '{case}' no es uno de {cases}"),
+    ("warning.normalized", "{field} '{value}' se normalizó a '{normalized}'"),
+    ("warning.interpreted", "{field} '{value}' se interpretó como '{normalized}'"),
+    ("warning.implausible_combination", "Combinación poco plausible de relationship '{relationship}' y situation '{situation}': {reason}"),
+    (
+        "warning.reason.parent_of_child",
+        "relationship debe describir al solicitante como progenitor del menor, p. ej. 'mother', 'father' o 'parent'",
+    ),
+    ("warning.reason.birth_not_foster_care", "un nacimiento no es un acogimiento, use 'foster_care' o 'multiple_foster_care' en su lugar"),
+    ("warning.children_ignored", "total_children_after ({children}) no se tiene en cuenta en la situación de cuidado '{situation}'"),
+    (
+        "warning.children_missing",
+        "no se indicó total_children_after para la situación '{situation}'; los supuestos que dependen del número de hijos no se pueden evaluar por completo",
+    ),
+    ("warning.inconsistent_output", "Resultado incoherente de las reglas: {mismatch}"),
+    ("warning.served_from_cache", "{error}; este resultado procede de la caché de una evaluación idéntica anterior"),
+    ("warning.household_size_without_income", "household_size no tiene efecto sin household_income"),
+    ("warning.unknown_region", "Región '{region}' desconocida, se aplican los importes nacionales"),
+    ("warning.disability_uplift_applied", "Incremento por discapacidad aplicado: monthly_benefit pasa de {before} a {after}"),
+    ("warning.disability_uplift_situation", "has_disability solo incrementa la ayuda en las situaciones {situations}"),
+    ("warning.disability_uplift_not_configured", "has_disability no tiene efecto: este servidor no configura ningún incremento por discapacidad"),
+    ("warning.benefit_capped", "monthly_benefit de {amount} limitado al máximo de {cap}"),
+    ("warning.unsupported_locale", "el idioma '{locale}' no está soportado, se esperaba uno de {locales}; los textos están en '{default}'"),
+    ("error.income_threshold", "Los ingresos del hogar de {income} superan el límite de {threshold} del supuesto {case}"),
+    (
+        "error.per_member_income_threshold",
+        "Los ingresos por miembro del hogar de {per_member} ({income} entre {size} miembros) superan el límite de {threshold} del supuesto {case}",
+    ),
+    (
+        "explanation.relationship",
+        "'{relationship}' no es un parentesco de primer grado cubierto por la ayuda, debe ser uno de {relationships}.",
+    ),
+    (
+        "explanation.third_child",
+        "Un parto simple en una familia biparental solo da derecho a la ayuda a partir del tercer hijo, pero total_children_after es {children}. \
+         Las familias monoparentales y los partos múltiples tienen derecho sea cual sea el número de hijos.",
+    ),
+    (
+        "explanation.third_child_unknown",
+        "Un parto simple en una familia biparental solo da derecho a la ayuda a partir del tercer hijo, pero no se indicó total_children_after.",
+    ),
+    ("explanation.no_case", "Ningún supuesto de la normativa se aplica a {relationship} en la situación {situation}."),
+];
+
+const EN: &[(&str, &str)] = &[
+    ("case_a.description", "First-degree family care sick or accident victim"),
+    ("case_a.requirements", "The person must have been hospitalized and the care of the person must be continued"),
+    ("case_b.description", "Third child or more with newborn"),
+    (
+        "case_b.requirements",
+        "The number of children must be 3 or more, the ages of at least 2 of the minors must be less than 6, if there is disability greater than 33% then the limit is 9 years",
+    ),
+    ("case_b.not_enough_children", "The number of children must be 3 or more, must consult with administration"),
+    ("case_c.description", "Adoption or foster care"),
+    ("case_c.requirements", "In the foster care case the duration must be longer than one year"),
+    ("case_d.description", "Delivery, adoption or foster care multiple"),
+    ("case_e.description", "Single-parent family with newborn"),
+    ("case_e.requirements", "The single-parent status must be documented"),
+    ("no_case", "No case applies"),
+    ("invalid_relationship", "Not applicable by relationship (first degree)"),
+    ("next_steps.apply", "Submit the assistance application within 15 days of the start of the leave"),
+    ("next_steps.medical_certificate", "Provide a medical certificate of the hospitalization and of the need for continued care"),
+    ("next_steps.children_certificates", "Provide the family book or the birth certificates of all the children"),
+    ("next_steps.adoption_resolution", "Provide the adoption or foster care resolution"),
+    ("next_steps.single_parent_certificate", "Provide the document proving the single-parent status"),
+    ("next_steps.review_errors", "Review the requirements that are not met and evaluate again if your situation changes"),
+    ("next_steps.contact_administration", "Contact the administration if you believe your situation qualifies for the assistance"),
+    ("validation.unknown_field", "unknown field '{field}'"),
+    ("validation.invalid_value", "'{value}' is not a valid {field}. Valid values: {values}"),
+    ("validation.did_you_mean", "Did you mean '{suggestion}'?"),
+    ("validation.total_children_after", "total_children_after must be a whole number greater than or equal to 0, got {value}"),
+    ("validation.leave_end_required", "leave_end is required when leave_start is given"),
+    ("validation.leave_start_required", "leave_start is required when leave_end is given"),
+    ("validation.leave_period_combined", "leave_start and leave_end cannot be combined with leave_days or leave_months"),
+    ("validation.leave_end_before_start", "leave_end {end} is before leave_start {start}"),
+    ("validation.leave_too_long", "the leave from {start} to {end} is longer than the maximum of {max} days"),
+    ("validation.household_income", "household_income must be greater than or equal to 0, got {value}"),
+    ("validation.household_size", "household_size must be at least 1, got 0"),
+    ("validation.leave_percentage", "leave_percentage must be between 1 and 100, got {value}"),
+    ("validation.effective_date", "'{value}' is not a valid effective_date, expected format YYYY-MM-DD"),
+    ("validation.no_regulation", "No regulation in effect on {date}"),
+    ("validation.no_regulation_any_date", "No regulation in effect on any date"),
+    ("validation.unknown_tenant", "unknown tenant '{tenant_id}', check the tenant_id with the server operator"),
+    ("validation.unknown_case", "case '{case}' is not one of {cases}"),
+    ("warning.normalized", "{field} '{value}' was normalized to '{normalized}'"),
+    ("warning.interpreted", "{field} '{value}' was interpreted as '{normalized}'"),
+    ("warning.implausible_combination", "Implausible combination of relationship '{relationship}' and situation '{situation}': {reason}"),
+    (
+        "warning.reason.parent_of_child",
+        "the relationship must describe the applicant as the parent of the child, e.g. 'mother', 'father' or 'parent'",
+    ),
+    ("warning.reason.birth_not_foster_care", "a birth is not a foster care arrangement, use 'foster_care' or 'multiple_foster_care' instead"),
+    ("warning.children_ignored", "total_children_after ({children}) is ignored for the '{situation}' care situation"),
+    (
+        "warning.children_missing",
+        "total_children_after was not provided for situation '{situation}'; cases that depend on the number of children cannot be fully assessed",
+    ),
+    ("warning.inconsistent_output", "Inconsistent rule output: {mismatch}"),
+    ("warning.served_from_cache", "{error}; this result was served from the cache of a previous identical evaluation"),
+    ("warning.household_size_without_income", "household_size has no effect without household_income"),
+    ("warning.unknown_region", "Unknown region '{region}', national amounts apply"),
+    ("warning.disability_uplift_applied", "Disability uplift applied: monthly_benefit raised from {before} to {after}"),
+    ("warning.disability_uplift_situation", "has_disability only raises the benefit in {situations} situations"),
+    ("warning.disability_uplift_not_configured", "has_disability has no effect: this server configures no disability uplift"),
+    ("warning.benefit_capped", "monthly_benefit of {amount} capped at the maximum of {cap}"),
+    ("warning.unsupported_locale", "locale '{locale}' is not supported, expected one of {locales}; texts are in '{default}'"),
+    ("error.income_threshold", "Household income of {income} exceeds the {threshold} limit for case {case}"),
+    (
+        "error.per_member_income_threshold",
+        "Household income per member of {per_member} ({income} over {size} members) exceeds the {threshold} limit for case {case}",
+    ),
+    (
+        "explanation.relationship",
+        "'{relationship}' is not a first-degree relationship covered by the assistance, it must be one of {relationships}.",
+    ),
+    (
+        "explanation.third_child",
+        "A single birth in a two-parent family only qualifies from the third child, but total_children_after is {children}. \
+         Single-parent families and multiple births qualify regardless of the number of children.",
+    ),
+    (
+        "explanation.third_child_unknown",
+        "A single birth in a two-parent family only qualifies from the third child, but total_children_after was not given.",
+    ),
+    ("explanation.no_case", "No case of the regulation applies to a {relationship} in a {situation} situation."),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locale_from_tag() {
+        assert_eq!(Locale::from_tag("en"), Some(Locale::En));
+        assert_eq!(Locale::from_tag(" en-GB "), Some(Locale::En));
+        assert_eq!(Locale::from_tag("ES_es"), Some(Locale::Es));
+        assert_eq!(Locale::from_tag("fr"), None);
+        assert_eq!(Locale::from_tag(""), None);
+    }
+
+    #[test]
+    fn test_locale_from_accept_language() {
+        assert_eq!(locale_from_accept_language("en"), Some(Locale::En));
+        assert_eq!(locale_from_accept_language("en-US,en;q=0.9,es;q=0.8"), Some(Locale::En));
+        assert_eq!(locale_from_accept_language("fr-CH, fr;q=0.9, es;q=0.5, en;q=0.7"), Some(Locale::En));
+        assert_eq!(locale_from_accept_language("es, en"), Some(Locale::Es));
+        assert_eq!(locale_from_accept_language("en;q=0, es;q=0.1"), Some(Locale::Es));
+        assert_eq!(locale_from_accept_language("fr, de"), None);
+        assert_eq!(locale_from_accept_language("*"), None);
+    }
+
+    #[test]
+    fn test_catalogs_are_complete() {
+        for (_, key) in RULE_TEXT_KEYS {
+            assert!(ES.iter().any(|(entry, _)| entry == key), "'{}' has no Spanish text", key);
+            assert!(EN.iter().any(|(entry, _)| entry == key), "'{}' has no English text", key);
+        }
+        // The English catalog is the rule document itself
+        for (text, key) in RULE_TEXT_KEYS {
+            assert_eq!(message(Locale::En, key), Some(*text));
+        }
+        assert_eq!(message(Locale::En, "unknown"), None);
+
+        // Both catalogs hold the same keys, with the same placeholders
+        let placeholders = |text: &str| {
+            let mut names: Vec<String> = text.split('{').skip(1).filter_map(|rest| rest.split_once('}')).map(|(name, _)| name.to_string()).collect();
+            names.sort();
+            names
+        };
+        for (catalog, other) in [(ES, EN), (EN, ES)] {
+            for (key, text) in catalog {
+                let translation = other.iter().find(|(entry, _)| entry == key).map(|(_, text)| *text);
+                let translation = translation.unwrap_or_else(|| panic!("'{}' is only in one catalog", key));
+                assert_eq!(placeholders(text), placeholders(translation), "placeholders of '{}' differ", key);
+            }
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let value: &dyn fmt::Display = &"catalonia";
+        assert_eq!(render(Locale::En, "warning.unknown_region", &[("region", value)]), "Unknown region 'catalonia', national amounts apply");
+        assert_eq!(
+            render(Locale::Es, "warning.unknown_region", &[("region", value)]),
+            "Región 'catalonia' desconocida, se aplican los importes nacionales"
+        );
+        assert_eq!(render(Locale::Es, "unknown.key", &[]), "unknown.key");
+    }
+
+    #[test]
+    fn test_rule_text() {
+        assert_eq!(rule_text(Locale::Es, "Adoption or foster care"), "Adopción o acogimiento");
+        assert_eq!(rule_text(Locale::En, "Adoption or foster care"), "Adoption or foster care");
+        assert_eq!(rule_text(Locale::Es, "Custom rule text"), "Custom rule text");
+    }
+}
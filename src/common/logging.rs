@@ -0,0 +1,81 @@
+use std::fmt;
+use std::str::FromStr;
+use tracing::Subscriber;
+use tracing_subscriber::{Layer, fmt::MakeWriter, registry::LookupSpan};
+
+/// Format of the log lines, "pretty" (default) or "json"
+pub const LOG_FORMAT_ENV: &str = "LOG_FORMAT";
+
+/// How log events are written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable lines, as printed by the default `tracing_subscriber` formatter
+    #[default]
+    Pretty,
+    /// One JSON object per event, for log aggregators such as Loki or Elasticsearch
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "" | "pretty" => Ok(LogFormat::Pretty),
+            "json" => Ok(LogFormat::Json),
+            other => Err(format!("invalid LOG_FORMAT '{}': expected 'pretty' or 'json'", other)),
+        }
+    }
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogFormat::Pretty => write!(f, "pretty"),
+            LogFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+impl LogFormat {
+    /// Format from `LOG_FORMAT`, pretty when unset
+    #[allow(dead_code)] // Used by the binaries without command-line arguments
+    pub fn from_env() -> anyhow::Result<Self> {
+        std::env::var(LOG_FORMAT_ENV)
+            .unwrap_or_default()
+            .parse()
+            .map_err(|e: String| anyhow::anyhow!(e))
+    }
+}
+
+/// Formatting layer writing every event to `writer` in `format`.
+/// `ansi` only applies to the pretty format, JSON lines are never colored
+pub fn fmt_layer<S, W>(format: LogFormat, writer: W, ansi: bool) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+    W: for<'writer> MakeWriter<'writer> + Send + Sync + 'static,
+{
+    match format {
+        LogFormat::Pretty => tracing_subscriber::fmt::layer().with_writer(writer).with_ansi(ansi).boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .with_current_span(true)
+            .with_span_list(false)
+            .with_writer(writer)
+            .boxed(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_format_parsing() {
+        assert_eq!("".parse::<LogFormat>(), Ok(LogFormat::Pretty));
+        assert_eq!("pretty".parse::<LogFormat>(), Ok(LogFormat::Pretty));
+        assert_eq!("JSON".parse::<LogFormat>(), Ok(LogFormat::Json));
+        assert!("logfmt".parse::<LogFormat>().is_err());
+        assert_eq!(LogFormat::Json.to_string().parse::<LogFormat>(), Ok(LogFormat::Json));
+    }
+}
@@ -1,41 +1,231 @@
 use once_cell::sync::Lazy;
-use prometheus::{Counter, Gauge, Histogram, HistogramOpts, Opts, Registry};
+#[cfg(feature = "metrics")]
+use prometheus::{Counter, CounterVec, GaugeVec, HistogramOpts, HistogramVec, Opts, Registry};
+use std::sync::Arc;
+use std::time::Instant;
+#[cfg(feature = "metrics")]
+use std::time::{SystemTime, UNIX_EPOCH};
 
-pub static METRICS: Lazy<EligibilityMetrics> = Lazy::new(|| EligibilityMetrics::new());
+#[cfg(feature = "metrics")]
+pub use prometheus::Gauge;
+#[cfg(not(feature = "metrics"))]
+pub use noop::{EligibilityMetrics, Gauge, RequestTimer};
 
+/// Crate version, as declared in Cargo.toml
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Short git SHA the binary was built from, set by build.rs
+pub const GIT_SHA: &str = env!("GIT_SHA");
+/// Version of the compiler used to build the binary, set by build.rs
+#[cfg_attr(not(feature = "metrics"), allow(dead_code))] // Only exported in build_info
+pub const RUSTC_VERSION: &str = env!("RUSTC_VERSION");
+
+/// Instant the process started, forced by the servers at startup
+pub static START_TIME: Lazy<Instant> = Lazy::new(Instant::now);
+
+/// Process-wide metrics, used by default and served by the /metrics endpoints.
+/// Invalid `REQUEST_DURATION_BUCKETS` fall back to the defaults, the binaries reject them at startup
+pub static METRICS: Lazy<Arc<EligibilityMetrics>> = Lazy::new(|| {
+    let buckets = request_duration_buckets().unwrap_or_else(|_| DEFAULT_DURATION_BUCKETS.to_vec());
+    Arc::new(EligibilityMetrics::with_buckets(buckets))
+});
+
+/// Comma-separated upper bounds, in seconds, of the request duration histogram buckets
+pub const REQUEST_DURATION_BUCKETS_ENV: &str = "REQUEST_DURATION_BUCKETS";
+
+/// Request duration buckets used unless `REQUEST_DURATION_BUCKETS` is set
+pub const DEFAULT_DURATION_BUCKETS: [f64; 9] = [0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 10.0];
+
+/// Buckets from `REQUEST_DURATION_BUCKETS`, the defaults when it is unset
+pub fn request_duration_buckets() -> Result<Vec<f64>, String> {
+    match std::env::var(REQUEST_DURATION_BUCKETS_ENV) {
+        Ok(value) if !value.trim().is_empty() => parse_buckets(&value),
+        _ => Ok(DEFAULT_DURATION_BUCKETS.to_vec()),
+    }
+}
+
+/// Parses comma-separated bucket bounds, which must be positive and strictly increasing
+pub fn parse_buckets(value: &str) -> Result<Vec<f64>, String> {
+    let invalid = |reason: String| format!("invalid {} '{}': {}", REQUEST_DURATION_BUCKETS_ENV, value, reason);
+    let mut buckets: Vec<f64> = Vec::new();
+    for bound in value.split(',').map(str::trim) {
+        let bound: f64 = bound
+            .parse()
+            .map_err(|_| invalid(format!("'{}' is not a number of seconds", bound)))?;
+        if !bound.is_finite() || bound <= 0.0 {
+            return Err(invalid(format!("bucket {} must be positive", bound)));
+        }
+        if let Some(previous) = buckets.last()
+            && bound <= *previous
+        {
+            return Err(invalid(format!("bucket {} must be greater than {}", bound, previous)));
+        }
+        buckets.push(bound);
+    }
+    Ok(buckets)
+}
+
+#[cfg(feature = "metrics")]
+#[derive(Debug)]
 pub struct EligibilityMetrics {
     #[allow(dead_code)] // Used internally by gather() method
     pub registry: Registry,
-    pub requests_total: Counter,
-    pub errors_total: Counter,
-    pub request_duration: Histogram,
+    pub requests_total: CounterVec,
+    pub errors_total: CounterVec,
+    pub errors_by_type_total: CounterVec,
+    pub rejected_total: Counter,
+    pub cache_hits_total: Counter,
+    pub cache_misses_total: Counter,
+    pub served_from_cache_on_error_total: Counter,
+    pub inconsistent_outputs_total: CounterVec,
+    pub retries_total: Counter,
+    pub panics_total: Counter,
+    #[cfg_attr(not(feature = "audit"), allow(dead_code))] // Only counted by the audit log
+    pub audit_failures_total: Counter,
+    pub webhook_failures_total: Counter,
+    pub event_failures_total: Counter,
+    pub feedback_total: CounterVec,
+    pub cases_total: CounterVec,
+    pub request_duration: HistogramVec,
     pub active_requests: Gauge,
+    #[allow(dead_code)] // Only set by the streamable-http server
+    pub active_sessions: Gauge,
+    #[allow(dead_code)] // Set once at creation, only read through gather()
+    pub build_info: GaugeVec,
+    pub rules_info: GaugeVec,
+    pub uptime_seconds: Gauge,
+    pub last_evaluation_timestamp: Gauge,
 }
 
+#[cfg(feature = "metrics")]
 impl EligibilityMetrics {
-    fn new() -> Self {
+    /// Creates a set of metrics with its own registry, independent of `METRICS`
+    #[allow(dead_code)] // Used by tests to get isolated metrics
+    pub fn new() -> Self {
+        Self::with_buckets(DEFAULT_DURATION_BUCKETS.to_vec())
+    }
+
+    /// Same as `new`, with `buckets` as the upper bounds of the request duration histogram
+    pub fn with_buckets(buckets: Vec<f64>) -> Self {
         let registry = Registry::new();
 
-        let requests_total = Counter::with_opts(
+        let requests_total = CounterVec::new(
             Opts::new(
                 "eligibility_requests_total",
-                "Total number of unpaid leave eligibility evaluation requests"
-            )
+                "Total number of unpaid leave eligibility evaluation requests by tool and tenant"
+            ),
+            &["tool", "tenant"]
         ).unwrap();
 
-        let errors_total = Counter::with_opts(
+        let errors_total = CounterVec::new(
             Opts::new(
                 "eligibility_errors_total",
-                "Total number of errors in unpaid leave eligibility evaluations"
+                "Total number of errors in unpaid leave eligibility evaluations by tool and tenant"
+            ),
+            &["tool", "tenant"]
+        ).unwrap();
+
+        let errors_by_type_total = CounterVec::new(
+            Opts::new(
+                "eligibility_errors_by_type_total",
+                "Total number of errors in unpaid leave eligibility evaluations by error type and tool"
+            ),
+            &["error_type", "tool"]
+        ).unwrap();
+
+        let rejected_total = Counter::with_opts(
+            Opts::new(
+                "eligibility_rejected_total",
+                "Total number of unpaid leave eligibility evaluations rejected because the concurrency limit was reached"
+            )
+        ).unwrap();
+
+        let cache_hits_total = Counter::with_opts(
+            Opts::new(
+                "eligibility_cache_hits_total",
+                "Total number of unpaid leave eligibility evaluations answered from the result cache"
+            )
+        ).unwrap();
+
+        let cache_misses_total = Counter::with_opts(
+            Opts::new(
+                "eligibility_cache_misses_total",
+                "Total number of unpaid leave eligibility evaluations not found in the result cache"
+            )
+        ).unwrap();
+
+        let served_from_cache_on_error_total = Counter::with_opts(
+            Opts::new(
+                "eligibility_served_from_cache_on_error_total",
+                "Total number of unpaid leave eligibility evaluations answered from the result cache because the decision engine failed"
+            )
+        ).unwrap();
+
+        let retries_total = Counter::with_opts(
+            Opts::new(
+                "eligibility_evaluation_retries_total",
+                "Total number of decision engine evaluations attempted again after a transient error"
+            )
+        ).unwrap();
+
+        let panics_total = Counter::with_opts(
+            Opts::new(
+                "eligibility_panics_total",
+                "Total number of unpaid leave eligibility evaluations that panicked"
+            )
+        ).unwrap();
+
+        let audit_failures_total = Counter::with_opts(
+            Opts::new(
+                "eligibility_audit_failures_total",
+                "Total number of unpaid leave eligibility evaluations that could not be written to the audit log"
+            )
+        ).unwrap();
+
+        let webhook_failures_total = Counter::with_opts(
+            Opts::new(
+                "eligibility_webhook_failures_total",
+                "Total number of unpaid leave eligibility evaluations that could not be delivered to the webhook"
+            )
+        ).unwrap();
+
+        let event_failures_total = Counter::with_opts(
+            Opts::new(
+                "eligibility_event_failures_total",
+                "Total number of unpaid leave eligibility evaluation events that could not be queued or delivered to Kafka"
             )
         ).unwrap();
 
-        let request_duration = Histogram::with_opts(
+        let inconsistent_outputs_total = CounterVec::new(
+            Opts::new(
+                "eligibility_inconsistent_outputs_total",
+                "Total number of rule outputs marked potentially eligible without a case by rules version"
+            ),
+            &["rules_version"]
+        ).unwrap();
+
+        let feedback_total = CounterVec::new(
+            Opts::new(
+                "eligibility_feedback_total",
+                "Total number of real-world outcomes reported for unpaid leave eligibility evaluations by outcome"
+            ),
+            &["outcome"]
+        ).unwrap();
+
+        let cases_total = CounterVec::new(
+            Opts::new(
+                "eligibility_cases_total",
+                "Total number of successful unpaid leave eligibility evaluations by resulting case and tenant"
+            ),
+            &["case", "tenant"]
+        ).unwrap();
+
+        let request_duration = HistogramVec::new(
             HistogramOpts::new(
                 "eligibility_request_duration_seconds",
-                "Duration of unpaid leave eligibility evaluation requests in seconds"
+                "Duration of unpaid leave eligibility evaluation requests in seconds by tool and tenant"
             )
-            .buckets(vec![0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 10.0])
+            .buckets(buckets),
+            &["tool", "tenant"]
         ).unwrap();
 
         let active_requests = Gauge::with_opts(
@@ -45,59 +235,608 @@ impl EligibilityMetrics {
             )
         ).unwrap();
 
+        let active_sessions = Gauge::with_opts(
+            Opts::new(
+                "eligibility_active_sessions",
+                "Number of open MCP sessions of the streamable-http server"
+            )
+        ).unwrap();
+
+        let build_info = GaugeVec::new(
+            Opts::new(
+                "eligibility_build_info",
+                "Build information of the running binary, the value is always 1"
+            ),
+            &["version", "git_sha", "rustc_version"]
+        ).unwrap();
+        build_info.with_label_values(&[VERSION, GIT_SHA, RUSTC_VERSION]).set(1.0);
+
+        let rules_info = GaugeVec::new(
+            Opts::new(
+                "eligibility_rules_info",
+                "Rule documents loaded by the server by tenant, with their version and SHA-256 checksum, the value is always 1"
+            ),
+            &["tenant", "rules_version", "rules_checksum"]
+        ).unwrap();
+
+        // Refreshed on every gather() from START_TIME
+        Lazy::force(&START_TIME);
+        let uptime_seconds = Gauge::with_opts(
+            Opts::new(
+                "eligibility_uptime_seconds",
+                "Number of seconds since the server process started"
+            )
+        ).unwrap();
+
+        let last_evaluation_timestamp = Gauge::with_opts(
+            Opts::new(
+                "eligibility_last_evaluation_timestamp_seconds",
+                "Unix timestamp of the end of the last unpaid leave eligibility evaluation"
+            )
+        ).unwrap();
+
         registry.register(Box::new(requests_total.clone())).unwrap();
         registry.register(Box::new(errors_total.clone())).unwrap();
+        registry.register(Box::new(errors_by_type_total.clone())).unwrap();
+        registry.register(Box::new(rejected_total.clone())).unwrap();
+        registry.register(Box::new(cache_hits_total.clone())).unwrap();
+        registry.register(Box::new(cache_misses_total.clone())).unwrap();
+        registry.register(Box::new(served_from_cache_on_error_total.clone())).unwrap();
+        registry.register(Box::new(inconsistent_outputs_total.clone())).unwrap();
+        registry.register(Box::new(retries_total.clone())).unwrap();
+        registry.register(Box::new(panics_total.clone())).unwrap();
+        registry.register(Box::new(audit_failures_total.clone())).unwrap();
+        registry.register(Box::new(webhook_failures_total.clone())).unwrap();
+        registry.register(Box::new(event_failures_total.clone())).unwrap();
+        registry.register(Box::new(feedback_total.clone())).unwrap();
+        registry.register(Box::new(cases_total.clone())).unwrap();
         registry.register(Box::new(request_duration.clone())).unwrap();
         registry.register(Box::new(active_requests.clone())).unwrap();
+        registry.register(Box::new(active_sessions.clone())).unwrap();
+        registry.register(Box::new(build_info.clone())).unwrap();
+        registry.register(Box::new(rules_info.clone())).unwrap();
+        registry.register(Box::new(uptime_seconds.clone())).unwrap();
+        registry.register(Box::new(last_evaluation_timestamp.clone())).unwrap();
 
         EligibilityMetrics {
             registry,
             requests_total,
             errors_total,
+            errors_by_type_total,
+            rejected_total,
+            cache_hits_total,
+            cache_misses_total,
+            served_from_cache_on_error_total,
+            inconsistent_outputs_total,
+            retries_total,
+            panics_total,
+            audit_failures_total,
+            webhook_failures_total,
+            event_failures_total,
+            feedback_total,
+            cases_total,
             request_duration,
             active_requests,
+            active_sessions,
+            build_info,
+            rules_info,
+            uptime_seconds,
+            last_evaluation_timestamp,
         }
     }
 
+    /// Encodes all registered metrics in the Prometheus text format
     #[allow(dead_code)] // Used by HTTP metrics endpoints
-    pub fn gather(&self) -> String {
+    pub fn gather(&self) -> Result<String, prometheus::Error> {
         use prometheus::{Encoder, TextEncoder};
+        self.uptime_seconds.set(START_TIME.elapsed().as_secs_f64());
         let encoder = TextEncoder::new();
         let metric_families = self.registry.gather();
         let mut buffer = vec![];
-        encoder.encode(&metric_families, &mut buffer).unwrap();
-        String::from_utf8(buffer).unwrap()
+        encoder.encode(&metric_families, &mut buffer)?;
+        String::from_utf8(buffer).map_err(|e| prometheus::Error::Msg(e.to_string()))
+    }
+
+    /// All registered metrics as a JSON object keyed by metric name. Each entry has the type,
+    /// the help text and one sample per label set; histograms report count, sum and cumulative buckets
+    #[allow(dead_code)] // Used by HTTP metrics endpoints
+    pub fn gather_json(&self) -> serde_json::Value {
+        use prometheus::proto::MetricType;
+        use serde_json::{Map, Value, json};
+
+        self.uptime_seconds.set(START_TIME.elapsed().as_secs_f64());
+        let mut families = Map::new();
+        for family in self.registry.gather() {
+            let samples: Vec<Value> = family
+                .get_metric()
+                .iter()
+                .map(|metric| {
+                    let labels: Map<String, Value> = metric
+                        .get_label()
+                        .iter()
+                        .map(|label| (label.get_name().to_string(), Value::from(label.get_value())))
+                        .collect();
+                    match family.get_field_type() {
+                        MetricType::COUNTER => json!({ "labels": labels, "value": metric.get_counter().get_value() }),
+                        MetricType::GAUGE => json!({ "labels": labels, "value": metric.get_gauge().get_value() }),
+                        MetricType::HISTOGRAM => {
+                            let histogram = metric.get_histogram();
+                            let buckets: Vec<Value> = histogram
+                                .get_bucket()
+                                .iter()
+                                .map(|bucket| json!({ "le": bucket.get_upper_bound(), "count": bucket.get_cumulative_count() }))
+                                .collect();
+                            json!({
+                                "labels": labels,
+                                "count": histogram.get_sample_count(),
+                                "sum": histogram.get_sample_sum(),
+                                "buckets": buckets,
+                            })
+                        }
+                        _ => json!({ "labels": labels, "value": metric.get_untyped().get_value() }),
+                    }
+                })
+                .collect();
+
+            let metric_type = match family.get_field_type() {
+                MetricType::COUNTER => "counter",
+                MetricType::GAUGE => "gauge",
+                MetricType::HISTOGRAM => "histogram",
+                MetricType::SUMMARY => "summary",
+                MetricType::UNTYPED => "untyped",
+            };
+            families.insert(
+                family.get_name().to_string(),
+                json!({ "type": metric_type, "help": family.get_help(), "metrics": samples }),
+            );
+        }
+        Value::Object(families)
     }
 }
 
 /// Timer struct to automatically measure request duration and track active requests
+#[cfg(feature = "metrics")]
 pub struct RequestTimer {
     timer: Option<prometheus::HistogramTimer>,
+    active_requests: Gauge,
 }
 
+#[cfg(feature = "metrics")]
 impl RequestTimer {
-    pub fn new() -> Self {
-        METRICS.active_requests.inc();
-        let timer = METRICS.request_duration.start_timer();
-        Self { timer: Some(timer) }
+    pub fn new(metrics: &EligibilityMetrics, labels: &MetricLabels) -> Self {
+        metrics.active_requests.inc();
+        let timer = metrics.request_duration.with_label_values(&labels.values()).start_timer();
+        Self { timer: Some(timer), active_requests: metrics.active_requests.clone() }
     }
 }
 
+#[cfg(feature = "metrics")]
 impl Drop for RequestTimer {
     fn drop(&mut self) {
         if let Some(timer) = self.timer.take() {
             timer.observe_duration();
         }
-        METRICS.active_requests.dec();
+        self.active_requests.dec();
     }
 }
 
-/// Helper function to increment request counter
-pub fn increment_requests() {
-    METRICS.requests_total.inc();
+/// Cases reported with their own label, any other outcome is counted as "none"
+#[cfg(feature = "metrics")]
+const CASE_LABELS: &[&str] = &["A", "B", "C", "D", "E"];
+
+/// Tenant label of evaluations under the shared rules
+pub const DEFAULT_TENANT_LABEL: &str = "default";
+
+/// Tenant label of evaluations for a tenant the server does not know
+pub const UNKNOWN_TENANT_LABEL: &str = "unknown";
+
+/// Tool label of requests that do not come from a specific MCP tool, e.g. the REST endpoint
+pub const DEFAULT_TOOL_LABEL: &str = "evaluate_unpaid_leave_eligibility";
+
+/// Tool and tenant a request is counted under. The default labels are the single-tool,
+/// shared-rules deployment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MetricLabels<'a> {
+    pub tool: &'a str,
+    pub tenant: Option<&'a str>,
 }
 
-/// Helper function to increment error counter
-pub fn increment_errors() {
-    METRICS.errors_total.inc();
+impl<'a> MetricLabels<'a> {
+    /// Labels of `tool` under the shared rules
+    pub fn new(tool: &'a str) -> Self {
+        Self { tool, tenant: None }
+    }
+
+    /// Same labels under the rules of `tenant`, the shared ones when `None`
+    pub fn with_tenant(mut self, tenant: Option<&'a str>) -> Self {
+        self.tenant = tenant;
+        self
+    }
+
+    /// Label values in the `["tool", "tenant"]` order of the labeled metrics
+    #[cfg(feature = "metrics")]
+    fn values(&self) -> [&'a str; 2] {
+        [self.tool, self.tenant.unwrap_or(DEFAULT_TENANT_LABEL)]
+    }
+}
+
+impl Default for MetricLabels<'_> {
+    fn default() -> Self {
+        Self::new(DEFAULT_TOOL_LABEL)
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl EligibilityMetrics {
+    /// Increments the request counter of `labels`
+    pub fn increment_requests(&self, labels: &MetricLabels) {
+        self.requests_total.with_label_values(&labels.values()).inc();
+    }
+
+    /// Increments the counter of evaluations rejected by the concurrency limit
+    pub fn increment_rejected(&self) {
+        self.rejected_total.inc();
+    }
+
+    /// Increments the counter of results served from the result cache
+    pub fn increment_cache_hits(&self) {
+        self.cache_hits_total.inc();
+    }
+
+    /// Increments the counter of results not found in the result cache
+    pub fn increment_cache_misses(&self) {
+        self.cache_misses_total.inc();
+    }
+
+    /// Increments the counter of results served from the result cache after an engine failure
+    pub fn increment_served_from_cache_on_error(&self) {
+        self.served_from_cache_on_error_total.inc();
+    }
+
+    /// Increments the counter of rule outputs marked potentially eligible without a case
+    pub fn increment_inconsistent_outputs(&self, rules_version: &str) {
+        self.inconsistent_outputs_total.with_label_values(&[rules_version]).inc();
+    }
+
+    /// Increments the counter of evaluations attempted again after a transient error
+    pub fn increment_retries(&self) {
+        self.retries_total.inc();
+    }
+
+    /// Increments the counter of evaluations that panicked
+    pub fn increment_panics(&self) {
+        self.panics_total.inc();
+    }
+
+    /// Increments the counter of evaluations that could not be written to the audit log
+    #[cfg_attr(not(feature = "audit"), allow(dead_code))]
+    pub fn increment_audit_failures(&self) {
+        self.audit_failures_total.inc();
+    }
+
+    /// Increments the counter of evaluations that could not be delivered to the webhook
+    pub fn increment_webhook_failures(&self) {
+        self.webhook_failures_total.inc();
+    }
+
+    /// Increments the counter of evaluation events that could not be queued or delivered
+    pub fn increment_event_failures(&self) {
+        self.event_failures_total.inc();
+    }
+
+    /// Increments the counter of feedback submissions with the reported `outcome`
+    pub fn increment_feedback(&self, outcome: &str) {
+        self.feedback_total.with_label_values(&[outcome]).inc();
+    }
+
+    /// Increments the error counters of `labels`.
+    /// `error_type` is one of "validation", "engine", "serialization", "join", "panic", "rules", "busy" or "timeout"
+    pub fn increment_errors(&self, error_type: &str, labels: &MetricLabels) {
+        self.errors_total.with_label_values(&labels.values()).inc();
+        self.errors_by_type_total.with_label_values(&[error_type, labels.tool]).inc();
+    }
+
+    /// Exports the rule document `rules_version` with `rules_checksum` as loaded for `tenant`,
+    /// the shared rules when `None`
+    pub fn set_rules_info(&self, rules_version: &str, rules_checksum: &str, tenant: Option<&str>) {
+        self.rules_info
+            .with_label_values(&[tenant.unwrap_or(DEFAULT_TENANT_LABEL), rules_version, rules_checksum])
+            .set(1.0);
+    }
+
+    /// Forgets the rule documents exported so far, before the loaded ones are exported again
+    pub fn reset_rules_info(&self) {
+        self.rules_info.reset();
+    }
+
+    /// Records the current time as the end of the last evaluation
+    pub fn record_evaluation(&self) {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        self.last_evaluation_timestamp.set(now.as_secs_f64());
+    }
+
+    /// Increments the counter of the case resulting from an evaluation under the rules of `tenant`,
+    /// the shared rules when `None`
+    pub fn increment_case(&self, case: &str, tenant: Option<&str>) {
+        let label = if CASE_LABELS.contains(&case) { case } else { "none" };
+        self.cases_total.with_label_values(&[label, tenant.unwrap_or(DEFAULT_TENANT_LABEL)]).inc();
+    }
+
+    /// Zeroes the counters and gauges and drops every labeled series, so a test can assert exact
+    /// values on metrics it shares with other code. `build_info` is kept, it is set once at creation
+    #[cfg(test)]
+    pub fn reset(&self) {
+        for counter in [
+            &self.rejected_total,
+            &self.cache_hits_total,
+            &self.cache_misses_total,
+            &self.served_from_cache_on_error_total,
+            &self.retries_total,
+            &self.panics_total,
+            &self.audit_failures_total,
+            &self.webhook_failures_total,
+            &self.event_failures_total,
+        ] {
+            counter.reset();
+        }
+        for counters in [
+            &self.requests_total,
+            &self.errors_total,
+            &self.errors_by_type_total,
+            &self.inconsistent_outputs_total,
+            &self.feedback_total,
+            &self.cases_total,
+        ] {
+            counters.reset();
+        }
+        self.request_duration.reset();
+        self.rules_info.reset();
+        for gauge in [&self.active_requests, &self.active_sessions, &self.uptime_seconds, &self.last_evaluation_timestamp] {
+            gauge.set(0.0);
+        }
+    }
+}
+
+/// Stand-ins used when the `metrics` feature is disabled, with the API of the Prometheus metrics.
+/// Nothing is recorded but the gauges the servers read back themselves, such as the number of
+/// in-flight evaluations logged while draining
+#[cfg(not(feature = "metrics"))]
+mod noop {
+    use super::MetricLabels;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    /// Gauge held in memory only, shared by its clones
+    #[derive(Debug, Clone, Default)]
+    pub struct Gauge(Arc<AtomicU64>);
+
+    impl Gauge {
+        /// Same signature as the Prometheus gauge, the name and help are not kept
+        #[allow(dead_code)]
+        pub fn new(_name: &str, _help: &str) -> Result<Self, std::convert::Infallible> {
+            Ok(Self::default())
+        }
+
+        pub fn get(&self) -> f64 {
+            f64::from_bits(self.0.load(Ordering::Relaxed))
+        }
+
+        #[allow(dead_code)]
+        pub fn set(&self, value: f64) {
+            self.0.store(value.to_bits(), Ordering::Relaxed);
+        }
+
+        pub fn inc(&self) {
+            self.add(1.0);
+        }
+
+        pub fn dec(&self) {
+            self.add(-1.0);
+        }
+
+        fn add(&self, delta: f64) {
+            let _ = self.0.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| Some((f64::from_bits(bits) + delta).to_bits()));
+        }
+    }
+
+    #[derive(Debug, Default)]
+    pub struct EligibilityMetrics {
+        pub active_requests: Gauge,
+        #[allow(dead_code)] // Only set by the streamable-http server
+        pub active_sessions: Gauge,
+    }
+
+    impl EligibilityMetrics {
+        #[allow(dead_code)] // Used by tests to get isolated metrics
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn with_buckets(_buckets: Vec<f64>) -> Self {
+            Self::default()
+        }
+
+        pub fn increment_requests(&self, _labels: &MetricLabels) {}
+
+        pub fn increment_rejected(&self) {}
+
+        pub fn increment_cache_hits(&self) {}
+
+        pub fn increment_cache_misses(&self) {}
+
+        pub fn increment_served_from_cache_on_error(&self) {}
+
+        pub fn increment_inconsistent_outputs(&self, _rules_version: &str) {}
+
+        pub fn increment_retries(&self) {}
+
+        pub fn increment_panics(&self) {}
+
+        #[cfg_attr(not(feature = "audit"), allow(dead_code))]
+        pub fn increment_audit_failures(&self) {}
+
+        pub fn increment_webhook_failures(&self) {}
+
+        pub fn increment_event_failures(&self) {}
+
+        pub fn increment_feedback(&self, _outcome: &str) {}
+
+        pub fn increment_errors(&self, _error_type: &str, _labels: &MetricLabels) {}
+
+        pub fn set_rules_info(&self, _rules_version: &str, _rules_checksum: &str, _tenant: Option<&str>) {}
+
+        pub fn reset_rules_info(&self) {}
+
+        pub fn record_evaluation(&self) {}
+
+        pub fn increment_case(&self, _case: &str, _tenant: Option<&str>) {}
+    }
+
+    /// Tracks the evaluation as in flight until dropped
+    pub struct RequestTimer {
+        active_requests: Gauge,
+    }
+
+    impl RequestTimer {
+        pub fn new(metrics: &EligibilityMetrics, _labels: &MetricLabels) -> Self {
+            metrics.active_requests.inc();
+            Self { active_requests: metrics.active_requests.clone() }
+        }
+    }
+
+    impl Drop for RequestTimer {
+        fn drop(&mut self) {
+            self.active_requests.dec();
+        }
+    }
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_info_is_exported() {
+        let metrics = EligibilityMetrics::new();
+        let output = metrics.gather().unwrap();
+
+        let line = output
+            .lines()
+            .find(|line| line.starts_with("eligibility_build_info{"))
+            .expect("build_info should be exported");
+        assert!(line.contains(&format!("version=\"{}\"", VERSION)));
+        assert!(line.contains(&format!("git_sha=\"{}\"", GIT_SHA)));
+        assert!(line.ends_with(" 1"));
+    }
+
+    #[test]
+    fn test_last_evaluation_timestamp_is_recorded() {
+        let metrics = EligibilityMetrics::new();
+        assert_eq!(metrics.last_evaluation_timestamp.get(), 0.0);
+
+        metrics.record_evaluation();
+        assert!(metrics.last_evaluation_timestamp.get() > 0.0);
+        assert!(metrics.gather().unwrap().contains("eligibility_uptime_seconds"));
+    }
+
+    #[test]
+    fn test_gather_populated_registry() {
+        let metrics = EligibilityMetrics::new();
+        metrics.increment_requests(&MetricLabels::default());
+        metrics.increment_case("A", None);
+        metrics.increment_case("B", Some("acme"));
+        metrics.increment_errors("validation", &MetricLabels::default());
+        {
+            let _timer = RequestTimer::new(&metrics, &MetricLabels::default());
+        }
+
+        let output = metrics.gather().expect("metrics should encode");
+        assert!(output.contains("eligibility_requests_total{tenant=\"default\",tool=\"evaluate_unpaid_leave_eligibility\"} 1"));
+        assert!(output.contains("eligibility_cases_total{case=\"A\",tenant=\"default\"} 1"));
+        assert!(output.contains("eligibility_cases_total{case=\"B\",tenant=\"acme\"} 1"));
+        assert!(output.contains("eligibility_errors_by_type_total{error_type=\"validation\",tool=\"evaluate_unpaid_leave_eligibility\"} 1"));
+        assert!(output.contains("eligibility_request_duration_seconds_count{tenant=\"default\",tool=\"evaluate_unpaid_leave_eligibility\"} 1"));
+        assert!(output.contains("eligibility_active_requests 0"));
+    }
+
+    #[test]
+    fn test_labeled_series_are_gathered() {
+        let metrics = EligibilityMetrics::new();
+        let batch = MetricLabels::new("evaluate_unpaid_leave_batch").with_tenant(Some("acme"));
+        metrics.increment_requests(&batch);
+        metrics.increment_requests(&batch);
+        metrics.increment_requests(&MetricLabels::new("estimate_unpaid_leave"));
+        metrics.increment_errors("engine", &batch);
+        {
+            let _timer = RequestTimer::new(&metrics, &batch);
+        }
+
+        let output = metrics.gather().unwrap();
+        assert!(output.contains("eligibility_requests_total{tenant=\"acme\",tool=\"evaluate_unpaid_leave_batch\"} 2"));
+        assert!(output.contains("eligibility_requests_total{tenant=\"default\",tool=\"estimate_unpaid_leave\"} 1"));
+        assert!(output.contains("eligibility_errors_total{tenant=\"acme\",tool=\"evaluate_unpaid_leave_batch\"} 1"));
+        assert!(output.contains("eligibility_errors_by_type_total{error_type=\"engine\",tool=\"evaluate_unpaid_leave_batch\"} 1"));
+        assert!(output.contains("eligibility_request_duration_seconds_count{tenant=\"acme\",tool=\"evaluate_unpaid_leave_batch\"} 1"));
+        assert!(!output.contains("eligibility_errors_total{tenant=\"default\""));
+    }
+
+    #[test]
+    fn test_reset() {
+        let metrics = EligibilityMetrics::new();
+        metrics.increment_requests(&MetricLabels::default());
+        metrics.increment_case("A", None);
+        metrics.increment_cache_hits();
+        metrics.increment_webhook_failures();
+        metrics.set_rules_info("2024.1", "abc123", None);
+        metrics.record_evaluation();
+
+        metrics.reset();
+        assert_eq!(metrics.cache_hits_total.get(), 0.0);
+        assert_eq!(metrics.webhook_failures_total.get(), 0.0);
+        assert_eq!(metrics.last_evaluation_timestamp.get(), 0.0);
+        let output = metrics.gather().unwrap();
+        assert!(!output.contains("eligibility_requests_total{"));
+        assert!(!output.contains("eligibility_cases_total{"));
+        assert!(!output.contains("eligibility_rules_info{"));
+        assert!(output.contains("eligibility_build_info{"));
+
+        // Counts start again from zero
+        metrics.increment_requests(&MetricLabels::default());
+        assert!(metrics.gather().unwrap().contains("eligibility_requests_total{tenant=\"default\",tool=\"evaluate_unpaid_leave_eligibility\"} 1"));
+    }
+
+    #[test]
+    fn test_parse_buckets() {
+        assert_eq!(parse_buckets("0.0005, 0.001,0.0025").unwrap(), vec![0.0005, 0.001, 0.0025]);
+        assert!(parse_buckets("0.1,fast").unwrap_err().contains("'fast' is not a number of seconds"));
+        assert!(parse_buckets("0,0.1").unwrap_err().contains("must be positive"));
+        assert!(parse_buckets("0.1,0.1").unwrap_err().contains("must be greater than 0.1"));
+        assert!(parse_buckets("1,0.5").is_err());
+        assert!(parse_buckets("").is_err());
+
+        let metrics = EligibilityMetrics::with_buckets(vec![0.0005, 0.001]);
+        metrics.request_duration.with_label_values(&MetricLabels::default().values()).observe(0.0007);
+        let duration = &metrics.gather_json()["eligibility_request_duration_seconds"]["metrics"][0];
+        assert_eq!(duration["buckets"][1]["count"], 1);
+    }
+
+    #[test]
+    fn test_gather_json() {
+        let metrics = EligibilityMetrics::new();
+        metrics.increment_requests(&MetricLabels::default());
+        metrics.increment_case("A", None);
+        {
+            let _timer = RequestTimer::new(&metrics, &MetricLabels::default());
+        }
+
+        let output = metrics.gather_json();
+        assert_eq!(output["eligibility_requests_total"]["type"], "counter");
+        assert_eq!(output["eligibility_requests_total"]["metrics"][0]["value"], 1.0);
+        assert_eq!(output["eligibility_requests_total"]["metrics"][0]["labels"]["tool"], DEFAULT_TOOL_LABEL);
+        assert_eq!(output["eligibility_cases_total"]["metrics"][0]["labels"]["case"], "A");
+        assert_eq!(output["eligibility_cases_total"]["metrics"][0]["labels"]["tenant"], DEFAULT_TENANT_LABEL);
+        let duration = &output["eligibility_request_duration_seconds"]["metrics"][0];
+        assert_eq!(duration["count"], 1);
+        assert_eq!(duration["buckets"].as_array().unwrap().len(), 9);
+    }
 }
@@ -1,2 +1,17 @@
+pub mod audit;
+#[allow(dead_code)] // Only used by the stdio server
+pub mod bulk;
+pub mod config;
 pub mod eligibility_engine;
+pub mod events;
+#[allow(dead_code)] // Only used by the HTTP servers
+pub mod http;
+pub mod i18n;
+pub mod logging;
 pub mod metrics;
+pub mod money;
+pub mod prompts;
+#[allow(dead_code)] // Only used by the streamable-http server
+pub mod sessions;
+pub mod telemetry;
+pub mod webhook;
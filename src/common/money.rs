@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+
+use rmcp::schemars;
+
+/// Locales with a known number format
+pub const SUPPORTED_LOCALES: &[&str] = &["es-ES", "en-US", "en-GB"];
+
+/// Amount of money ready to be displayed
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct Money {
+    #[schemars(description = "Amount in the currency units, with up to two decimals")]
+    pub amount: f64,
+    #[schemars(description = "ISO 4217 currency code, e.g. 'EUR'")]
+    pub currency: String,
+    #[schemars(description = "Amount formatted for the configured locale, e.g. '725,00 €'")]
+    pub formatted: String,
+}
+
+impl Money {
+    pub fn new(amount: f64, currency: &str, locale: &str) -> Self {
+        Self {
+            amount,
+            currency: currency.to_string(),
+            formatted: format_amount(amount, currency, locale),
+        }
+    }
+}
+
+/// Length of a month when prorating benefits, whatever the calendar month
+pub const DAYS_PER_MONTH: u32 = 30;
+
+/// Benefit for `days` of leave at `monthly` per 30-day month, rounded to the cent (half away from zero)
+pub fn prorated_total(monthly: i32, days: u32) -> f64 {
+    let numerator = monthly as i64 * 100 * days as i64;
+    let divisor = DAYS_PER_MONTH as i64;
+    let cents = (numerator.abs() * 2 + divisor) / (divisor * 2) * numerator.signum();
+    cents as f64 / 100.0
+}
+
+/// `monthly` scaled to `percentage` percent, rounded to the whole unit (half away from zero)
+pub fn scale_by_percentage(monthly: i32, percentage: u32) -> i32 {
+    let scaled = monthly as i64 * percentage as i64;
+    ((scaled.abs() * 2 + 100) / 200 * scaled.signum()) as i32
+}
+
+fn currency_symbol(currency: &str) -> &str {
+    match currency {
+        "EUR" => "€",
+        "USD" => "$",
+        "GBP" => "£",
+        other => other,
+    }
+}
+
+/// Formats `amount` with two decimals and grouped thousands.
+/// Spanish places the symbol after the number ("1.234,50 €"), English before it ("€1,234.50").
+fn format_amount(amount: f64, currency: &str, locale: &str) -> String {
+    let (thousands, decimal, symbol_first) = match locale {
+        "en-US" | "en-GB" => (',', '.', true),
+        _ => ('.', ',', false),
+    };
+
+    let cents = (amount.abs() * 100.0).round() as u64;
+    let digits = (cents / 100).to_string();
+    let mut integer = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            integer.push(thousands);
+        }
+        integer.push(digit);
+    }
+    let sign = if amount < 0.0 && cents > 0 { "-" } else { "" };
+    let number = format!("{}{}{}{:02}", sign, integer, decimal, cents % 100);
+
+    let symbol = currency_symbol(currency);
+    if symbol_first {
+        format!("{}{}", symbol, number)
+    } else {
+        format!("{} {}", number, symbol)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_amount() {
+        assert_eq!(Money::new(725.0, "EUR", "es-ES").formatted, "725,00 €");
+        assert_eq!(Money::new(1234.5, "EUR", "es-ES").formatted, "1.234,50 €");
+        assert_eq!(Money::new(1234567.891, "USD", "en-US").formatted, "$1,234,567.89");
+        assert_eq!(Money::new(0.0, "CHF", "en-GB").formatted, "CHF0.00");
+    }
+
+    #[test]
+    fn test_scale_by_percentage() {
+        assert_eq!(scale_by_percentage(500, 50), 250);
+        // 362.5 rounds up
+        assert_eq!(scale_by_percentage(725, 50), 363);
+        assert_eq!(scale_by_percentage(725, 100), 725);
+        assert_eq!(scale_by_percentage(725, 1), 7);
+        assert_eq!(scale_by_percentage(0, 50), 0);
+    }
+
+    #[test]
+    fn test_prorated_total() {
+        assert_eq!(prorated_total(725, 30), 725.0);
+        assert_eq!(prorated_total(725, 90), 2175.0);
+        assert_eq!(prorated_total(500, 45), 750.0);
+        // 725 * 10 / 30 = 241.666... and 500 / 30 = 16.666... round up
+        assert_eq!(prorated_total(725, 10), 241.67);
+        assert_eq!(prorated_total(500, 1), 16.67);
+        // 500 * 2 / 30 = 33.333... rounds down
+        assert_eq!(prorated_total(500, 2), 33.33);
+        assert_eq!(prorated_total(0, 60), 0.0);
+    }
+}
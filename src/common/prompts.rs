@@ -0,0 +1,151 @@
+use rmcp::model::{GetPromptResult, Prompt, PromptMessage, PromptMessageRole};
+
+use super::eligibility_engine::{UnpaidLeaveDirectParams, UnpaidLeaveInput};
+
+/// Ready-made evaluation of a typical situation, served as an MCP prompt
+#[derive(Debug)]
+pub struct PromptScenario {
+    pub name: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+    /// Case the filled-in call is expected to produce
+    pub case: &'static str,
+    pub relationship: &'static str,
+    pub situation: &'static str,
+    pub is_single_parent: bool,
+    pub total_children_after: f64,
+}
+
+/// One archetype per case evaluated by the rules
+pub const PROMPT_SCENARIOS: &[PromptScenario] = &[
+    PromptScenario {
+        name: "caring_for_sick_parent",
+        title: "Caring for a sick or injured parent",
+        description: "A son or daughter takes leave to care for a parent who is ill or had an accident",
+        case: "A",
+        relationship: "son",
+        situation: "illness",
+        is_single_parent: false,
+        total_children_after: 0.0,
+    },
+    PromptScenario {
+        name: "third_child_newborn",
+        title: "Third child with a newborn",
+        description: "A mother takes leave after the birth of her third child",
+        case: "B",
+        relationship: "mother",
+        situation: "birth",
+        is_single_parent: false,
+        total_children_after: 3.0,
+    },
+    PromptScenario {
+        name: "adoption",
+        title: "Adoption or foster care",
+        description: "A parent takes leave after adopting a child",
+        case: "C",
+        relationship: "mother",
+        situation: "adoption",
+        is_single_parent: false,
+        total_children_after: 1.0,
+    },
+    PromptScenario {
+        name: "multiple_birth",
+        title: "Multiple birth",
+        description: "A mother takes leave after giving birth to twins",
+        case: "D",
+        relationship: "mother",
+        situation: "multiple_birth",
+        is_single_parent: false,
+        total_children_after: 2.0,
+    },
+    PromptScenario {
+        name: "single_parent_newborn",
+        title: "Single parent with a newborn",
+        description: "A single father takes leave after the birth of his first child",
+        case: "E",
+        relationship: "father",
+        situation: "birth",
+        is_single_parent: true,
+        total_children_after: 1.0,
+    },
+];
+
+impl PromptScenario {
+    /// Tool arguments matching the scenario
+    pub fn params(&self) -> UnpaidLeaveDirectParams {
+        UnpaidLeaveDirectParams {
+            input: UnpaidLeaveInput {
+                relationship: self.relationship.to_string(),
+                situation: self.situation.to_string(),
+                is_single_parent: self.is_single_parent,
+                total_children_after: Some(self.total_children_after),
+            },
+            ..Default::default()
+        }
+    }
+
+    fn prompt(&self) -> Prompt {
+        let mut prompt = Prompt::new(self.name, Some(self.description), None);
+        prompt.title = Some(self.title.to_string());
+        prompt
+    }
+
+    fn messages(&self) -> Vec<PromptMessage> {
+        let arguments = serde_json::to_string(&self.params()).unwrap_or_default();
+        vec![PromptMessage::new_text(
+            PromptMessageRole::User,
+            format!(
+                "{}. Evaluate the unpaid leave assistance this person may be entitled to by calling the \
+                 evaluate_unpaid_leave_eligibility tool with exactly these arguments: {}",
+                self.description, arguments
+            ),
+        )]
+    }
+}
+
+/// Prompts listed to MCP clients
+pub fn list() -> Vec<Prompt> {
+    PROMPT_SCENARIOS.iter().map(PromptScenario::prompt).collect()
+}
+
+/// Messages of the prompt called `name`, `None` for an unknown prompt
+pub fn get(name: &str) -> Option<GetPromptResult> {
+    PROMPT_SCENARIOS.iter().find(|scenario| scenario.name == name).map(|scenario| GetPromptResult {
+        description: Some(format!("{}, expected to fall under case {}", scenario.title, scenario.case)),
+        messages: scenario.messages(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::eligibility_engine::EligibilityEngine;
+
+    #[tokio::test]
+    async fn test_scenarios_produce_their_case() {
+        let eligibility_engine = EligibilityEngine::new();
+        let mut cases: Vec<&str> = PROMPT_SCENARIOS.iter().map(|scenario| scenario.case).collect();
+        cases.sort();
+        assert_eq!(cases, vec!["A", "B", "C", "D", "E"]);
+
+        for scenario in PROMPT_SCENARIOS {
+            let response = eligibility_engine.evaluate(scenario.params()).await.unwrap();
+            assert_eq!(response.output.case, scenario.case, "prompt '{}'", scenario.name);
+            assert!(response.output.potentially_eligible, "prompt '{}'", scenario.name);
+        }
+    }
+
+    #[test]
+    fn test_get_prompt() {
+        assert_eq!(list().len(), PROMPT_SCENARIOS.len());
+
+        let result = get("single_parent_newborn").unwrap();
+        let message = serde_json::to_value(&result.messages[0]).unwrap();
+        let text = message["content"]["text"].as_str().unwrap();
+        assert!(text.contains("evaluate_unpaid_leave_eligibility"));
+        let arguments: UnpaidLeaveDirectParams = serde_json::from_str(text.rsplit_once(": ").unwrap().1).unwrap();
+        assert_eq!(arguments, PROMPT_SCENARIOS[4].params());
+
+        assert!(get("unknown").is_none());
+    }
+}
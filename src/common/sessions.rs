@@ -0,0 +1,582 @@
+use super::metrics::Gauge;
+use futures::future::BoxFuture;
+use rmcp::model::{ClientJsonRpcMessage, ServerJsonRpcMessage};
+use rmcp::transport::common::server_side_http::ServerSseMessage;
+use rmcp::transport::streamable_http_server::session::local::{
+    LocalSessionManager, LocalSessionManagerError, SessionConfig, create_local_session,
+};
+use rmcp::transport::streamable_http_server::session::{SessionId, SessionManager};
+use rmcp::transport::WorkerTransport;
+use futures::Stream;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+use super::eligibility_engine::EligibilityEngine;
+
+/// Sessions open at once unless configured otherwise
+pub const DEFAULT_MAX_SESSIONS: u64 = 1000;
+/// Seconds without requests after which a session is closed unless configured otherwise
+pub const DEFAULT_SESSION_IDLE_TIMEOUT_SECS: u64 = 30 * 60;
+/// Longest time between two sweeps for idle sessions
+const MAX_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Redis server the MCP sessions are shared through, e.g. `redis://redis:6379`, so any replica
+/// behind a load balancer can serve them. Sessions only live in the instance that opened them when unset
+pub const REDIS_URL_ENV: &str = "REDIS_URL";
+#[cfg(feature = "redis-sessions")]
+const SESSION_KEY_PREFIX: &str = "eligibility:mcp-session:";
+
+/// Bounds of the MCP sessions of the streamable-http server
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SessionLimits {
+    pub max_sessions: usize,
+    pub idle_timeout: Duration,
+}
+
+impl Default for SessionLimits {
+    fn default() -> Self {
+        Self {
+            max_sessions: DEFAULT_MAX_SESSIONS as usize,
+            idle_timeout: Duration::from_secs(DEFAULT_SESSION_IDLE_TIMEOUT_SECS),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum BoundedSessionError {
+    /// `max_sessions` sessions are already open
+    TooManySessions(usize),
+    Session(LocalSessionManagerError),
+    /// The session store failed
+    Store(String),
+}
+
+impl fmt::Display for BoundedSessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BoundedSessionError::TooManySessions(max_sessions) => {
+                write!(f, "the server already has the maximum of {} open sessions, retry later", max_sessions)
+            }
+            BoundedSessionError::Session(e) => write!(f, "{}", e),
+            BoundedSessionError::Store(e) => write!(f, "cannot reach the session store: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for BoundedSessionError {}
+
+impl From<LocalSessionManagerError> for BoundedSessionError {
+    fn from(error: LocalSessionManagerError) -> Self {
+        BoundedSessionError::Session(error)
+    }
+}
+
+/// Initialize requests of the MCP sessions, shared by every replica so any of them can serve a session
+pub trait SessionStore: Send + Sync {
+    /// Stores the initialize request of session `id`
+    fn save<'a>(&'a self, id: &'a SessionId, initialize: &'a ClientJsonRpcMessage) -> BoxFuture<'a, Result<(), String>>;
+
+    /// Initialize request of session `id`, `None` when the session expired or was closed
+    fn load<'a>(&'a self, id: &'a SessionId) -> BoxFuture<'a, Result<Option<ClientJsonRpcMessage>, String>>;
+
+    /// Restarts the expiry of session `id`
+    fn refresh<'a>(&'a self, id: &'a SessionId) -> BoxFuture<'a, Result<(), String>>;
+
+    /// Forgets session `id` on every replica
+    fn remove<'a>(&'a self, id: &'a SessionId) -> BoxFuture<'a, Result<(), String>>;
+}
+
+/// Connects the session store when `REDIS_URL` is set, sessions staying local otherwise. Fails when
+/// the server cannot be reached or was built without the `redis-sessions` feature
+pub async fn connect_store(ttl: Duration) -> anyhow::Result<Option<Arc<dyn SessionStore>>> {
+    let url = match std::env::var(REDIS_URL_ENV) {
+        Ok(url) if !url.trim().is_empty() => url,
+        _ => return Ok(None),
+    };
+    let store = redis_store::connect(url.trim(), ttl).await?;
+    tracing::info!("Sharing MCP sessions with the other replicas through Redis");
+    Ok(Some(store))
+}
+
+/// `LocalSessionManager` that refuses new sessions beyond `max_sessions` and closes the ones
+/// that received no request for `idle_timeout`, reporting the open sessions in `active_sessions`.
+/// With a `SessionStore`, sessions opened by other replicas are also served
+pub struct BoundedSessionManager {
+    inner: LocalSessionManager,
+    limits: SessionLimits,
+    // Time of the last request of every open session
+    last_activity: Mutex<HashMap<SessionId, Instant>>,
+    // Sessions closed for being idle here, which other replicas may still be serving
+    evicted: Arc<std::sync::Mutex<HashSet<SessionId>>>,
+    active_sessions: Gauge,
+    store: Option<Arc<dyn SessionStore>>,
+}
+
+impl BoundedSessionManager {
+    pub fn new(limits: SessionLimits, active_sessions: Gauge) -> Self {
+        Self::with_store(limits, active_sessions, None)
+    }
+
+    /// Manager sharing its sessions through `store`, local only when `None`
+    pub fn with_store(limits: SessionLimits, active_sessions: Gauge, store: Option<Arc<dyn SessionStore>>) -> Self {
+        // Without a store the session worker also stops on its own once idle for as long. With
+        // one, only the sweeper closes sessions, so they are not dropped from the store meanwhile
+        let keep_alive = if store.is_some() { None } else { Some(limits.idle_timeout) };
+        let inner = LocalSessionManager {
+            session_config: SessionConfig { keep_alive, ..Default::default() },
+            ..Default::default()
+        };
+        active_sessions.set(0.0);
+        Self {
+            inner,
+            limits,
+            last_activity: Mutex::new(HashMap::new()),
+            evicted: Arc::default(),
+            active_sessions,
+            store,
+        }
+    }
+
+    /// Closes the sessions idle for longer than the idle timeout, returning how many were closed
+    pub async fn evict_idle(&self) -> usize {
+        let idle: Vec<SessionId> = {
+            let mut last_activity = self.last_activity.lock().await;
+            let idle: Vec<SessionId> = last_activity
+                .iter()
+                .filter(|(_, last)| last.elapsed() > self.limits.idle_timeout)
+                .map(|(id, _)| id.clone())
+                .collect();
+            for id in &idle {
+                last_activity.remove(id);
+            }
+            self.active_sessions.set(last_activity.len() as f64);
+            if self.store.is_some() {
+                self.evicted.lock().unwrap().extend(idle.iter().cloned());
+            }
+            idle
+        };
+        for id in &idle {
+            // The worker may already have stopped after its own keep-alive timeout
+            if let Err(e) = self.inner.close_session(id).await {
+                tracing::debug!(session_id = %id, error = %e, "Idle session already closed");
+            }
+        }
+        if !idle.is_empty() {
+            tracing::info!("Closed {} idle MCP sessions", idle.len());
+        }
+        idle.len()
+    }
+
+    /// Sweeps for idle sessions in the background, at least once per minute
+    pub fn spawn_idle_sweeper(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let period = self.limits.idle_timeout.min(MAX_SWEEP_INTERVAL);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                self.evict_idle().await;
+            }
+        })
+    }
+
+    /// Records a request on session `id`, false when the session is not open
+    async fn touch(&self, id: &SessionId) -> bool {
+        match self.last_activity.lock().await.get_mut(id) {
+            Some(last) => *last = Instant::now(),
+            None => return false,
+        }
+        if let Some(store) = &self.store {
+            // The session keeps working here, other replicas may just forget it sooner
+            if let Err(e) = store.refresh(id).await {
+                tracing::warn!(session_id = %id, error = %e, "Cannot refresh the MCP session in Redis");
+            }
+        }
+        true
+    }
+
+    /// Opens here session `id` opened by another replica, replaying its stored initialize request.
+    /// False when no replica knows the session
+    async fn restore(&self, id: &SessionId) -> Result<bool, BoundedSessionError> {
+        let Some(store) = &self.store else {
+            return Ok(false);
+        };
+        // Held while the session is restored, so concurrent requests restore it only once
+        let mut last_activity = self.last_activity.lock().await;
+        if last_activity.contains_key(id) {
+            return Ok(true);
+        }
+        let Some(initialize) = store.load(id).await.map_err(BoundedSessionError::Store)? else {
+            return Ok(false);
+        };
+        if last_activity.len() >= self.limits.max_sessions {
+            tracing::warn!("Refusing to restore an MCP session, {} sessions are open", last_activity.len());
+            return Err(BoundedSessionError::TooManySessions(self.limits.max_sessions));
+        }
+
+        let (handle, worker) = create_local_session(id.clone(), self.inner.session_config.clone());
+        self.inner.sessions.write().await.insert(id.clone(), handle);
+        let evicted = self.evicted.clone();
+        let session_id = id.clone();
+        tokio::spawn(async move {
+            match rmcp::serve_server(EligibilityEngine::new(), WorkerTransport::spawn(worker)).await {
+                Ok(service) => {
+                    let _ = service.waiting().await;
+                }
+                Err(e) => tracing::error!(session_id = %session_id, error = %e, "Failed to restore the MCP session"),
+            }
+            evicted.lock().unwrap().remove(&session_id);
+        });
+        self.inner.initialize_session(id, initialize).await?;
+        let initialized = serde_json::from_value(serde_json::json!({"jsonrpc": "2.0", "method": "notifications/initialized"}))
+            .expect("valid initialized notification");
+        self.inner.accept_message(id, initialized).await?;
+
+        last_activity.insert(id.clone(), Instant::now());
+        self.active_sessions.set(last_activity.len() as f64);
+        tracing::info!(session_id = %id, "Restored an MCP session opened by another replica");
+        Ok(true)
+    }
+}
+
+impl SessionManager for BoundedSessionManager {
+    type Error = BoundedSessionError;
+    type Transport = <LocalSessionManager as SessionManager>::Transport;
+
+    async fn create_session(&self) -> Result<(SessionId, Self::Transport), Self::Error> {
+        self.evict_idle().await;
+        // Held while the session is created, so concurrent clients cannot exceed the limit
+        let mut last_activity = self.last_activity.lock().await;
+        if last_activity.len() >= self.limits.max_sessions {
+            tracing::warn!("Refusing a new MCP session, {} sessions are open", last_activity.len());
+            return Err(BoundedSessionError::TooManySessions(self.limits.max_sessions));
+        }
+        let (id, transport) = self.inner.create_session().await?;
+        last_activity.insert(id.clone(), Instant::now());
+        self.active_sessions.set(last_activity.len() as f64);
+        Ok((id, transport))
+    }
+
+    async fn initialize_session(&self, id: &SessionId, message: ClientJsonRpcMessage) -> Result<ServerJsonRpcMessage, Self::Error> {
+        self.touch(id).await;
+        let stored = message.clone();
+        let response = self.inner.initialize_session(id, message).await?;
+        if let Some(store) = &self.store {
+            store.save(id, &stored).await.map_err(BoundedSessionError::Store)?;
+        }
+        Ok(response)
+    }
+
+    async fn has_session(&self, id: &SessionId) -> Result<bool, Self::Error> {
+        // Evicted sessions are gone even if the inner manager still lists them
+        if self.touch(id).await {
+            return Ok(self.inner.has_session(id).await?);
+        }
+        self.restore(id).await
+    }
+
+    async fn close_session(&self, id: &SessionId) -> Result<(), Self::Error> {
+        {
+            let mut last_activity = self.last_activity.lock().await;
+            last_activity.remove(id);
+            self.active_sessions.set(last_activity.len() as f64);
+        }
+        // A session evicted here ends its service, which closes it once more: other replicas may
+        // still be serving it, so it stays in the store until it expires there
+        let evicted = self.evicted.lock().unwrap().remove(id);
+        if let (Some(store), false) = (&self.store, evicted) {
+            store.remove(id).await.map_err(BoundedSessionError::Store)?;
+        }
+        Ok(self.inner.close_session(id).await?)
+    }
+
+    async fn create_stream(
+        &self,
+        id: &SessionId,
+        message: ClientJsonRpcMessage,
+    ) -> Result<impl Stream<Item = ServerSseMessage> + Send + Sync + 'static, Self::Error> {
+        self.touch(id).await;
+        Ok(self.inner.create_stream(id, message).await?)
+    }
+
+    async fn accept_message(&self, id: &SessionId, message: ClientJsonRpcMessage) -> Result<(), Self::Error> {
+        self.touch(id).await;
+        Ok(self.inner.accept_message(id, message).await?)
+    }
+
+    async fn create_standalone_stream(
+        &self,
+        id: &SessionId,
+    ) -> Result<impl Stream<Item = ServerSseMessage> + Send + Sync + 'static, Self::Error> {
+        self.touch(id).await;
+        Ok(self.inner.create_standalone_stream(id).await?)
+    }
+
+    async fn resume(
+        &self,
+        id: &SessionId,
+        last_event_id: String,
+    ) -> Result<impl Stream<Item = ServerSseMessage> + Send + Sync + 'static, Self::Error> {
+        self.touch(id).await;
+        Ok(self.inner.resume(id, last_event_id).await?)
+    }
+}
+
+#[cfg(feature = "redis-sessions")]
+mod redis_store {
+    use redis::aio::{ConnectionLike, ConnectionManager};
+    use redis::{RedisError, RedisResult};
+    use rmcp::model::ClientJsonRpcMessage;
+    use rmcp::transport::streamable_http_server::session::SessionId;
+    use futures::future::BoxFuture;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::{SessionStore, REDIS_URL_ENV, SESSION_KEY_PREFIX};
+
+    /// MCP sessions shared by every replica through Redis. Only the initialize request of a session is
+    /// stored, expiring once the session is idle for `ttl`: evaluations keep no state between requests,
+    /// so a replica can open an equivalent session for any stored id by replaying it
+    #[derive(Clone)]
+    pub struct RedisSessionStore<C = ConnectionManager> {
+        connection: C,
+        ttl: Duration,
+    }
+
+    impl RedisSessionStore {
+        /// Connects to the Redis server at `url`, reconnecting on its own when the connection drops
+        pub async fn connect(url: &str, ttl: Duration) -> anyhow::Result<Self> {
+            // The URL may carry a password, keep it out of the errors
+            let client = redis::Client::open(url).map_err(|e| anyhow::anyhow!("Invalid {}: {}", REDIS_URL_ENV, e))?;
+            let connection = ConnectionManager::new(client)
+                .await
+                .map_err(|e| anyhow::anyhow!("Cannot connect to the Redis server in {}: {}", REDIS_URL_ENV, e))?;
+            Ok(Self::new(connection, ttl))
+        }
+    }
+
+    impl<C: ConnectionLike + Clone + Send + Sync> RedisSessionStore<C> {
+        pub fn new(connection: C, ttl: Duration) -> Self {
+            Self { connection, ttl }
+        }
+
+        fn key(id: &SessionId) -> String {
+            format!("{}{}", SESSION_KEY_PREFIX, id)
+        }
+
+        fn ttl_secs(&self) -> u64 {
+            self.ttl.as_secs().max(1)
+        }
+
+        /// Stores the initialize request of session `id`
+        pub async fn save(&self, id: &SessionId, initialize: &ClientJsonRpcMessage) -> RedisResult<()> {
+            let value = serde_json::to_string(initialize)
+                .map_err(|e| RedisError::from((redis::ErrorKind::TypeError, "cannot serialize the initialize request", e.to_string())))?;
+            redis::cmd("SET")
+                .arg(Self::key(id))
+                .arg(value)
+                .arg("EX")
+                .arg(self.ttl_secs())
+                .query_async(&mut self.connection.clone())
+                .await
+        }
+
+        /// Initialize request of session `id`, `None` when the session expired or was closed
+        pub async fn load(&self, id: &SessionId) -> RedisResult<Option<ClientJsonRpcMessage>> {
+            let value: Option<String> = redis::cmd("GET").arg(Self::key(id)).query_async(&mut self.connection.clone()).await?;
+            Ok(value.and_then(|value| {
+                serde_json::from_str(&value)
+                    .inspect_err(|e| tracing::warn!(session_id = %id, error = %e, "Ignoring an unreadable stored MCP session"))
+                    .ok()
+            }))
+        }
+
+        /// Restarts the expiry of session `id`
+        pub async fn refresh(&self, id: &SessionId) -> RedisResult<()> {
+            redis::cmd("EXPIRE")
+                .arg(Self::key(id))
+                .arg(self.ttl_secs())
+                .query_async(&mut self.connection.clone())
+                .await
+        }
+
+        /// Forgets session `id` on every replica
+        pub async fn remove(&self, id: &SessionId) -> RedisResult<()> {
+            redis::cmd("DEL").arg(Self::key(id)).query_async(&mut self.connection.clone()).await
+        }
+    }
+
+    impl<C: ConnectionLike + Clone + Send + Sync> SessionStore for RedisSessionStore<C> {
+        fn save<'a>(&'a self, id: &'a SessionId, initialize: &'a ClientJsonRpcMessage) -> BoxFuture<'a, Result<(), String>> {
+            Box::pin(async move { Self::save(self, id, initialize).await.map_err(|e| e.to_string()) })
+        }
+
+        fn load<'a>(&'a self, id: &'a SessionId) -> BoxFuture<'a, Result<Option<ClientJsonRpcMessage>, String>> {
+            Box::pin(async move { Self::load(self, id).await.map_err(|e| e.to_string()) })
+        }
+
+        fn refresh<'a>(&'a self, id: &'a SessionId) -> BoxFuture<'a, Result<(), String>> {
+            Box::pin(async move { Self::refresh(self, id).await.map_err(|e| e.to_string()) })
+        }
+
+        fn remove<'a>(&'a self, id: &'a SessionId) -> BoxFuture<'a, Result<(), String>> {
+            Box::pin(async move { Self::remove(self, id).await.map_err(|e| e.to_string()) })
+        }
+    }
+
+    pub async fn connect(url: &str, ttl: Duration) -> anyhow::Result<Arc<dyn SessionStore>> {
+        Ok(Arc::new(RedisSessionStore::connect(url, ttl).await?))
+    }
+}
+
+#[cfg(not(feature = "redis-sessions"))]
+mod redis_store {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::{SessionStore, REDIS_URL_ENV};
+
+    pub async fn connect(_url: &str, _ttl: Duration) -> anyhow::Result<Arc<dyn SessionStore>> {
+        anyhow::bail!("{} is set but this server was built without the redis-sessions feature", REDIS_URL_ENV)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "redis-sessions")]
+    use futures::StreamExt;
+    #[cfg(feature = "redis-sessions")]
+    use super::redis_store::RedisSessionStore;
+    #[cfg(feature = "redis-sessions")]
+    use redis_test::{MockCmd, MockRedisConnection};
+    #[cfg(feature = "redis-sessions")]
+    use serde_json::json;
+
+    fn manager(max_sessions: usize, idle_timeout: Duration) -> (BoundedSessionManager, Gauge) {
+        let gauge = Gauge::new("test_active_sessions", "Open sessions").unwrap();
+        let limits = SessionLimits { max_sessions, idle_timeout };
+        (BoundedSessionManager::new(limits, gauge.clone()), gauge)
+    }
+
+    #[tokio::test]
+    async fn test_max_sessions() {
+        let (manager, active_sessions) = manager(2, Duration::from_secs(60));
+        let (first, _first_transport) = manager.create_session().await.unwrap();
+        let (_second, _second_transport) = manager.create_session().await.unwrap();
+        assert_eq!(active_sessions.get(), 2.0);
+
+        let error = manager.create_session().await.err().unwrap();
+        assert!(matches!(error, BoundedSessionError::TooManySessions(2)));
+        assert!(error.to_string().contains("maximum of 2 open sessions"));
+
+        // Closing a session makes room for a new one
+        manager.close_session(&first).await.unwrap();
+        assert_eq!(active_sessions.get(), 1.0);
+        assert!(!manager.has_session(&first).await.unwrap());
+        let _third = manager.create_session().await.unwrap();
+        assert_eq!(active_sessions.get(), 2.0);
+    }
+
+    #[tokio::test]
+    async fn test_idle_sessions_evicted() {
+        let (manager, active_sessions) = manager(1, Duration::from_millis(50));
+        let (idle, _idle_transport) = manager.create_session().await.unwrap();
+        assert_eq!(manager.evict_idle().await, 0);
+        assert!(manager.has_session(&idle).await.unwrap());
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(manager.evict_idle().await, 1);
+        assert_eq!(active_sessions.get(), 0.0);
+        assert!(!manager.has_session(&idle).await.unwrap());
+
+        // Stale sessions are also evicted when a new one is requested
+        let (stale, _stale_transport) = manager.create_session().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let (_, _transport) = manager.create_session().await.unwrap();
+        assert!(!manager.has_session(&stale).await.unwrap());
+        assert_eq!(active_sessions.get(), 1.0);
+    }
+
+    #[cfg(feature = "redis-sessions")]
+    fn message(value: serde_json::Value) -> ClientJsonRpcMessage {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[cfg(feature = "redis-sessions")]
+    fn initialize() -> ClientJsonRpcMessage {
+        message(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": "2025-03-26",
+                "capabilities": {},
+                "clientInfo": { "name": "test-client", "version": "1.0.0" }
+            }
+        }))
+    }
+
+    #[cfg(feature = "redis-sessions")]
+    fn session_key(id: &str) -> String {
+        format!("{}{}", SESSION_KEY_PREFIX, id)
+    }
+
+    #[cfg(feature = "redis-sessions")]
+    #[tokio::test]
+    async fn test_redis_session_store() {
+        let id = SessionId::from("session-1");
+        let stored = serde_json::to_string(&initialize()).unwrap();
+        let connection = MockRedisConnection::new(vec![
+            MockCmd::new(redis::cmd("SET").arg(session_key("session-1")).arg(&stored).arg("EX").arg(60), Ok("OK")),
+            MockCmd::new(redis::cmd("GET").arg(session_key("session-1")), Ok(stored.as_str())),
+            MockCmd::new(redis::cmd("EXPIRE").arg(session_key("session-1")).arg(60), Ok(1)),
+            MockCmd::new(redis::cmd("DEL").arg(session_key("session-1")), Ok(1)),
+            MockCmd::new(redis::cmd("GET").arg(session_key("session-1")), Ok(redis::Value::Nil)),
+        ]);
+        let store = RedisSessionStore::new(connection, Duration::from_secs(60));
+
+        store.save(&id, &initialize()).await.unwrap();
+        let loaded = store.load(&id).await.unwrap().unwrap();
+        assert_eq!(serde_json::to_string(&loaded).unwrap(), stored);
+        store.refresh(&id).await.unwrap();
+        store.remove(&id).await.unwrap();
+        assert!(store.load(&id).await.unwrap().is_none());
+    }
+
+    #[cfg(feature = "redis-sessions")]
+    #[tokio::test]
+    async fn test_session_restored_from_redis() {
+        // Opened by another replica, which stored its initialize request
+        let id = SessionId::from("opened-elsewhere");
+        let stored = serde_json::to_string(&initialize()).unwrap();
+        let connection = MockRedisConnection::new(vec![
+            MockCmd::new(redis::cmd("GET").arg(session_key("unknown")), Ok(redis::Value::Nil)),
+            MockCmd::new(redis::cmd("GET").arg(session_key("opened-elsewhere")), Ok(stored.as_str())),
+            MockCmd::new(redis::cmd("EXPIRE").arg(session_key("opened-elsewhere")).arg(60), Ok(1)),
+            MockCmd::new(redis::cmd("DEL").arg(session_key("opened-elsewhere")), Ok(1)),
+        ]);
+        let gauge = Gauge::new("test_active_sessions", "Open sessions").unwrap();
+        let limits = SessionLimits { max_sessions: 10, idle_timeout: Duration::from_secs(60) };
+        let store = RedisSessionStore::new(connection, limits.idle_timeout);
+        let manager = BoundedSessionManager::with_store(limits, gauge.clone(), Some(Arc::new(store)));
+
+        assert!(!manager.has_session(&SessionId::from("unknown")).await.unwrap());
+        assert!(manager.has_session(&id).await.unwrap());
+        assert_eq!(gauge.get(), 1.0);
+
+        let list_tools = message(json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" }));
+        let mut stream = std::pin::pin!(manager.create_stream(&id, list_tools).await.unwrap());
+        let response = tokio::time::timeout(Duration::from_secs(5), stream.next()).await.unwrap().unwrap();
+        let response = serde_json::to_value(&*response.message).unwrap();
+        assert_eq!(response["id"], 2);
+        assert!(response["result"]["tools"].as_array().is_some_and(|tools| !tools.is_empty()));
+
+        // Closed by the client, so no replica serves it anymore
+        manager.close_session(&id).await.unwrap();
+        assert_eq!(gauge.get(), 0.0);
+    }
+}
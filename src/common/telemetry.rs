@@ -0,0 +1,69 @@
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::SpanExporter;
+use opentelemetry_sdk::{Resource, trace::SdkTracerProvider};
+use tracing::{Level, Subscriber};
+use tracing_subscriber::{Layer, filter::{LevelFilter, Targets}, registry::LookupSpan};
+
+use super::eligibility_engine::SERVER_NAME;
+
+/// Standard OpenTelemetry variables, trace export is enabled when either is set
+pub const OTLP_ENDPOINT_ENV: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+pub const OTLP_TRACES_ENDPOINT_ENV: &str = "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT";
+const SERVICE_NAME_ENV: &str = "OTEL_SERVICE_NAME";
+
+/// Crates used by the exporter itself, exporting their spans would feed back into the exporter
+const EXPORTER_TARGETS: &[&str] = &["opentelemetry", "opentelemetry_sdk", "opentelemetry_otlp", "reqwest", "hyper", "hyper_util", "h2"];
+
+/// Exports spans over OTLP/HTTP. Pending spans are flushed when the guard is dropped
+#[derive(Debug)]
+pub struct TelemetryGuard {
+    provider: SdkTracerProvider,
+}
+
+impl TelemetryGuard {
+    /// Builds the exporter from the `OTEL_*` environment variables, `None` when no endpoint is configured
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
+        let configured = |name: &str| std::env::var(name).is_ok_and(|value| !value.trim().is_empty());
+        if !configured(OTLP_ENDPOINT_ENV) && !configured(OTLP_TRACES_ENDPOINT_ENV) {
+            return Ok(None);
+        }
+
+        let exporter = SpanExporter::builder()
+            .with_http()
+            .build()
+            .map_err(|e| anyhow::anyhow!("Cannot create the OTLP trace exporter: {}", e))?;
+
+        // OTEL_SERVICE_NAME and OTEL_RESOURCE_ATTRIBUTES are read by the default resource
+        let mut resource = Resource::builder();
+        if !configured(SERVICE_NAME_ENV) {
+            resource = resource.with_service_name(SERVER_NAME);
+        }
+
+        let provider = SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(resource.build())
+            .build();
+        Ok(Some(Self { provider }))
+    }
+
+    /// Layer turning `tracing` spans of level info and above into OpenTelemetry spans,
+    /// with their fields as attributes
+    pub fn layer<S>(&self) -> Box<dyn Layer<S> + Send + Sync>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span> + Send + Sync,
+    {
+        let tracer = self.provider.tracer(SERVER_NAME);
+        let targets = EXPORTER_TARGETS
+            .iter()
+            .fold(Targets::new().with_default(Level::INFO), |targets, target| targets.with_target(*target, LevelFilter::OFF));
+        tracing_opentelemetry::layer().with_tracer(tracer).with_filter(targets).boxed()
+    }
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        if let Err(e) = self.provider.shutdown() {
+            eprintln!("Failed to flush pending OpenTelemetry spans: {}", e);
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use once_cell::sync::OnceCell;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+use super::eligibility_engine::UnpaidLeaveResponse;
+use super::metrics::EligibilityMetrics;
+
+/// URL every evaluation result is POSTed to as JSON, e.g. for a case-management system.
+/// Results are not delivered when unset
+pub const WEBHOOK_URL_ENV: &str = "WEBHOOK_URL";
+/// Environment variable with the number of times a failed delivery is attempted again
+pub const WEBHOOK_RETRIES_ENV: &str = "WEBHOOK_RETRIES";
+const DEFAULT_WEBHOOK_RETRIES: u32 = 3;
+/// Wait before the first retry, doubled before each further one
+const DEFAULT_WEBHOOK_BACKOFF: Duration = Duration::from_millis(500);
+/// Time a single delivery attempt may take
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Webhook configured at startup from `WEBHOOK_URL`
+static WEBHOOK: OnceCell<Webhook> = OnceCell::new();
+
+/// Configures the webhook when `WEBHOOK_URL` is set. Fails on an invalid URL or retry count
+pub fn init() -> anyhow::Result<()> {
+    let url = match std::env::var(WEBHOOK_URL_ENV) {
+        Ok(url) if !url.trim().is_empty() => url,
+        _ => return Ok(()),
+    };
+    let retries = match std::env::var(WEBHOOK_RETRIES_ENV) {
+        Ok(retries) if !retries.trim().is_empty() => retries.trim().parse().map_err(|_| {
+            anyhow::anyhow!("invalid {} '{}': expected a number of retries", WEBHOOK_RETRIES_ENV, retries)
+        })?,
+        _ => DEFAULT_WEBHOOK_RETRIES,
+    };
+    let webhook = Webhook::new(url.trim())?.with_retries(retries, DEFAULT_WEBHOOK_BACKOFF);
+    tracing::info!("Delivering evaluation results to the webhook at {}", webhook.url);
+    let _ = WEBHOOK.set(webhook);
+    Ok(())
+}
+
+/// Webhook configured by `init`, `None` when delivery is not configured
+pub fn current() -> Option<Webhook> {
+    WEBHOOK.get().cloned()
+}
+
+/// Outbound delivery of evaluation results to a single URL
+#[derive(Debug, Clone)]
+pub struct Webhook {
+    client: reqwest::Client,
+    url: reqwest::Url,
+    retries: u32,
+    backoff: Duration,
+}
+
+impl Webhook {
+    /// Webhook POSTing to `url`, which must be an http or https URL
+    pub fn new(url: &str) -> anyhow::Result<Self> {
+        let url = reqwest::Url::parse(url)
+            .ok()
+            .filter(|url| matches!(url.scheme(), "http" | "https"))
+            .ok_or_else(|| anyhow::anyhow!("invalid {} '{}': expected an http or https URL", WEBHOOK_URL_ENV, url))?;
+        let client = reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .map_err(|e| anyhow::anyhow!("Cannot create the webhook client: {}", e))?;
+        Ok(Self { client, url, retries: DEFAULT_WEBHOOK_RETRIES, backoff: DEFAULT_WEBHOOK_BACKOFF })
+    }
+
+    /// Attempts a failed delivery `retries` more times, waiting `backoff` before the first retry
+    pub fn with_retries(mut self, retries: u32, backoff: Duration) -> Self {
+        self.retries = retries;
+        self.backoff = backoff;
+        self
+    }
+
+    /// POSTs `response` in the background. A delivery still failing after the retries is logged
+    /// and counted in `metrics`, it never affects the evaluation itself
+    pub fn deliver(&self, response: &UnpaidLeaveResponse, metrics: Arc<EligibilityMetrics>) -> JoinHandle<()> {
+        let webhook = self.clone();
+        let evaluation_id = response.evaluation_id.clone().unwrap_or_default();
+        let body = serde_json::to_vec(response).unwrap_or_default();
+
+        tokio::spawn(async move {
+            let mut backoff = webhook.backoff;
+            for attempt in 0..=webhook.retries {
+                if attempt > 0 {
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+                let result = webhook
+                    .client
+                    .post(webhook.url.clone())
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .body(body.clone())
+                    .send()
+                    .await
+                    .and_then(reqwest::Response::error_for_status);
+                match result {
+                    Ok(_) => return,
+                    Err(e) => tracing::debug!(error = %e, %evaluation_id, attempt, "Webhook delivery failed"),
+                }
+            }
+            tracing::warn!(%evaluation_id, "Failed to deliver the evaluation to the webhook after {} attempts", webhook.retries + 1);
+            metrics.increment_webhook_failures();
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::eligibility_engine::{direct_params, EligibilityEngine};
+    use axum::{Router, http::StatusCode, routing::post};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::sync::mpsc;
+
+    /// Webhook receiver answering 500 to the first `failures` deliveries, and sending the bodies it accepts
+    async fn mock_sink(failures: usize) -> (String, mpsc::UnboundedReceiver<serde_json::Value>, Arc<AtomicUsize>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = attempts.clone();
+        let router = Router::new().route(
+            "/hook",
+            post(move |axum::Json(body): axum::Json<serde_json::Value>| {
+                let sender = sender.clone();
+                let counter = counter.clone();
+                async move {
+                    if counter.fetch_add(1, Ordering::SeqCst) < failures {
+                        return StatusCode::INTERNAL_SERVER_ERROR;
+                    }
+                    let _ = sender.send(body);
+                    StatusCode::NO_CONTENT
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        (url, receiver, attempts)
+    }
+
+    #[tokio::test]
+    async fn test_evaluations_are_delivered() {
+        let (url, mut received, attempts) = mock_sink(1).await;
+        let webhook = Webhook::new(&url).unwrap().with_retries(2, Duration::from_millis(10));
+        let metrics = Arc::new(EligibilityMetrics::new());
+        let eligibility_engine = EligibilityEngine::new().with_metrics(metrics.clone()).with_webhook(Some(webhook));
+
+        let response = eligibility_engine.evaluate(direct_params("mother", "illness")).await.unwrap();
+        let delivered = tokio::time::timeout(Duration::from_secs(5), received.recv()).await.unwrap().unwrap();
+        assert_eq!(delivered["evaluation_id"], serde_json::json!(response.evaluation_id));
+        assert_eq!(delivered["output"]["case"], "A");
+        // Delivered on the retry after the first attempt failed
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+        #[cfg(feature = "metrics")]
+        assert_eq!(metrics.webhook_failures_total.get(), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_failed_delivery_is_counted() {
+        let (url, _received, attempts) = mock_sink(usize::MAX).await;
+        let webhook = Webhook::new(&url).unwrap().with_retries(2, Duration::from_millis(10));
+        let metrics = Arc::new(EligibilityMetrics::new());
+        let response = EligibilityEngine::new().with_result_cache(None).evaluate(direct_params("mother", "illness")).await.unwrap();
+
+        webhook.deliver(&response, metrics.clone()).await.unwrap();
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        #[cfg(feature = "metrics")]
+        assert_eq!(metrics.webhook_failures_total.get(), 1.0);
+    }
+
+    #[test]
+    fn test_invalid_url_rejected() {
+        assert!(Webhook::new("https://cases.example.com/hooks/eligibility").is_ok());
+        assert!(Webhook::new("ftp://cases.example.com").is_err());
+        assert!(Webhook::new("not a url").is_err());
+    }
+}
@@ -1,56 +1,353 @@
-use rmcp::transport::streamable_http_server::{
-    StreamableHttpService, session::local::LocalSessionManager,
-};
+use clap::Parser;
+use rmcp::transport::streamable_http_server::StreamableHttpService;
 use tracing_subscriber::{
     layer::SubscriberExt,
     util::SubscriberInitExt,
     {self},
 };
 mod common;
-use common::{eligibility_engine::EligibilityEngine, metrics};
-use axum::{response::IntoResponse, http::StatusCode};
+use common::{
+    audit,
+    eligibility_engine::{self, EligibilityEngine},
+    events,
+    http,
+    logging::{self, LogFormat},
+    metrics,
+    sessions::{self, BoundedSessionManager, SessionLimits},
+    telemetry::TelemetryGuard,
+    webhook,
+};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 
 const BIND_ADDRESS: &str = "127.0.0.1:8001";
+/// Path the MCP endpoint is served on unless configured otherwise
+const MCP_PATH: &str = "/mcp";
+
+/// Streamable-http MCP server for the unpaid leave eligibility rules.
+/// Every option falls back to its environment variable, then to its default
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Args {
+    /// Address and port to listen on, as host:port
+    #[arg(long, env = "BIND_ADDRESS", default_value = BIND_ADDRESS)]
+    bind: String,
+
+    /// Unix domain socket path to listen on instead of `bind`, for clients on the same host
+    #[arg(long, env = http::BIND_UDS_ENV)]
+    bind_uds: Option<PathBuf>,
+
+    /// JDM rule document to serve instead of the embedded one
+    #[arg(long, env = eligibility_engine::RULES_PATH_ENV)]
+    rules_path: Option<PathBuf>,
+
+    /// Log filter, a level such as "info" or a list of target=level directives
+    #[arg(long, env = "RUST_LOG", default_value = "debug")]
+    log_level: String,
+
+    /// Log line format, "pretty" or "json"
+    #[arg(long, env = logging::LOG_FORMAT_ENV, default_value_t = LogFormat::Pretty)]
+    log_format: LogFormat,
+
+    /// Path the Prometheus metrics are served on
+    #[arg(long, env = "METRICS_PATH", default_value = http::DEFAULT_METRICS_PATH, value_parser = parse_route_path)]
+    metrics_path: String,
+
+    /// Path the MCP endpoint is served on
+    #[arg(long, env = "MCP_PATH", default_value = MCP_PATH, value_parser = parse_mcp_path)]
+    mcp_path: String,
+
+    /// Maximum number of MCP sessions open at once, new sessions are refused beyond it
+    #[arg(long, env = "MAX_SESSIONS", default_value_t = sessions::DEFAULT_MAX_SESSIONS, value_parser = clap::value_parser!(u64).range(1..))]
+    max_sessions: u64,
+
+    /// Seconds without requests after which an MCP session is closed
+    #[arg(long, env = "SESSION_IDLE_TIMEOUT_SECS", default_value_t = sessions::DEFAULT_SESSION_IDLE_TIMEOUT_SECS, value_parser = clap::value_parser!(u64).range(1..))]
+    session_idle_timeout_secs: u64,
+}
+
+impl Args {
+    fn session_limits(&self) -> SessionLimits {
+        SessionLimits {
+            max_sessions: self.max_sessions as usize,
+            idle_timeout: Duration::from_secs(self.session_idle_timeout_secs),
+        }
+    }
+
+    /// Rejects a metrics path the MCP endpoint would shadow
+    fn check_paths(&self) -> Result<(), String> {
+        let metrics_path = self.metrics_path.trim_end_matches('/');
+        if metrics_path == self.mcp_path || metrics_path.starts_with(&format!("{}/", self.mcp_path)) {
+            return Err(format!("METRICS_PATH '{}' must not be under MCP_PATH '{}'", self.metrics_path, self.mcp_path));
+        }
+        Ok(())
+    }
+}
+
+fn parse_route_path(path: &str) -> Result<String, String> {
+    if path.starts_with('/') {
+        Ok(path.to_string())
+    } else {
+        Err(format!("'{}' must start with '/'", path))
+    }
+}
+
+/// Same as `parse_route_path`, the MCP endpoint owns every path under it so it cannot be the root
+fn parse_mcp_path(path: &str) -> Result<String, String> {
+    let path = parse_route_path(path)?;
+    match path.trim_end_matches('/') {
+        "" => Err("the MCP endpoint cannot be served at '/'".to_string()),
+        trimmed => Ok(trimmed.to_string()),
+    }
+}
+
+/// MCP endpoint at `mcp_path` plus the shared HTTP routes, without CORS or access log layers
+fn build_router(
+    metrics_path: &str,
+    mcp_path: &str,
+    api_key: Option<String>,
+    session_manager: Arc<BoundedSessionManager>,
+) -> axum::Router {
+    let service = StreamableHttpService::new(
+        || Ok(EligibilityEngine::new()),
+        session_manager,
+        Default::default(),
+    );
+
+    // MCP, metrics and evaluation require the API key when one is configured, probes never do
+    let protected = axum::Router::new()
+        .nest_service(mcp_path, service)
+        .merge(http::protected_routes(metrics_path));
+    http::with_api_key(protected, api_key).merge(http::public_routes())
+}
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    args.check_paths().map_err(|e| anyhow::anyhow!(e))?;
+
+    // Flushes exported spans when main returns
+    let telemetry = TelemetryGuard::from_env()?;
     tracing_subscriber::registry()
         .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "debug".to_string().into()),
+            tracing_subscriber::EnvFilter::try_new(&args.log_level)
+                .map_err(|e| anyhow::anyhow!("Invalid log level '{}': {}", args.log_level, e))?,
         )
-        .with(tracing_subscriber::fmt::layer())
+        .with(logging::fmt_layer(args.log_format, std::io::stdout, true))
+        .with(telemetry.as_ref().map(TelemetryGuard::layer))
         .init();
 
-    // Use environment variable or the static value
-    let bind_address = std::env::var("BIND_ADDRESS").unwrap_or_else(|_| BIND_ADDRESS.to_string());
-    tracing::info!("Starting streamable-http Eligibility Engine MCP server on {}", bind_address);
-    let service = StreamableHttpService::new(
-        || Ok(EligibilityEngine::new()),
-        LocalSessionManager::default().into(),
-        Default::default(),
+    if let Some(rules_path) = args.rules_path.clone() {
+        tracing::info!("Serving rule document {}", rules_path.display());
+        eligibility_engine::set_rules_path(rules_path);
+    }
+
+    http::init()?;
+    audit::init().await?;
+    webhook::init()?;
+    events::init()?;
+    // Fail fast on a rule document that cannot be parsed or gives wrong results
+    eligibility_engine::verify_rules().await.map_err(|e| anyhow::anyhow!(e))?;
+    let tls_paths = http::tls_paths()?;
+    if tls_paths.is_some() && args.bind_uds.is_some() {
+        anyhow::bail!("TLS is not supported on Unix domain sockets, unset TLS_CERT_PATH and TLS_KEY_PATH or {}", http::BIND_UDS_ENV);
+    }
+    let cors = http::cors_layer()?;
+    let access_log = http::access_log_layer()?;
+    let body_limit = http::body_limit_layer()?;
+    let compression = http::compression_layer()?;
+
+    let bind_address = http::parse_bind_address(&args.bind)?;
+    match &args.bind_uds {
+        Some(socket_path) => tracing::info!(
+            "Starting streamable-http Eligibility Engine MCP server on unix:{} at {}", socket_path.display(), args.mcp_path
+        ),
+        None => tracing::info!("Starting streamable-http Eligibility Engine MCP server on {}{}", bind_address, args.mcp_path),
+    }
+    let session_limits = args.session_limits();
+    tracing::info!(
+        "Accepting up to {} MCP sessions, closed after {:?} without requests",
+        session_limits.max_sessions,
+        session_limits.idle_timeout
     );
+    let session_store = sessions::connect_store(session_limits.idle_timeout).await?;
+    let session_manager = Arc::new(BoundedSessionManager::with_store(
+        session_limits,
+        metrics::METRICS.active_sessions.clone(),
+        session_store,
+    ));
+    session_manager.clone().spawn_idle_sweeper();
+    let mut router = build_router(&args.metrics_path, &args.mcp_path, http::api_key(), session_manager).layer(body_limit);
+    if let Some(compression) = compression {
+        router = router.layer(compression);
+    }
 
-    let router = axum::Router::new()
-        .nest_service("/mcp", service)
-        .route("/metrics", axum::routing::get(metrics_handler))
-        .route("/health", axum::routing::get(health_handler));
+    // Outermost, so preflight requests are answered before authentication
+    if let Some(cors) = cors {
+        router = router.layer(cors);
+    }
+    // Wraps everything else, so rejected and preflight requests are logged too
+    if let Some(access_log) = access_log {
+        router = router.layer(access_log);
+    }
 
-    let tcp_listener = tokio::net::TcpListener::bind(bind_address).await?;
-    let _ = axum::serve(tcp_listener, router)
-        .with_graceful_shutdown(async { tokio::signal::ctrl_c().await.unwrap() })
-        .await;
-    Ok(())
-}
+    // Serve on the Unix domain socket when one is configured, TCP otherwise
+    if let Some(socket_path) = &args.bind_uds {
+        #[cfg(unix)]
+        return http::serve_unix_with_drain(socket_path, router).await;
+        #[cfg(not(unix))]
+        anyhow::bail!("{} {} is only supported on Unix", http::BIND_UDS_ENV, socket_path.display());
+    }
 
-/// Handler for the /metrics endpoint
-async fn metrics_handler() -> impl IntoResponse {
-    let output = metrics::METRICS.gather();
-    (StatusCode::OK, output)
+    // Serve HTTPS when certificate and key are configured, plain HTTP otherwise
+    if let Some(tls_paths) = tls_paths {
+        tracing::info!("TLS enabled with certificate {}", tls_paths.cert_path);
+        // Several rustls backends are linked in, so the one to use must be chosen explicitly
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let tls_config = axum_server::tls_rustls::RustlsConfig::from_pem_file(&tls_paths.cert_path, &tls_paths.key_path)
+            .await
+            .map_err(|e| anyhow::anyhow!("Cannot load TLS certificate or key: {}", e))?;
+
+        let handle = axum_server::Handle::new();
+        let shutdown_handle = handle.clone();
+        tokio::spawn(async move {
+            http::shutdown_signal().await;
+            shutdown_handle.graceful_shutdown(Some(http::shutdown_timeout()));
+        });
+
+        let listener = http::bind_listener(bind_address).await?.into_std()?;
+        axum_server::from_tcp_rustls(listener, tls_config)
+            .handle(handle)
+            .serve(router.into_make_service())
+            .await?;
+        return Ok(());
+    }
+
+    let tcp_listener = http::bind_listener(bind_address).await?;
+    http::serve_with_drain(tcp_listener, router).await
 }
 
-/// Handler for the /health endpoint
-async fn health_handler() -> impl IntoResponse {
-    let output = "OK";
-    (StatusCode::OK, output)
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+
+    fn test_session_manager() -> Arc<BoundedSessionManager> {
+        Arc::new(BoundedSessionManager::new(SessionLimits::default(), metrics::Gauge::new("test_active_sessions", "Open sessions").unwrap()))
+    }
+
+    /// Posts a JSON-RPC message to the MCP endpoint, as a streamable-http client would
+    async fn post_mcp(client: &reqwest::Client, url: &str, session_id: Option<&str>, message: Value) -> reqwest::Response {
+        let mut request = client
+            .post(url)
+            .header("accept", "application/json, text/event-stream")
+            .json(&message);
+        if let Some(session_id) = session_id {
+            request = request.header("mcp-session-id", session_id);
+        }
+        request.send().await.unwrap()
+    }
+
+    /// First JSON-RPC response carried by the `data:` lines of an SSE body
+    fn sse_response(body: &str) -> Value {
+        body.lines()
+            .filter_map(|line| line.strip_prefix("data:"))
+            .filter_map(|data| serde_json::from_str::<Value>(data.trim()).ok())
+            .find(|message| message.get("id").is_some())
+            .unwrap_or_else(|| panic!("no JSON-RPC response in {:?}", body))
+    }
+
+    #[tokio::test]
+    async fn test_custom_paths() {
+        use tower::ServiceExt;
+
+        let router = build_router("/internal/metrics", "/api/mcp", None, test_session_manager());
+        let get = |path: &str| axum::http::Request::get(path).body(axum::body::Body::empty()).unwrap();
+
+        #[cfg(feature = "metrics")]
+        {
+            let response = router.clone().oneshot(get("/internal/metrics")).await.unwrap();
+            assert_eq!(response.status(), axum::http::StatusCode::OK);
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            assert!(String::from_utf8(body.to_vec()).unwrap().contains("eligibility_build_info"));
+        }
+        assert_eq!(router.clone().oneshot(get("/metrics")).await.unwrap().status(), axum::http::StatusCode::NOT_FOUND);
+
+        // Only the MCP endpoint answers a GET without a session with something other than 404
+        assert_ne!(router.clone().oneshot(get("/api/mcp")).await.unwrap().status(), axum::http::StatusCode::NOT_FOUND);
+        assert_eq!(router.oneshot(get("/mcp")).await.unwrap().status(), axum::http::StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_path_validation() {
+        assert_eq!(parse_mcp_path("/api/mcp/"), Ok("/api/mcp".to_string()));
+        assert!(parse_mcp_path("mcp").unwrap_err().contains("must start with '/'"));
+        assert!(parse_mcp_path("/").is_err());
+
+        let args = |metrics_path: &str, mcp_path: &str| {
+            Args::parse_from(["mcp_server", "--metrics-path", metrics_path, "--mcp-path", mcp_path])
+        };
+        assert_eq!(args("/metrics", "/mcp").check_paths(), Ok(()));
+        assert_eq!(args("/mcpmetrics", "/mcp").check_paths(), Ok(()));
+        assert!(args("/mcp/metrics", "/mcp").check_paths().is_err());
+        assert!(args("/mcp", "/mcp/").check_paths().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tool_call_over_http() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            axum::serve(listener, build_router(http::DEFAULT_METRICS_PATH, MCP_PATH, None, test_session_manager())).await.unwrap();
+        });
+        let client = reqwest::Client::builder().timeout(std::time::Duration::from_secs(10)).build().unwrap();
+
+        let initialize = post_mcp(&client, &url, None, json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": "2025-03-26",
+                "capabilities": {},
+                "clientInfo": { "name": "test-client", "version": "1.0.0" }
+            }
+        })).await;
+        assert!(initialize.status().is_success());
+        let session_id = initialize.headers()["mcp-session-id"].to_str().unwrap().to_string();
+        let initialize = sse_response(&initialize.text().await.unwrap());
+        assert_eq!(initialize["result"]["serverInfo"]["name"], eligibility_engine::SERVER_NAME);
+
+        let initialized = post_mcp(&client, &url, Some(&session_id), json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/initialized"
+        })).await;
+        assert!(initialized.status().is_success());
+
+        let call = post_mcp(&client, &url, Some(&session_id), json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "tools/call",
+            "params": {
+                "name": "evaluate_unpaid_leave_eligibility",
+                "arguments": { "relationship": "mother", "situation": "illness", "is_single_parent": false }
+            }
+        })).await;
+        assert!(call.status().is_success());
+        let call = sse_response(&call.text().await.unwrap());
+        assert_eq!(call["result"]["isError"], false);
+        let text = call["result"]["content"][0]["text"].as_str().unwrap();
+        let response: Value = serde_json::from_str(text).unwrap();
+        assert_eq!(response["output"]["case"], "A");
+
+        let read = post_mcp(&client, &url, Some(&session_id), json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "resources/read",
+            "params": { "uri": eligibility_engine::RULES_RESOURCE_URI }
+        })).await;
+        let read = sse_response(&read.text().await.unwrap());
+        let document: Value = serde_json::from_str(read["result"]["contents"][0]["text"].as_str().unwrap()).unwrap();
+        assert!(document["rules"]["nodes"].is_array());
+    }
+}
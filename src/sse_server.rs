@@ -5,40 +5,61 @@ use tracing_subscriber::{
     {self},
 };
 mod common;
-use common::{eligibility_engine::EligibilityEngine, metrics};
-use axum::{response::IntoResponse, http::StatusCode};
+use common::{audit, eligibility_engine::{self, EligibilityEngine}, events, http, logging::{self, LogFormat}, telemetry::TelemetryGuard, webhook};
 
 const BIND_ADDRESS: &str = "127.0.0.1:8000";
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let log_format = LogFormat::from_env()?;
+    // Flushes exported spans when main returns
+    let telemetry = TelemetryGuard::from_env()?;
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| "debug".to_string().into()),
         )
-        .with(tracing_subscriber::fmt::layer())
+        .with(logging::fmt_layer(log_format, std::io::stdout, true))
+        .with(telemetry.as_ref().map(TelemetryGuard::layer))
         .init();
 
+    http::init()?;
+    audit::init().await?;
+    webhook::init()?;
+    events::init()?;
+    // Fail fast on a rule document that cannot be parsed or gives wrong results
+    eligibility_engine::verify_rules().await.map_err(|e| anyhow::anyhow!(e))?;
+
     // Use environment variable or the static value
-    let bind_address = std::env::var("BIND_ADDRESS").unwrap_or_else(|_| BIND_ADDRESS.to_string());
+    let bind_address = http::parse_bind_address(&http::bind_address(BIND_ADDRESS))?;
     tracing::info!("Starting sse Eligibility Engine MCP server on {}", bind_address);
     let config = SseServerConfig {
-        bind: bind_address.parse()?,
+        bind: bind_address,
         sse_path: "/sse".to_string(),
         post_path: "/message".to_string(),
         ct: tokio_util::sync::CancellationToken::new(),
         sse_keep_alive: None,
     };
 
-    let (sse_server, mut router) = SseServer::new(config);
+    let (sse_server, router) = SseServer::new(config);
 
-    // Add endpoints for metrics and health
-    router = router
-        .route("/metrics", axum::routing::get(metrics_handler))
-        .route("/health", axum::routing::get(health_handler));
+    // Add endpoints for metrics, health and plain REST evaluation.
+    // SSE, metrics and evaluation require the API key when one is configured, probes never do
+    let mut router = http::with_api_key(router.merge(http::protected_routes(http::DEFAULT_METRICS_PATH)), http::api_key())
+        .merge(http::public_routes())
+        .layer(http::body_limit_layer()?);
+    if let Some(compression) = http::compression_layer()? {
+        router = router.layer(compression);
+    }
+    // Outermost, so preflight requests are answered before authentication
+    if let Some(cors) = http::cors_layer()? {
+        router = router.layer(cors);
+    }
+    if let Some(access_log) = http::access_log_layer()? {
+        router = router.layer(access_log);
+    }
 
-    let listener = tokio::net::TcpListener::bind(sse_server.config.bind).await?;
+    let listener = http::bind_listener(sse_server.config.bind).await?;
 
     let ct = sse_server.config.ct.child_token();
 
@@ -47,7 +68,7 @@ async fn main() -> anyhow::Result<()> {
         tracing::info!("sse server cancelled");
     });
 
-    tokio::spawn(async move {
+    let server_task = tokio::spawn(async move {
         if let Err(e) = server.await {
             tracing::error!(error = %e, "sse server shutdown with error");
         }
@@ -55,19 +76,10 @@ async fn main() -> anyhow::Result<()> {
 
     let ct = sse_server.with_service(EligibilityEngine::new);
 
-    tokio::signal::ctrl_c().await?;
+    http::shutdown_signal().await;
     ct.cancel();
+    if tokio::time::timeout(http::shutdown_timeout(), server_task).await.is_err() {
+        http::log_abandoned_requests();
+    }
     Ok(())
 }
-
-/// Handler for the /metrics endpoint
-async fn metrics_handler() -> impl IntoResponse {
-    let output = metrics::METRICS.gather();
-    (StatusCode::OK, output)
-}
-
-/// Handler for the /health endpoint
-async fn health_handler() -> impl IntoResponse {
-    let output = "OK";
-    (StatusCode::OK, output)
-}
\ No newline at end of file
@@ -1,21 +1,132 @@
 use anyhow::Result;
+use clap::{Parser, Subcommand};
 
 use rmcp::{ServiceExt, transport::stdio};
-use tracing_subscriber::{self, EnvFilter};
+use std::path::PathBuf;
+use tracing_subscriber::{self, EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
 
 mod common;
-use common::eligibility_engine::EligibilityEngine;
+use common::{audit, bulk, config, eligibility_engine::{self, EligibilityEngine, UnpaidLeaveDirectParams, UnpaidLeaveError, UnpaidLeaveInput}, events, metrics, logging::{self, LogFormat}, telemetry::TelemetryGuard, webhook};
+
+/// MCP server over stdio for the unpaid leave eligibility rules. Configured through environment variables
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Runs a single evaluation and prints its JSON result instead of serving MCP, failing on invalid input
+    Eval(EvalArgs),
+
+    /// Evaluates every row of a CSV of inputs and writes a CSV of results instead of serving MCP
+    EvaluateCsv {
+        /// CSV file whose header names the input fields, e.g. relationship,situation,is_single_parent
+        input: PathBuf,
+
+        /// File to write the results to instead of stdout
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+}
+
+/// Input of a one-shot evaluation, the fields of evaluate_unpaid_leave_eligibility most often needed
+#[derive(Debug, clap::Args)]
+struct EvalArgs {
+    /// Relationship with the person cared for or the child, e.g. "mother" or "father"
+    #[arg(long)]
+    relationship: String,
+
+    /// Situation, e.g. "birth", "adoption" or "illness"
+    #[arg(long)]
+    situation: String,
+
+    /// The applicant is a single parent
+    #[arg(long)]
+    single_parent: bool,
+
+    /// Total number of children after the birth or adoption
+    #[arg(long)]
+    children: Option<f64>,
+
+    /// Language of the texts, "es" (default) or "en"
+    #[arg(long)]
+    locale: Option<String>,
+}
+
+impl EvalArgs {
+    fn params(self) -> UnpaidLeaveDirectParams {
+        UnpaidLeaveDirectParams {
+            input: UnpaidLeaveInput {
+                relationship: self.relationship,
+                situation: self.situation,
+                is_single_parent: self.single_parent,
+                total_children_after: self.children,
+            },
+            locale: self.locale,
+            ..Default::default()
+        }
+    }
+}
+
+/// Evaluates `args` and returns the pretty-printed response, or why the input could not be evaluated
+async fn evaluate_once(engine: &EligibilityEngine, args: EvalArgs) -> Result<String> {
+    match engine.evaluate(args.params()).await {
+        Ok(response) => Ok(serde_json::to_string_pretty(&response)?),
+        Err(UnpaidLeaveError::ValidationError(errors)) => Err(anyhow::anyhow!(
+            "Invalid input: {}",
+            errors.iter().map(|error| format!("{}: {}", error.path, error.message)).collect::<Vec<_>>().join("; ")
+        )),
+        Err(error) => Err(anyhow::anyhow!("Evaluation failed: {}", error)),
+    }
+}
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize the tracing subscriber with file and stdout logging
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env().add_directive(tracing::Level::DEBUG.into()))
-        .with_writer(std::io::stderr)
-        .with_ansi(false)
+    let args = Args::parse();
+
+    // Log to stderr, stdout carries the MCP protocol
+    let log_format = LogFormat::from_env()?;
+    // Flushes exported spans when main returns
+    let telemetry = TelemetryGuard::from_env()?;
+    tracing_subscriber::registry()
+        .with(EnvFilter::from_default_env().add_directive(tracing::Level::DEBUG.into()))
+        .with(logging::fmt_layer(log_format, std::io::stderr, false))
+        .with(telemetry.as_ref().map(TelemetryGuard::layer))
         .init();
 
-    tracing::info!("Starting Eligibility Engine MCP server using stdio transport");
+    if args.command.is_none() {
+        tracing::info!("Starting Eligibility Engine MCP server using stdio transport");
+    }
+
+    // Abort on an invalid configuration file rather than serving default amounts
+    config::load_config().map_err(|e| anyhow::anyhow!("{}", e))?;
+    metrics::request_duration_buckets().map_err(|e| anyhow::anyhow!(e))?;
+    audit::init().await?;
+    webhook::init()?;
+    events::init()?;
+    // Fail fast on a rule document that cannot be parsed or gives wrong results
+    eligibility_engine::verify_rules().await.map_err(|e| anyhow::anyhow!(e))?;
+
+    if let Some(Command::Eval(eval_args)) = args.command {
+        println!("{}", evaluate_once(&EligibilityEngine::new(), eval_args).await?);
+        return Ok(());
+    }
+    if let Some(Command::EvaluateCsv { input, output }) = args.command {
+        let reader = std::fs::File::open(&input).map_err(|e| anyhow::anyhow!("Cannot open {}: {}", input.display(), e))?;
+        let engine = EligibilityEngine::new();
+        let summary = match output {
+            Some(output) => {
+                let writer = std::fs::File::create(&output).map_err(|e| anyhow::anyhow!("Cannot create {}: {}", output.display(), e))?;
+                bulk::evaluate_csv(&engine, reader, writer).await?
+            }
+            None => bulk::evaluate_csv(&engine, reader, std::io::stdout().lock()).await?,
+        };
+        tracing::info!("Evaluated {} rows of {}, {} could not be evaluated", summary.evaluated, input.display(), summary.failed);
+        return Ok(());
+    }
 
     // Create an instance of our eligibility-engine router
     let service = EligibilityEngine::new().serve(stdio()).await.inspect_err(|e| {
@@ -24,4 +135,30 @@ async fn main() -> Result<()> {
 
     service.waiting().await?;
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval_args(args: &[&str]) -> EvalArgs {
+        match Args::parse_from(std::iter::once("stdio_server").chain(std::iter::once("eval")).chain(args.iter().copied())).command {
+            Some(Command::Eval(eval_args)) => eval_args,
+            other => panic!("Expected the eval subcommand, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_once() {
+        let engine = EligibilityEngine::new();
+        let args = eval_args(&["--relationship", "father", "--situation", "birth", "--single-parent", "--children", "1"]);
+        let response: serde_json::Value = serde_json::from_str(&evaluate_once(&engine, args).await.unwrap()).unwrap();
+        assert_eq!(response["output"]["case"], "E");
+        assert_eq!(response["output"]["monthly_benefit"], 500);
+
+        let args = eval_args(&["--relationship", "father", "--situation", "birht"]);
+        let error = evaluate_once(&engine, args).await.unwrap_err().to_string();
+        assert!(error.starts_with("Invalid input: "), "{}", error);
+        assert!(error.contains("birht"), "{}", error);
+    }
+}